@@ -0,0 +1,90 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use ipa_backend::models::Dialect;
+use ipa_backend::services::ipa::Transcription;
+use ipa_backend::services::scoring::{
+  DTWAligner, DtwConfig, FormantSynthesizer, MFCCExtractor, MfccConfig, QualityGate, QualityReport,
+  Reference, ScorerPool, ScoringInput, Window,
+};
+
+/// Words the synthesizer renders clearly enough to score; attempts cycle through them
+const WORDS: [&str; 5] = ["ˈnɑlmi", "ˈglæd", "ˈmɑnə", "ˈlɑmə", "ˈnoʊm"];
+
+/// A classroom's worth of submissions arriving together
+const CLASS_SIZE: usize = 32;
+
+fn synthesize(word: &str) -> (Transcription, Vec<f32>) {
+  let target = Transcription::parse(word);
+  let audio = FormantSynthesizer::for_dialect(&Dialect::GA)
+    .synthesize(&target)
+    .expect("benchmark words synthesize")
+    .audio;
+  (target, audio)
+}
+
+fn features(c: &mut Criterion) {
+  let (_, audio) = synthesize(WORDS[0]);
+  let mut extractor = MFCCExtractor::with_config(MfccConfig::with_deltas()).unwrap();
+  let mut group = c.benchmark_group("mfcc");
+  group.throughput(Throughput::Elements(audio.len() as u64));
+  group.bench_function("extract_features", |b| {
+    b.iter(|| extractor.extract_features(&audio).unwrap())
+  });
+  group.finish();
+}
+
+fn alignment(c: &mut Criterion) {
+  let mut extractor = MFCCExtractor::with_config(MfccConfig::with_deltas()).unwrap();
+  let learner = extractor.extract_features(&synthesize(WORDS[0]).1).unwrap();
+  let reference = extractor.extract_features(&synthesize(WORDS[3]).1).unwrap();
+  let dtw = DTWAligner::new(DtwConfig {
+    window: Window::Itakura { max_slope: 2.0 },
+    ..Default::default()
+  });
+  let mut group = c.benchmark_group("dtw");
+  group.throughput(Throughput::Elements(
+    (learner.len() * reference.len()) as u64,
+  ));
+  group.bench_function("align", |b| b.iter(|| dtw.align(&learner, &reference)));
+  group.finish();
+}
+
+/// Attempts scored per second as the pool grows, for a burst of submissions
+fn batch(c: &mut Criterion) {
+  let words: Vec<(Transcription, Vec<f32>)> = WORDS.iter().map(|w| synthesize(w)).collect();
+  let references: Vec<[Reference; 1]> = words.iter().map(|(_, a)| [Reference::new(a)]).collect();
+  let quality: Vec<QualityReport> = words
+    .iter()
+    .map(|(_, a)| QualityGate::default().check(a, None))
+    .collect();
+  // Each attempt is scored against another word's reference, as a poor attempt would be
+  let inputs: Vec<ScoringInput> = (0..CLASS_SIZE)
+    .map(|i| ScoringInput {
+      audio: &words[i % WORDS.len()].1,
+      quality: &quality[i % WORDS.len()],
+      target: &words[i % WORDS.len()].0,
+      dialect: &Dialect::GA,
+      references: &references[(i + 1) % WORDS.len()],
+      competitors: &[],
+    })
+    .collect();
+
+  let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+  let mut sizes = vec![1, 2, 4, cores];
+  sizes.retain(|&size| size <= cores);
+  sizes.dedup();
+
+  let mut group = c.benchmark_group("score_batch");
+  group.sample_size(10);
+  group.throughput(Throughput::Elements(CLASS_SIZE as u64));
+  for size in sizes {
+    let pool = ScorerPool::from_config("mfcc-dtw", None, None, size).unwrap();
+    group.bench_with_input(BenchmarkId::new("workers", size), &pool, |b, pool| {
+      b.iter(|| pool.score_batch(&inputs))
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, features, alignment, batch);
+criterion_main!(benches);
@@ -0,0 +1,4 @@
+-- Where voice activity detection found speech in the scored recording
+ALTER TABLE scores
+    ADD COLUMN speech_start_ms INTEGER CHECK (speech_start_ms >= 0),
+    ADD COLUMN speech_end_ms INTEGER CHECK (speech_end_ms >= speech_start_ms);
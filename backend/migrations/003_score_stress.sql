@@ -0,0 +1,2 @@
+-- Lexical stress placement: expected vs detected syllable, score and per-syllable prosody
+ALTER TABLE scores ADD COLUMN stress JSONB;
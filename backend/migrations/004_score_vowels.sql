@@ -0,0 +1,2 @@
+-- Learner and reference formants (F1-F3) for each target vowel
+ALTER TABLE scores ADD COLUMN vowels JSONB NOT NULL DEFAULT '[]';
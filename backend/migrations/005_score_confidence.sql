@@ -0,0 +1,4 @@
+-- Calibrated probability that the score matches a listener's judgement, and the evidence it was
+-- computed from, kept so the confidence model can be refitted against labelled scores
+ALTER TABLE scores ADD COLUMN confidence REAL CHECK (confidence >= 0 AND confidence <= 1);
+ALTER TABLE scores ADD COLUMN confidence_features JSONB;
@@ -0,0 +1,39 @@
+-- Speakers who recorded reference pronunciations
+CREATE TABLE reference_speakers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(255) NOT NULL,
+    gender VARCHAR(16) CHECK (gender IN ('female', 'male', 'other')),
+    birth_year INTEGER CHECK (birth_year > 1900),
+    region VARCHAR(255),
+    notes TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Several speakers' recordings of each dialect variant; learners are scored against all of them
+CREATE TABLE reference_recordings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    variant_id UUID NOT NULL REFERENCES dialect_variants(id) ON DELETE CASCADE,
+    speaker_id UUID NOT NULL REFERENCES reference_speakers(id) ON DELETE CASCADE,
+    audio_url TEXT NOT NULL,
+    -- Relative trust in the recording when combining speakers
+    weight REAL NOT NULL DEFAULT 1 CHECK (weight >= 0),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(variant_id, speaker_id)
+);
+
+CREATE INDEX idx_reference_recordings_variant_id ON reference_recordings (variant_id);
+
+-- Existing single reference clips become recordings by an unattributed speaker
+INSERT INTO reference_speakers (id, name, notes)
+SELECT uuid_generate_v4(), 'Unattributed', 'Original dialect variant audio'
+WHERE EXISTS (SELECT 1 FROM dialect_variants WHERE audio_url IS NOT NULL);
+
+INSERT INTO reference_recordings (variant_id, speaker_id, audio_url)
+SELECT dv.id, rs.id, dv.audio_url
+FROM dialect_variants dv
+CROSS JOIN reference_speakers rs
+WHERE dv.audio_url IS NOT NULL AND rs.name = 'Unattributed';
+
+-- Which reference recording the score's phoneme times and formants were taken from
+ALTER TABLE scores
+    ADD COLUMN reference_recording_id UUID REFERENCES reference_recordings(id) ON DELETE SET NULL;
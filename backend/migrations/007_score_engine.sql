@@ -0,0 +1,7 @@
+-- Which scoring engine and version produced each score, so scores from different engines are
+-- never compared as if they were alike. Earlier scores all came from the MFCC/DTW engine.
+ALTER TABLE scores
+    ADD COLUMN engine VARCHAR(64) NOT NULL DEFAULT 'mfcc-dtw',
+    ADD COLUMN engine_version VARCHAR(32);
+
+CREATE INDEX idx_scores_engine ON scores (engine, engine_version);
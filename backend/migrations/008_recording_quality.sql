@@ -0,0 +1,3 @@
+-- Outcome of the quality check run before scoring. Recordings that fail it get no score; the
+-- report lists what the learner should fix before trying again.
+ALTER TABLE recordings ADD COLUMN quality JSONB;
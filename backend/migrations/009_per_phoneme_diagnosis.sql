@@ -0,0 +1,8 @@
+-- Document the per-phoneme JSON now that it carries a diagnosis. Earlier rows lack the new keys
+-- and read as correct with nothing inserted.
+COMMENT ON COLUMN scores.per_phoneme IS
+    'Array, one object per target phoneme in transcription order: '
+    '{phoneme, start_ms, end_ms, score (0-100), '
+    'diagnosis: {kind: "correct"} | {kind: "substituted", produced: IPA or null} | {kind: "deleted"}, '
+    'inserted_before?: [{sound: IPA or null, start_ms, end_ms}], '
+    'inserted_after?: same, on the last phoneme only}. Times are ms into the recording.';
@@ -0,0 +1,3 @@
+-- Phoneme and syllable lengths against the reference, missing vowel length contrasts and
+-- speaking rate
+ALTER TABLE scores ADD COLUMN duration JSONB;
@@ -0,0 +1,7 @@
+-- Where each phoneme of the variant's transcription falls in a reference recording, so scoring
+-- need not re-segment it: array of {phoneme, start_ms, end_ms}, ms into the recording.
+-- 'aligned' boundaries are found automatically and redone when the transcription changes;
+-- 'editor' boundaries were set by hand and are never overwritten by alignment.
+ALTER TABLE reference_recordings ADD COLUMN phoneme_boundaries JSONB;
+ALTER TABLE reference_recordings ADD COLUMN boundaries_source VARCHAR(16)
+    CHECK (boundaries_source IN ('aligned', 'editor'));
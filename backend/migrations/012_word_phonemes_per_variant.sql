@@ -0,0 +1,22 @@
+-- Dialects segment the same word differently, so each dialect variant's transcription gets its
+-- own phoneme sequence. The table has never been filled, so there is nothing to attribute.
+DELETE FROM word_phonemes;
+ALTER TABLE word_phonemes DROP CONSTRAINT word_phonemes_pkey;
+ALTER TABLE word_phonemes
+    ADD COLUMN variant_id UUID NOT NULL REFERENCES dialect_variants(id) ON DELETE CASCADE;
+ALTER TABLE word_phonemes ADD PRIMARY KEY (variant_id, order_index);
+CREATE INDEX idx_word_phonemes_word_id ON word_phonemes (word_id);
+
+-- A transcription changed outside the application leaves its phonemes stale; drop them so
+-- scoring falls back to splitting the new IPA until they are stored again
+CREATE FUNCTION clear_stale_word_phonemes() RETURNS TRIGGER AS $$
+BEGIN
+    DELETE FROM word_phonemes WHERE variant_id = NEW.id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER dialect_variants_ipa_changed
+    AFTER UPDATE OF ipa ON dialect_variants
+    FOR EACH ROW WHEN (OLD.ipa IS DISTINCT FROM NEW.ipa)
+    EXECUTE FUNCTION clear_stale_word_phonemes();
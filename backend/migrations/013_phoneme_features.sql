@@ -0,0 +1,29 @@
+-- Articulatory features of each phoneme, so phonemes can be compared and queried by voicing,
+-- place, manner and vowel quality rather than by their free-text description. Consonants fill
+-- voiced, place and manner; vowels fill height, backness, rounded and tense. Existing rows are
+-- filled in by the application from its IPA feature table on startup.
+CREATE TYPE place_of_articulation AS ENUM (
+    'bilabial', 'labiodental', 'dental', 'alveolar', 'postalveolar', 'retroflex', 'palatal',
+    'velar', 'uvular', 'pharyngeal', 'glottal', 'labial_velar'
+);
+CREATE TYPE manner_of_articulation AS ENUM (
+    'plosive', 'nasal', 'trill', 'tap', 'fricative', 'lateral_fricative', 'affricate',
+    'approximant', 'lateral_approximant'
+);
+CREATE TYPE vowel_height AS ENUM (
+    'close', 'near_close', 'close_mid', 'mid', 'open_mid', 'near_open', 'open'
+);
+CREATE TYPE vowel_backness AS ENUM ('front', 'central', 'back');
+
+ALTER TABLE phonemes
+    ADD COLUMN voiced BOOLEAN,
+    ADD COLUMN place place_of_articulation,
+    ADD COLUMN manner manner_of_articulation,
+    ADD COLUMN height vowel_height,
+    ADD COLUMN backness vowel_backness,
+    ADD COLUMN rounded BOOLEAN,
+    ADD COLUMN tense BOOLEAN,
+    ADD CONSTRAINT phonemes_consonant_or_vowel CHECK (manner IS NULL OR height IS NULL);
+
+CREATE INDEX idx_phonemes_manner_place ON phonemes (manner, place);
+CREATE INDEX idx_phonemes_height_backness ON phonemes (height, backness);
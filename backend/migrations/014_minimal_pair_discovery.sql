@@ -0,0 +1,12 @@
+-- Minimal pairs are found per dialect, since a contrast in one accent may be a homophone in
+-- another. Pairs curated by editors have no dialect and apply to all of them; discovered pairs
+-- are replaced whenever their words are re-examined, curated ones are never touched.
+ALTER TABLE minimal_pairs
+    ADD COLUMN dialect dialect,
+    ADD COLUMN source VARCHAR(16) NOT NULL DEFAULT 'editor'
+        CHECK (source IN ('editor', 'discovered'));
+
+-- One row per pair of words and dialect, whichever way round they were inserted
+CREATE UNIQUE INDEX idx_minimal_pairs_words_dialect ON minimal_pairs (
+    LEAST(word_a_id, word_b_id), GREATEST(word_a_id, word_b_id), dialect
+);
@@ -0,0 +1,5 @@
+-- What a user may do beyond practising: tutors review learners' recordings, editors curate
+-- words, transcriptions and reference audio, and admins may do both
+CREATE TYPE user_role AS ENUM ('learner', 'tutor', 'editor', 'admin');
+
+ALTER TABLE users ADD COLUMN role user_role NOT NULL DEFAULT 'learner';
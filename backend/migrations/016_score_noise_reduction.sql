@@ -0,0 +1,5 @@
+-- The noise reduction applied before each recording was scored, so its alignment can be
+-- reproduced later. Earlier scores were all made without it.
+CREATE TYPE noise_reduction AS ENUM ('off', 'spectral_subtraction', 'wiener');
+
+ALTER TABLE scores ADD COLUMN noise_reduction noise_reduction NOT NULL DEFAULT 'off';
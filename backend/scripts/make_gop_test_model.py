@@ -0,0 +1,126 @@
+#!/usr/bin/env python3
+"""Write the tiny acoustic model used by the GOP engine's tests.
+
+The model is a nearest-prototype classifier over three phones: each frame's logit for phone p is
+the Gaussian log likelihood -|x - mu_p|^2 / (2 var) up to a shared constant, i.e. a MatMul and an
+Add followed by LogSoftmax. The prototypes are the mean 13-coefficient MFCCs (no deltas, no CMVN)
+of the synthetic phones built in src/services/scoring/gop.rs's tests, so rerun this script if the
+MFCC front end or those signals change.
+
+Only the standard library is used; the ONNX protobuf is encoded by hand.
+
+    python3 scripts/make_gop_test_model.py tests/fixtures/gop
+"""
+
+import json
+import os
+import struct
+import sys
+
+PHONES = ["u", "s", "ə"]
+
+MEANS = [
+    # u: harmonics at 130 Hz shaped by formants at 300 and 870 Hz
+    [-4.68, -3.81, 0.29, -2.93, -4.1, -0.65, 0.06, 0.69, -2.57, -2.69, -2.95, 0.12, 0.65],
+    # s: differentiated white noise
+    [3.03, -23.25, -3.21, -2.3, -0.72, -0.67, -0.04, -0.08, 0.04, -0.11, 0.13, 0.01, 0.0],
+    # ə: harmonics at 130 Hz shaped by formants at 500 and 1500 Hz
+    [-5.61, -7.1, -4.56, -4.4, -2.33, -1.98, -6.29, -3.86, 0.28, 3.89, 0.32, -1.09, -1.21],
+]
+
+# Shared variance; large enough that frames straddling two phones get mixed posteriors
+VARIANCE = 8.0
+
+FLOAT = 1
+ATTRIBUTE_INT = 2
+
+
+def varint(value):
+    out = bytearray()
+    while True:
+        byte = value & 0x7F
+        value >>= 7
+        if value:
+            out.append(byte | 0x80)
+        else:
+            out.append(byte)
+            return bytes(out)
+
+
+def field_varint(number, value):
+    return varint(number << 3) + varint(value)
+
+
+def field_bytes(number, payload):
+    if isinstance(payload, str):
+        payload = payload.encode("utf-8")
+    return varint(number << 3 | 2) + varint(len(payload)) + payload
+
+
+def tensor(name, dims, values):
+    return (
+        b"".join(field_varint(1, d) for d in dims)
+        + field_varint(2, FLOAT)
+        + field_bytes(8, name)
+        + field_bytes(9, struct.pack("<%df" % len(values), *values))
+    )
+
+
+def value_info(name, dims):
+    shape = b"".join(
+        field_bytes(1, field_bytes(2, d) if isinstance(d, str) else field_varint(1, d))
+        for d in dims
+    )
+    tensor_type = field_varint(1, FLOAT) + field_bytes(2, shape)
+    return field_bytes(1, name) + field_bytes(2, field_bytes(1, tensor_type))
+
+
+def node(op_type, inputs, outputs, attributes=b""):
+    return (
+        b"".join(field_bytes(1, i) for i in inputs)
+        + b"".join(field_bytes(2, o) for o in outputs)
+        + field_bytes(4, op_type)
+        + attributes
+    )
+
+
+def int_attribute(name, value):
+    # Negative int64s are encoded as ten-byte two's complement varints
+    return field_bytes(
+        5, field_bytes(1, name) + field_varint(3, value & (2**64 - 1)) + field_varint(20, ATTRIBUTE_INT)
+    )
+
+
+def model():
+    dim = len(MEANS[0])
+    weights = [MEANS[p][k] / VARIANCE for k in range(dim) for p in range(len(PHONES))]
+    bias = [-sum(m * m for m in mean) / (2 * VARIANCE) for mean in MEANS]
+
+    graph = (
+        field_bytes(1, node("MatMul", ["features", "weights"], ["projected"]))
+        + field_bytes(1, node("Add", ["projected", "bias"], ["logits"]))
+        + field_bytes(
+            1, node("LogSoftmax", ["logits"], ["log_posteriors"], int_attribute("axis", -1))
+        )
+        + field_bytes(2, "gop-test")
+        + field_bytes(5, tensor("weights", [dim, len(PHONES)], weights))
+        + field_bytes(5, tensor("bias", [len(PHONES)], bias))
+        + field_bytes(11, value_info("features", [1, "T", dim]))
+        + field_bytes(12, value_info("log_posteriors", [1, "T", len(PHONES)]))
+    )
+    opset = field_bytes(1, "") + field_varint(2, 13)
+    return field_varint(1, 7) + field_bytes(2, "make_gop_test_model") + field_bytes(7, graph) + field_bytes(8, opset)
+
+
+def main():
+    out = sys.argv[1] if len(sys.argv) > 1 else "tests/fixtures/gop"
+    os.makedirs(out, exist_ok=True)
+    with open(os.path.join(out, "model.onnx"), "wb") as f:
+        f.write(model())
+    with open(os.path.join(out, "model.json"), "w", encoding="utf-8") as f:
+        json.dump({"phones": PHONES, "deltas": False, "cmvn": "off"}, f, ensure_ascii=False, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,80 @@
+use anyhow::{bail, Context, Result};
+use std::env;
+use std::path::PathBuf;
+
+use ipa_backend::services::scoring::engine::scorer_from_config;
+use ipa_backend::services::scoring::evaluation::{evaluate, read_manifest};
+use ipa_backend::services::scoring::ConfidenceModel;
+
+const USAGE: &str = "Usage: eval <manifest.jsonl> [--engine NAME] [--gop-model-dir DIR] \
+                     [--fit-confidence FILE] [--json]";
+
+/// Fewer rated attempts than this cannot support a confidence fit
+const MIN_CONFIDENCE_SAMPLES: usize = 30;
+
+/// Score a tutor-rated corpus with one engine and report how closely it agrees with the tutors
+fn main() -> Result<()> {
+  // Engine settings default to the same variables the worker reads
+  let _ = dotenvy::dotenv();
+  let mut engine = env::var("SCORING_ENGINE").unwrap_or_else(|_| "mfcc-dtw".to_string());
+  let mut gop_model_dir = env::var("GOP_MODEL_DIR").ok().map(PathBuf::from);
+  let confidence_models = env::var("CONFIDENCE_MODELS").ok().map(PathBuf::from);
+  let mut fit_confidence = None;
+  let mut manifest = None;
+  let mut json = false;
+
+  let mut args = env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--engine" => engine = args.next().context(USAGE)?,
+      "--gop-model-dir" => gop_model_dir = Some(args.next().context(USAGE)?.into()),
+      "--fit-confidence" => fit_confidence = Some(PathBuf::from(args.next().context(USAGE)?)),
+      "--json" => json = true,
+      "-h" | "--help" => {
+        println!("{}", USAGE);
+        return Ok(());
+      }
+      flag if flag.starts_with("--") => bail!("Unknown option {}\n{}", flag, USAGE),
+      path if manifest.is_none() => manifest = Some(PathBuf::from(path)),
+      _ => bail!(USAGE),
+    }
+  }
+  let manifest = manifest.context(USAGE)?;
+
+  let rows = read_manifest(&manifest)?;
+  // Score with the coefficients the worker would use, so their calibration can be compared
+  let confidence = match &confidence_models {
+    Some(path) => ConfidenceModel::load(path, &engine)?,
+    None => None,
+  };
+  let mut scorer = scorer_from_config(&engine, gop_model_dir.as_deref(), confidence.as_ref())?;
+  let report = evaluate(scorer.as_mut(), &rows);
+
+  if json {
+    println!("{}", serde_json::to_string_pretty(&report)?);
+  } else {
+    print!("{}", report);
+  }
+
+  if let Some(path) = fit_confidence {
+    let samples = &report.confidence_samples;
+    if samples.len() < MIN_CONFIDENCE_SAMPLES {
+      bail!(
+        "Only {} rows were scored; fitting confidence needs at least {}",
+        samples.len(),
+        MIN_CONFIDENCE_SAMPLES
+      );
+    }
+    let fitted = ConfidenceModel::fit(samples);
+    fitted.save(&path, &engine)?;
+    eprintln!(
+      "Fitted confidence for {} on {} rows: calibration error {:.3}, was {:.3}; saved to {}",
+      engine,
+      samples.len(),
+      fitted.calibration_error(samples),
+      confidence.unwrap_or_default().calibration_error(samples),
+      path.display()
+    );
+  }
+  Ok(())
+}
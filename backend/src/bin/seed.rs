@@ -2,6 +2,10 @@ use anyhow::Result;
 use sqlx::Row;
 use std::env;
 
+use ipa_backend::jobs::worker::{Job, JobQueue, JobType};
+use ipa_backend::models::{CreateDialectVariant, Dialect};
+use ipa_backend::services::vocab;
+
 #[tokio::main]
 async fn main() -> Result<()> {
   // Load .env file if it exists
@@ -22,14 +26,52 @@ async fn main() -> Result<()> {
 
   // Seed data
   seed_users(&pool).await?;
-  seed_words(&pool).await?;
   seed_phonemes(&pool).await?;
+  vocab::backfill_features(&pool).await?;
+  seed_words(&pool).await?;
+  let backfilled = vocab::backfill_phonemes(&pool).await?;
+  if backfilled > 0 {
+    println!("🌱 Stored phonemes for {} existing variants", backfilled);
+  }
+  let pairs = vocab::discover_minimal_pairs(&pool, None).await?;
+  println!("🌱 Discovered or corrected {} minimal pairs", pairs);
+  seed_reference_segmentation(&pool).await?;
 
   println!("✅ Database seeded successfully!");
 
   Ok(())
 }
 
+/// Queue every reference recording not yet segmented, such as those carried over from variant
+/// audio, for the worker to align
+async fn seed_reference_segmentation(pool: &sqlx::PgPool) -> Result<()> {
+  let references = vocab::references_to_segment(pool, None).await?;
+  if references.is_empty() {
+    return Ok(());
+  }
+  let Ok(redis_url) = env::var("REDIS_URL") else {
+    println!(
+      "⚠️  REDIS_URL is not set; {} reference recordings will be segmented when first scored against",
+      references.len()
+    );
+    return Ok(());
+  };
+
+  let queue = JobQueue::new(&redis_url)?;
+  for &reference_recording_id in &references {
+    queue
+      .enqueue(Job::new(JobType::ReferenceSegmentation {
+        reference_recording_id,
+      }))
+      .await?;
+  }
+  println!(
+    "🌱 Queued segmentation of {} reference recordings",
+    references.len()
+  );
+  Ok(())
+}
+
 async fn seed_users(pool: &sqlx::PgPool) -> Result<()> {
   println!("🌱 Seeding users...");
 
@@ -38,7 +80,7 @@ async fn seed_users(pool: &sqlx::PgPool) -> Result<()> {
   let demo_password = "password123";
 
   sqlx::query(
-    "INSERT INTO users (email, pass_hash, name, dialect) VALUES ($1, $2, $3, $4) ON CONFLICT (email) DO NOTHING"
+    "INSERT INTO users (email, pass_hash, name, dialect, role) VALUES ($1, $2, $3, $4, 'admin') ON CONFLICT (email) DO NOTHING"
   )
   .bind("admin@example.com")
   .bind(admin_password)
@@ -91,15 +133,15 @@ async fn seed_words(pool: &sqlx::PgPool) -> Result<()> {
     if let Some(row) = result {
       let word_id: uuid::Uuid = row.get("id");
 
-      // Insert GA dialect variant
-      sqlx::query(
-              "INSERT INTO dialect_variants (word_id, dialect, ipa) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"
-          )
-          .bind(word_id)
-          .bind("GA")
-          .bind(ipa)
-          .execute(pool)
-          .await?;
+      // Insert GA dialect variant, with its phonemes
+      let variant = CreateDialectVariant {
+        dialect: Dialect::GA,
+        ipa: ipa.to_string(),
+        audio_url: None,
+        speaker_id: None,
+        video_url: None,
+      };
+      vocab::create_variant(&mut *pool.acquire().await?, word_id, &variant).await?;
     }
   }
 
@@ -124,6 +166,8 @@ async fn seed_phonemes(pool: &sqlx::PgPool) -> Result<()> {
     ("z", "voiced alveolar fricative"),
     ("ʃ", "voiceless postalveolar fricative"),
     ("ʒ", "voiced postalveolar fricative"),
+    ("tʃ", "voiceless postalveolar affricate"),
+    ("dʒ", "voiced postalveolar affricate"),
     ("h", "voiceless glottal fricative"),
     ("m", "bilabial nasal"),
     ("n", "alveolar nasal"),
@@ -144,6 +188,15 @@ async fn seed_phonemes(pool: &sqlx::PgPool) -> Result<()> {
     ("u", "close back rounded vowel"),
     ("ʌ", "open-mid back unrounded vowel"),
     ("ə", "mid central vowel"),
+    ("eɪ", "closing front diphthong, as in face"),
+    ("aɪ", "closing front diphthong, as in price"),
+    ("ɔɪ", "closing front diphthong, as in choice"),
+    ("aʊ", "closing back diphthong, as in mouth"),
+    ("oʊ", "closing back diphthong, as in goat (GA)"),
+    ("əʊ", "closing back diphthong, as in goat (RP)"),
+    ("ɪə", "centring diphthong, as in near (RP)"),
+    ("eə", "centring diphthong, as in square (RP)"),
+    ("ʊə", "centring diphthong, as in cure (RP)"),
   ];
 
   for (symbol, description) in phonemes {
@@ -1,10 +1,12 @@
 use anyhow::Result;
+use std::path::Path;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 // Import from the parent crate
 use ipa_backend::config::Config;
 use ipa_backend::db::create_pool;
 use ipa_backend::jobs::JobWorker;
+use ipa_backend::services::scoring::{ConfidenceModel, ScorerPool};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -23,8 +25,29 @@ async fn main() -> Result<()> {
   // Create database pool
   let pool = create_pool(&config.database_url).await?;
 
+  // Confidence coefficients fitted for this engine, if any have been
+  let confidence = match &config.confidence_models {
+    Some(path) => {
+      let model = ConfidenceModel::load(Path::new(path), &config.scoring_engine)?;
+      if model.is_none() {
+        tracing::warn!(
+          "{} has no confidence model for {}; using the default",
+          path,
+          config.scoring_engine
+        );
+      }
+      model
+    }
+    None => None,
+  };
+
   // Create and start job worker
-  let mut worker = JobWorker::new(&config.redis_url, pool)?;
+  let mut worker = JobWorker::new(&config.redis_url, pool)?.with_scorers(ScorerPool::from_config(
+    &config.scoring_engine,
+    config.gop_model_dir.as_deref().map(Path::new),
+    confidence.as_ref(),
+    config.scoring_workers,
+  )?);
 
   // Handle shutdown signal
   tokio::spawn(async {
@@ -15,6 +15,14 @@ pub struct Config {
   pub meilisearch_url: String,
   pub meilisearch_key: String,
   pub allow_dev_google_sso: bool,
+  /// Name of the pronunciation scoring engine the worker uses
+  pub scoring_engine: String,
+  /// Directory with `model.onnx` and `model.json` for the GOP engine
+  pub gop_model_dir: Option<String>,
+  /// Recordings the worker scores at once, each on its own engine and thread
+  pub scoring_workers: usize,
+  /// JSON file of confidence coefficients per engine, written by `eval --fit-confidence`
+  pub confidence_models: Option<String>,
 }
 
 impl Config {
@@ -41,6 +49,14 @@ impl Config {
         .unwrap_or_else(|_| "false".to_string())
         .parse()
         .unwrap_or(false),
+      scoring_engine: env::var("SCORING_ENGINE").unwrap_or_else(|_| "mfcc-dtw".to_string()),
+      gop_model_dir: env::var("GOP_MODEL_DIR").ok(),
+      scoring_workers: env::var("SCORING_WORKERS")
+        .ok()
+        .and_then(|workers| workers.parse().ok())
+        .filter(|&workers| workers > 0)
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get())),
+      confidence_models: env::var("CONFIDENCE_MODELS").ok(),
     })
   }
 }
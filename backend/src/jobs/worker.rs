@@ -1,14 +1,35 @@
-use anyhow::Result;
+use anyhow::{ensure, Result};
+use chrono::{DateTime, Utc};
 use deadpool_redis::redis::AsyncCommands;
 use deadpool_redis::{Config, Pool, Runtime};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::db::DbPool;
-use crate::services::scoring::PronunciationScorer;
+use crate::models::{Dialect, ReferenceRecording};
+use crate::services::ipa::Transcription;
+use crate::services::scoring::synth::MAX_SYNTHESIS_PHONEMES;
+use crate::services::scoring::{
+  check_boundaries, AudioProcessor, Denoiser, FormantSynthesizer, NoiseReduction, PhonemeBoundary,
+  PronunciationScore, PronunciationScorer, QualityGate, QualityReport, Reference, ScorerPool,
+  ScoringInput,
+};
+use crate::services::vocab;
+
+/// Where payloads that cannot be parsed as a [`Job`] are kept
+const DEAD_LETTER_QUEUE: &str = "job_queue:dead";
+
+/// Minimal-pair neighbours a recording is compared against; each costs another alignment
+const MAX_COMPETITORS: i64 = 8;
+
+/// Decoded reference recordings kept between jobs, about a second of audio each
+const REFERENCE_AUDIO_CACHE: usize = 256;
 
 /// Job types for the queue
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,10 +39,21 @@ pub enum JobType {
     word_id: Uuid,
     dialect: String,
     audio_url: String,
+    #[serde(default)]
+    noise_reduction: NoiseReduction,
   },
   SearchIndexUpdate {
     word_id: Uuid,
   },
+  /// Find the phoneme boundaries of a newly ingested reference recording
+  ReferenceSegmentation {
+    reference_recording_id: Uuid,
+  },
+  /// Find minimal pairs for a word that was added or whose transcription changed, or across the
+  /// whole vocabulary when `word_id` is empty
+  MinimalPairDiscovery {
+    word_id: Option<Uuid>,
+  },
 }
 
 /// Job structure
@@ -34,6 +66,18 @@ pub struct Job {
   pub max_retries: u32,
 }
 
+impl Job {
+  pub fn new(job_type: JobType) -> Self {
+    Self {
+      id: Uuid::new_v4(),
+      job_type,
+      created_at: chrono::Utc::now(),
+      retry_count: 0,
+      max_retries: 3,
+    }
+  }
+}
+
 /// Job result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JobResult {
@@ -46,10 +90,43 @@ pub enum JobResult {
 pub struct JobWorker {
   redis_pool: Pool,
   db_pool: DbPool,
-  scorer: PronunciationScorer,
+  /// Engines recordings are scored on; as many jobs run at once as it has engines
+  scorers: Arc<ScorerPool>,
+  quality_gate: Arc<QualityGate>,
+  /// The same words' references and neighbours are fetched job after job
+  reference_audio: Mutex<AudioCache>,
   running: bool,
 }
 
+/// A reference recording by id and creation time, so a recording stored again is fetched afresh
+/// rather than served from the cache
+type AudioKey = (Uuid, DateTime<Utc>);
+
+/// Decoded reference audio, dropping the oldest entry when full
+#[derive(Default)]
+struct AudioCache {
+  audio: HashMap<AudioKey, Arc<Vec<f32>>>,
+  order: VecDeque<AudioKey>,
+}
+
+impl AudioCache {
+  fn get(&self, key: &AudioKey) -> Option<Arc<Vec<f32>>> {
+    self.audio.get(key).cloned()
+  }
+
+  fn insert(&mut self, key: AudioKey, audio: Arc<Vec<f32>>) {
+    if self.audio.insert(key, audio).is_some() {
+      return;
+    }
+    self.order.push_back(key);
+    if self.order.len() > REFERENCE_AUDIO_CACHE {
+      if let Some(oldest) = self.order.pop_front() {
+        self.audio.remove(&oldest);
+      }
+    }
+  }
+}
+
 #[allow(dead_code)]
 impl JobWorker {
   pub fn new(redis_url: &str, db_pool: DbPool) -> Result<Self> {
@@ -59,11 +136,25 @@ impl JobWorker {
     Ok(Self {
       redis_pool,
       db_pool,
-      scorer: PronunciationScorer::new(),
+      scorers: Arc::new(ScorerPool::new(vec![Box::new(PronunciationScorer::new())])?),
+      quality_gate: Arc::new(QualityGate::default()),
+      reference_audio: Mutex::new(AudioCache::default()),
       running: false,
     })
   }
 
+  /// Score recordings with a different engine, or on several instances of it in parallel
+  pub fn with_scorers(mut self, scorers: ScorerPool) -> Self {
+    info!(
+      "Scoring with {} {} on {} thread(s)",
+      scorers.engine().name,
+      scorers.engine().version,
+      scorers.size()
+    );
+    self.scorers = Arc::new(scorers);
+    self
+  }
+
   /// Start the worker
   pub async fn start(&mut self) -> Result<()> {
     self.running = true;
@@ -93,58 +184,109 @@ impl JobWorker {
     self.running = false;
   }
 
-  /// Process the next job in the queue
+  /// Process the next batch of jobs in the queue: up to one per scoring engine, run concurrently
+  /// so a burst of submissions is scored across cores
   async fn process_next_job(&mut self) -> Result<bool> {
     let mut conn = self.redis_pool.get().await?;
 
     // Pop job from queue (blocking with timeout)
     let result: Option<String> = conn.blpop("job_queue", 1.0).await?;
+    let Some(first) = result else {
+      return Ok(false);
+    };
 
-    if let Some(job_json) = result {
-      let job: Job = serde_json::from_str(&job_json)?;
-      info!("Processing job: {:?}", job.id);
+    // Take whatever else is already waiting, without blocking
+    let mut batch = vec![first];
+    if let Some(more) = NonZeroUsize::new(self.scorers.size() - 1) {
+      let waiting: Option<Vec<String>> = conn.lpop("job_queue", Some(more)).await?;
+      batch.extend(waiting.unwrap_or_default());
+    }
 
-      match self.execute_job(&job).await {
-        Ok(result) => {
-          self.handle_job_result(&job, &result).await?;
-          info!("Job {} completed successfully", job.id);
-        }
+    // A malformed payload is set aside for inspection; it must not cost the rest of the batch
+    let mut jobs = Vec::with_capacity(batch.len());
+    for job_json in batch {
+      match serde_json::from_str::<Job>(&job_json) {
+        Ok(job) => jobs.push(job),
         Err(e) => {
-          error!("Job {} failed: {}", job.id, e);
-          self.handle_job_failure(&job, &e.to_string()).await?;
+          error!("Discarding malformed job payload: {}", e);
+          if let Err(e) = conn.rpush::<_, _, ()>(DEAD_LETTER_QUEUE, &job_json).await {
+            error!("Failed to dead-letter job payload: {}", e);
+          }
         }
       }
+    }
+    drop(conn);
 
-      return Ok(true);
+    let outcomes = futures::future::join_all(jobs.iter().map(|job| self.run_job(job))).await;
+    for (job, outcome) in jobs.iter().zip(outcomes) {
+      if let Err(e) = outcome {
+        error!("Failed to record outcome of job {}: {}", job.id, e);
+      }
     }
 
-    Ok(false)
+    Ok(true)
+  }
+
+  /// Execute a job and record its result or failure
+  async fn run_job(&self, job: &Job) -> Result<()> {
+    info!("Processing job: {:?}", job.id);
+
+    match self.execute_job(job).await {
+      Ok(result) => {
+        self.handle_job_result(job, &result).await?;
+        info!("Job {} completed successfully", job.id);
+      }
+      Err(e) => {
+        error!("Job {} failed: {}", job.id, e);
+        self.handle_job_failure(job, &e.to_string()).await?;
+      }
+    }
+
+    Ok(())
   }
 
   /// Execute a specific job
-  async fn execute_job(&mut self, job: &Job) -> Result<JobResult> {
+  async fn execute_job(&self, job: &Job) -> Result<JobResult> {
     match &job.job_type {
       JobType::PronunciationScoring {
         recording_id,
         word_id,
         dialect,
         audio_url,
+        noise_reduction,
       } => {
         self
-          .process_pronunciation_scoring(*recording_id, *word_id, dialect, audio_url)
+          .process_pronunciation_scoring(
+            *recording_id,
+            *word_id,
+            dialect,
+            audio_url,
+            *noise_reduction,
+          )
           .await
       }
       JobType::SearchIndexUpdate { word_id } => self.process_search_index_update(*word_id).await,
+      JobType::ReferenceSegmentation {
+        reference_recording_id,
+      } => {
+        self
+          .process_reference_segmentation(*reference_recording_id)
+          .await
+      }
+      JobType::MinimalPairDiscovery { word_id } => {
+        self.process_minimal_pair_discovery(*word_id).await
+      }
     }
   }
 
   /// Process pronunciation scoring job
   async fn process_pronunciation_scoring(
-    &mut self,
+    &self,
     recording_id: Uuid,
     word_id: Uuid,
     dialect: &str,
     audio_url: &str,
+    noise_reduction: NoiseReduction,
   ) -> Result<JobResult> {
     info!(
       "Processing pronunciation scoring for recording {}",
@@ -154,16 +296,120 @@ impl JobWorker {
     // Download audio file (simplified - in production, use proper S3 client)
     let audio_data = self.download_audio(audio_url).await?;
 
-    // Get reference audio for the word
-    let reference_audio = self.get_reference_audio(word_id, dialect).await?;
+    // Get the target phoneme sequence to align against
+    let target = self.get_target_transcription(word_id, dialect).await?;
+
+    // Recordings too poor to judge get reasons to try again instead of a misleading score
+    let expected_ms = FormantSynthesizer::new().duration_ms(&target).ok();
+    let gate = Arc::clone(&self.quality_gate);
+    let (audio_data, quality) = tokio::task::spawn_blocking(move || {
+      let quality = gate.check(&audio_data, expected_ms);
+      (audio_data, quality)
+    })
+    .await?;
+    self.save_recording_quality(recording_id, &quality).await?;
+    if !quality.passed() {
+      info!(
+        "Recording {} needs a retry: {:?}",
+        recording_id, quality.issues
+      );
+      return Ok(JobResult::Success {
+        data: serde_json::json!({
+            "recording_id": recording_id,
+            "status": "retry",
+            "retry_reasons": quality.retry_reasons()
+        }),
+      });
+    }
+
+    let parsed_dialect = dialect.parse::<Dialect>()?;
+
+    // Reference speakers and minimal-pair neighbours, for engines that compare against them
+    let (references, competitors) = if self.scorers.uses_reference_audio() {
+      let references = self.get_reference_recordings(word_id, dialect).await?;
+      let competitors = self.get_competitor_audio(word_id, dialect).await?;
+      (references, competitors)
+    } else {
+      (Vec::new(), Vec::new())
+    };
+    let boundaries = self.reference_boundaries(&references, &target).await?;
+
+    // Words nobody has recorded are scored against a synthesized reading of the transcription
+    let synthesize = self.scorers.uses_reference_audio() && references.is_empty();
+    if synthesize {
+      info!(
+        "No {} reference recordings for word {}; synthesizing one",
+        dialect, word_id
+      );
+      ensure!(
+        target.phonemes.len() <= MAX_SYNTHESIS_PHONEMES,
+        "Word {} has {} phonemes, too many to synthesize a reference for",
+        word_id,
+        target.phonemes.len()
+      );
+    }
+
+    // Synthesize and score off the async runtime, on the next free engine
+    let scorers = Arc::clone(&self.scorers);
+    let (score, references) = tokio::task::spawn_blocking(move || -> Result<_> {
+      let synthesized = if synthesize {
+        // The synthesizer knows exactly where it put each phoneme
+        let synthesis = FormantSynthesizer::for_dialect(&parsed_dialect).synthesize(&target)?;
+        let boundaries: Vec<PhonemeBoundary> = target
+          .phonemes
+          .iter()
+          .zip(&synthesis.spans)
+          .map(|(phoneme, span)| PhonemeBoundary {
+            phoneme: phoneme.clone(),
+            start_ms: span.start,
+            end_ms: span.end,
+          })
+          .collect();
+        Some((synthesis.audio, boundaries))
+      } else {
+        None
+      };
 
-    // Score pronunciation
-    let score = self
-      .scorer
-      .score_pronunciation(&audio_data, &reference_audio)?;
+      // The gate and confidence judge the recording as made; noise is only removed for scoring
+      let denoised = Denoiser::default().process(&audio_data, noise_reduction);
+      let reference_audio: Vec<Reference> = match &synthesized {
+        Some((audio, boundaries)) => vec![Reference::new(audio).with_boundaries(boundaries)],
+        None => references
+          .iter()
+          .zip(&boundaries)
+          .map(|((recording, audio), boundaries)| Reference {
+            audio: audio.as_slice(),
+            weight: recording.weight,
+            boundaries: boundaries.as_deref(),
+          })
+          .collect(),
+      };
+      let competitors: Vec<&[f32]> = competitors.iter().map(|audio| audio.as_slice()).collect();
+      let score = scorers.score(&ScoringInput {
+        audio: &denoised,
+        quality: &quality,
+        target: &target,
+        dialect: &parsed_dialect,
+        references: &reference_audio,
+        competitors: &competitors,
+      })?;
+      Ok((score, references))
+    })
+    .await??;
+    let reference_recording_id = score
+      .reference_index
+      .and_then(|i| references.get(i))
+      .map(|(recording, _)| recording.id);
 
     // Save score to database
-    self.save_pronunciation_score(recording_id, &score).await?;
+    self
+      .save_pronunciation_score(
+        recording_id,
+        reference_recording_id,
+        noise_reduction,
+        &score,
+      )
+      .await?;
 
     // Emit WebSocket notification
     self.notify_score_completion(recording_id, &score).await?;
@@ -173,7 +419,59 @@ impl JobWorker {
           "recording_id": recording_id,
           "overall_pct": score.overall_pct,
           "per_phoneme": score.per_phoneme,
-          "confidence": score.confidence
+          "speech": score.speech,
+          "stress": score.stress,
+          "duration": score.duration,
+          "vowels": score.vowels,
+          "confidence": score.confidence,
+          "confidence_features": score.confidence_features,
+          "reference_recording_id": reference_recording_id,
+          "synthesized_reference": synthesize,
+          "engine": score.engine
+      }),
+    })
+  }
+
+  /// Segment a reference recording against its variant's transcription when it is ingested, so
+  /// the first learner scored against it does not wait on alignment
+  async fn process_reference_segmentation(
+    &self,
+    reference_recording_id: Uuid,
+  ) -> Result<JobResult> {
+    let recording =
+      sqlx::query_as::<_, ReferenceRecording>("SELECT * FROM reference_recordings WHERE id = $1")
+        .bind(reference_recording_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+    let (word_id, dialect): (Uuid, String) =
+      sqlx::query_as("SELECT word_id, dialect::text FROM dialect_variants WHERE id = $1")
+        .bind(recording.variant_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+    let target = self.get_target_transcription(word_id, &dialect).await?;
+    let audio = self.reference_audio(&recording).await?;
+
+    let boundaries = self
+      .reference_boundaries(&[(recording, audio)], &target)
+      .await?
+      .pop()
+      .flatten();
+    Ok(JobResult::Success {
+      data: serde_json::json!({
+          "reference_recording_id": reference_recording_id,
+          "phoneme_boundaries": boundaries
+      }),
+    })
+  }
+
+  /// Compare transcriptions within each dialect and store the minimal pairs found
+  async fn process_minimal_pair_discovery(&self, word_id: Option<Uuid>) -> Result<JobResult> {
+    let changed = vocab::discover_minimal_pairs(&self.db_pool, word_id).await?;
+    info!("Discovered or corrected {} minimal pairs", changed);
+    Ok(JobResult::Success {
+      data: serde_json::json!({
+          "word_id": word_id,
+          "pairs_changed": changed
       }),
     })
   }
@@ -196,40 +494,261 @@ impl JobWorker {
     })
   }
 
-  /// Download audio file (simplified implementation)
-  async fn download_audio(&self, _url: &str) -> Result<Vec<f32>> {
-    // In production, this would download from S3/MinIO
-    // For now, return dummy audio data
-    Ok(vec![0.1; 16000]) // 1 second of audio at 16kHz
+  /// Download a recording and decode it to 16kHz mono off the async runtime
+  async fn download_audio(&self, url: &str) -> Result<Vec<f32>> {
+    // In production, this would go through the S3/MinIO client
+    let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    tokio::task::spawn_blocking(move || AudioProcessor::decode_audio(&bytes)).await?
+  }
+
+  /// A reference recording's decoded audio, from the cache when it was fetched recently
+  async fn reference_audio(&self, recording: &ReferenceRecording) -> Result<Arc<Vec<f32>>> {
+    let key = (recording.id, recording.created_at);
+    if let Some(audio) = self.reference_audio.lock().unwrap().get(&key) {
+      return Ok(audio);
+    }
+    let audio = Arc::new(self.download_audio(&recording.audio_url).await?);
+    self
+      .reference_audio
+      .lock()
+      .unwrap()
+      .insert(key, Arc::clone(&audio));
+    Ok(audio)
+  }
+
+  /// Reference speakers' recordings of a word in a dialect, with their decoded audio
+  async fn get_reference_recordings(
+    &self,
+    word_id: Uuid,
+    dialect: &str,
+  ) -> Result<Vec<(ReferenceRecording, Arc<Vec<f32>>)>> {
+    let recordings = sqlx::query_as::<_, ReferenceRecording>(
+      "SELECT rr.* FROM reference_recordings rr
+             JOIN dialect_variants dv ON dv.id = rr.variant_id
+             WHERE dv.word_id = $1 AND dv.dialect = $2::dialect
+             ORDER BY rr.created_at",
+    )
+    .bind(word_id)
+    .bind(dialect)
+    .fetch_all(&self.db_pool)
+    .await?;
+
+    let mut with_audio = Vec::with_capacity(recordings.len());
+    for recording in recordings {
+      let audio = self.reference_audio(&recording).await?;
+      with_audio.push((recording, audio));
+    }
+    Ok(with_audio)
+  }
+
+  /// Phoneme boundaries of each reference recording. Recordings not yet segmented, or whose
+  /// aligned boundaries no longer follow the transcription, are aligned now and the result
+  /// stored; an editor's boundaries are never replaced.
+  async fn reference_boundaries(
+    &self,
+    references: &[(ReferenceRecording, Arc<Vec<f32>>)],
+    target: &Transcription,
+  ) -> Result<Vec<Option<Vec<PhonemeBoundary>>>> {
+    let mut all = Vec::with_capacity(references.len());
+    for (recording, audio) in references {
+      let stored = recording
+        .phoneme_boundaries
+        .clone()
+        .and_then(|json| serde_json::from_value::<Vec<PhonemeBoundary>>(json).ok())
+        .filter(|boundaries| check_boundaries(boundaries, &target.phonemes).is_ok());
+      if stored.is_some() || target.phonemes.is_empty() {
+        all.push(stored);
+        continue;
+      }
+      if recording.boundaries_source.as_deref() == Some("editor") {
+        warn!(
+          "Edited boundaries of reference recording {} do not follow its transcription; ignoring them",
+          recording.id
+        );
+        all.push(None);
+        continue;
+      }
+
+      let (audio, segment_target) = (Arc::clone(audio), target.clone());
+      let boundaries = tokio::task::spawn_blocking(move || {
+        PronunciationScorer::new().segment_reference(&audio, &segment_target)
+      })
+      .await??;
+      self
+        .save_reference_boundaries(recording.id, &boundaries)
+        .await?;
+      all.push(Some(boundaries));
+    }
+    Ok(all)
+  }
+
+  /// Store automatically aligned boundaries, unless an editor has set them meanwhile
+  async fn save_reference_boundaries(
+    &self,
+    reference_recording_id: Uuid,
+    boundaries: &[PhonemeBoundary],
+  ) -> Result<()> {
+    sqlx::query(
+      "UPDATE reference_recordings SET phoneme_boundaries = $2, boundaries_source = 'aligned'
+             WHERE id = $1 AND boundaries_source IS DISTINCT FROM 'editor'",
+    )
+    .bind(reference_recording_id)
+    .bind(serde_json::to_value(boundaries)?)
+    .execute(&self.db_pool)
+    .await?;
+    Ok(())
+  }
+
+  /// Reference audio for up to [`MAX_COMPETITORS`] words that form a minimal pair with this one
+  /// in the dialect
+  async fn get_competitor_audio(&self, word_id: Uuid, dialect: &str) -> Result<Vec<Arc<Vec<f32>>>> {
+    // One recording of each neighbour, preferring neighbours that are in the most minimal pairs
+    let recordings = sqlx::query_as::<_, ReferenceRecording>(
+      "WITH competitors AS (
+               SELECT DISTINCT CASE WHEN word_a_id = $1 THEN word_b_id ELSE word_a_id END AS word_id
+               FROM minimal_pairs
+               WHERE (word_a_id = $1 OR word_b_id = $1)
+                 AND (dialect IS NULL OR dialect = $2::dialect)
+             ),
+             recordings AS (
+               SELECT DISTINCT ON (c.word_id) c.word_id, rr.id
+               FROM competitors c
+               JOIN dialect_variants dv ON dv.word_id = c.word_id AND dv.dialect = $2::dialect
+               JOIN reference_recordings rr ON rr.variant_id = dv.id
+               ORDER BY c.word_id, rr.created_at
+             )
+             SELECT rr.* FROM recordings r
+             JOIN reference_recordings rr ON rr.id = r.id
+             ORDER BY (SELECT COUNT(*) FROM minimal_pairs mp
+                       WHERE (mp.word_a_id = r.word_id OR mp.word_b_id = r.word_id)
+                         AND (mp.dialect IS NULL OR mp.dialect = $2::dialect)) DESC,
+                      r.word_id
+             LIMIT $3",
+    )
+    .bind(word_id)
+    .bind(dialect)
+    .bind(MAX_COMPETITORS)
+    .fetch_all(&self.db_pool)
+    .await?;
+
+    let mut competitors = Vec::with_capacity(recordings.len());
+    for recording in &recordings {
+      competitors.push(self.reference_audio(recording).await?);
+    }
+    Ok(competitors)
+  }
+
+  /// Get the target transcription for a word. Phonemes come from the dialect variant's
+  /// `word_phonemes` when present, otherwise from its IPA, which also supplies syllable stress.
+  async fn get_target_transcription(&self, word_id: Uuid, dialect: &str) -> Result<Transcription> {
+    let symbols: Vec<String> = sqlx::query_scalar(
+      "SELECT p.symbol FROM word_phonemes wp
+             JOIN phonemes p ON p.id = wp.phoneme_id
+             JOIN dialect_variants dv ON dv.id = wp.variant_id
+             WHERE wp.word_id = $1 AND dv.dialect = $2::dialect
+             ORDER BY wp.order_index",
+    )
+    .bind(word_id)
+    .bind(dialect)
+    .fetch_all(&self.db_pool)
+    .await?;
+
+    let variant_ipa: Option<String> = sqlx::query_scalar(
+      "SELECT ipa FROM dialect_variants WHERE word_id = $1 AND dialect = $2::dialect",
+    )
+    .bind(word_id)
+    .bind(dialect)
+    .fetch_optional(&self.db_pool)
+    .await?;
+    let from_ipa = variant_ipa.as_deref().map(Transcription::parse);
+
+    if symbols.is_empty() {
+      if from_ipa.is_none() {
+        warn!(
+          "No phonemes or {} transcription for word {}; skipping per-phoneme scores",
+          dialect, word_id
+        );
+      }
+      return Ok(from_ipa.unwrap_or_default());
+    }
+
+    // Stress marks only line up with the stored phonemes if both segment the word the same way
+    Ok(match from_ipa {
+      Some(transcription) if transcription.phonemes == symbols => transcription,
+      _ => Transcription::from_phonemes(symbols),
+    })
   }
 
-  /// Get reference audio for a word
-  async fn get_reference_audio(&self, _word_id: Uuid, _dialect: &str) -> Result<Vec<f32>> {
-    // In production, this would fetch from database and download from S3
-    // For now, return dummy reference audio
-    Ok(vec![0.1; 16000])
+  /// Keep the quality check's measurements with the recording
+  async fn save_recording_quality(
+    &self,
+    recording_id: Uuid,
+    quality: &QualityReport,
+  ) -> Result<()> {
+    sqlx::query("UPDATE recordings SET quality = $2 WHERE id = $1")
+      .bind(recording_id)
+      .bind(serde_json::to_value(quality)?)
+      .execute(&self.db_pool)
+      .await?;
+    Ok(())
   }
 
   /// Save pronunciation score to database
   async fn save_pronunciation_score(
     &self,
     recording_id: Uuid,
-    score: &crate::services::scoring::PronunciationScore,
+    reference_recording_id: Option<Uuid>,
+    noise_reduction: NoiseReduction,
+    score: &PronunciationScore,
   ) -> Result<()> {
     let per_phoneme_json = serde_json::to_value(&score.per_phoneme)?;
+    let stress_json = score
+      .stress
+      .as_ref()
+      .map(serde_json::to_value)
+      .transpose()?;
+    let duration_json = score
+      .duration
+      .as_ref()
+      .map(serde_json::to_value)
+      .transpose()?;
+    let vowels_json = serde_json::to_value(&score.vowels)?;
+    let confidence_features_json = serde_json::to_value(score.confidence_features)?;
 
     sqlx::query(
-      "INSERT INTO scores (recording_id, overall_pct, per_phoneme, latency_ms) 
-             VALUES ($1, $2, $3, $4) 
+      "INSERT INTO scores (recording_id, overall_pct, per_phoneme, latency_ms, speech_start_ms, speech_end_ms, stress, vowels, confidence, confidence_features, reference_recording_id, engine, engine_version, duration, noise_reduction) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) 
              ON CONFLICT (recording_id) DO UPDATE SET 
              overall_pct = EXCLUDED.overall_pct,
              per_phoneme = EXCLUDED.per_phoneme,
-             latency_ms = EXCLUDED.latency_ms",
+             latency_ms = EXCLUDED.latency_ms,
+             speech_start_ms = EXCLUDED.speech_start_ms,
+             speech_end_ms = EXCLUDED.speech_end_ms,
+             stress = EXCLUDED.stress,
+             vowels = EXCLUDED.vowels,
+             confidence = EXCLUDED.confidence,
+             confidence_features = EXCLUDED.confidence_features,
+             reference_recording_id = EXCLUDED.reference_recording_id,
+             engine = EXCLUDED.engine,
+             engine_version = EXCLUDED.engine_version,
+             duration = EXCLUDED.duration,
+             noise_reduction = EXCLUDED.noise_reduction",
     )
     .bind(recording_id)
     .bind(score.overall_pct as f64)
     .bind(per_phoneme_json)
     .bind(1000) // Dummy latency
+    .bind(score.speech.map(|s| s.start_ms as i32))
+    .bind(score.speech.map(|s| s.end_ms as i32))
+    .bind(stress_json)
+    .bind(vowels_json)
+    .bind(score.confidence)
+    .bind(confidence_features_json)
+    .bind(reference_recording_id)
+    .bind(score.engine.name)
+    .bind(score.engine.version)
+    .bind(duration_json)
+    .bind(noise_reduction)
     .execute(&self.db_pool)
     .await?;
 
@@ -240,7 +759,7 @@ impl JobWorker {
   async fn notify_score_completion(
     &self,
     recording_id: Uuid,
-    score: &crate::services::scoring::PronunciationScore,
+    score: &PronunciationScore,
   ) -> Result<()> {
     // In production, this would send WebSocket message
     info!(
@@ -390,6 +909,7 @@ mod tests {
         word_id: Uuid::new_v4(),
         dialect: "GA".to_string(),
         audio_url: "https://example.com/audio.wav".to_string(),
+        noise_reduction: NoiseReduction::Wiener,
       },
       created_at: chrono::Utc::now(),
       retry_count: 0,
@@ -400,5 +920,53 @@ mod tests {
     let deserialized: Job = serde_json::from_str(&json).unwrap();
 
     assert_eq!(job.id, deserialized.id);
+
+    // Jobs queued before noise reduction existed still deserialize, with it off
+    let mut legacy: serde_json::Value = serde_json::from_str(&json).unwrap();
+    legacy["job_type"]["PronunciationScoring"]
+      .as_object_mut()
+      .unwrap()
+      .remove("noise_reduction");
+    let legacy: Job = serde_json::from_value(legacy).unwrap();
+    assert!(matches!(
+      legacy.job_type,
+      JobType::PronunciationScoring {
+        noise_reduction: NoiseReduction::Off,
+        ..
+      }
+    ));
+  }
+
+  #[test]
+  fn test_minimal_pair_discovery_job_round_trips() {
+    for word_id in [Some(Uuid::new_v4()), None] {
+      let job = Job::new(JobType::MinimalPairDiscovery { word_id });
+      let json = serde_json::to_string(&job).unwrap();
+      let deserialized: Job = serde_json::from_str(&json).unwrap();
+      assert!(matches!(
+        deserialized.job_type,
+        JobType::MinimalPairDiscovery { word_id: w } if w == word_id
+      ));
+      assert_eq!(deserialized.retry_count, 0);
+    }
+  }
+
+  #[test]
+  fn test_audio_cache_drops_the_oldest_recording() {
+    let mut cache = AudioCache::default();
+    let created_at = Utc::now();
+    let keys: Vec<AudioKey> = (0..=REFERENCE_AUDIO_CACHE)
+      .map(|_| (Uuid::new_v4(), created_at))
+      .collect();
+    for (n, key) in keys.iter().enumerate() {
+      cache.insert(*key, Arc::new(vec![n as f32]));
+    }
+    assert!(cache.get(&keys[0]).is_none());
+    assert_eq!(cache.get(&keys[1]).as_deref(), Some(&vec![1.0]));
+    assert_eq!(cache.audio.len(), REFERENCE_AUDIO_CACHE);
+
+    // Storing a recording again does not count twice towards the limit
+    cache.insert(keys[1], Arc::new(vec![1.0]));
+    assert_eq!(cache.order.len(), REFERENCE_AUDIO_CACHE);
   }
 }
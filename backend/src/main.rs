@@ -44,6 +44,16 @@ async fn main() -> anyhow::Result<()> {
   // Run migrations
   sqlx::migrate!("./migrations").run(&pool).await?;
 
+  // Store phonemes for variants that have none yet, and features for phonemes without any
+  let backfilled = services::vocab::backfill_phonemes(&pool).await?;
+  if backfilled > 0 {
+    tracing::info!("Stored phonemes for {} dialect variants", backfilled);
+  }
+  let featured = services::vocab::backfill_features(&pool).await?;
+  if featured > 0 {
+    tracing::info!("Stored articulatory features for {} phonemes", featured);
+  }
+
   // Build application
   let app = create_app(pool, config).await?;
 
@@ -10,7 +10,7 @@ pub use practice::*;
 pub use user::*;
 pub use word::*;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "dialect", rename_all = "UPPERCASE")]
 pub enum Dialect {
   GA,
@@ -25,6 +25,15 @@ pub enum Dialect {
   WA,
 }
 
+impl std::str::FromStr for Dialect {
+  type Err = serde_json::Error;
+
+  /// Parse the same codes the API and database use, e.g. `GA`
+  fn from_str(code: &str) -> Result<Self, Self::Err> {
+    serde_json::from_value(serde_json::Value::String(code.to_string()))
+  }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "part_of_speech", rename_all = "lowercase")]
 pub enum PartOfSpeech {
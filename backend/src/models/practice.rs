@@ -1,4 +1,5 @@
 use super::Dialect;
+use crate::services::scoring::{ImageFormat, NoiseReduction};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -24,6 +25,8 @@ pub struct Recording {
   pub dialect: Dialect,
   pub media_url: String,
   pub duration_ms: i32,
+  /// Quality check report; `None` until the recording has been processed
+  pub quality: Option<serde_json::Value>,
   pub created_at: DateTime<Utc>,
 }
 
@@ -35,6 +38,17 @@ pub struct Score {
   pub overall_pct: rust_decimal::Decimal,
   pub per_phoneme: serde_json::Value,
   pub latency_ms: i32,
+  pub speech_start_ms: Option<i32>,
+  pub speech_end_ms: Option<i32>,
+  pub stress: Option<serde_json::Value>,
+  pub duration: Option<serde_json::Value>,
+  pub vowels: serde_json::Value,
+  pub confidence: Option<f32>,
+  pub confidence_features: Option<serde_json::Value>,
+  pub reference_recording_id: Option<Uuid>,
+  /// Scoring engine and version; version is unknown for scores from before it was recorded
+  pub engine: String,
+  pub engine_version: Option<String>,
   pub created_at: DateTime<Utc>,
 }
 
@@ -59,6 +73,9 @@ pub struct ScoreRequest {
   pub recording_id: Uuid,
   pub word_id: Uuid,
   pub dialect: Dialect,
+  /// Background noise removal applied before scoring; off unless asked for
+  #[serde(default)]
+  pub noise_reduction: NoiseReduction,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,17 +83,47 @@ pub struct ScoreResponse {
   pub status: ScoreStatus,
   pub overall_pct: Option<rust_decimal::Decimal>,
   pub per_phoneme: Option<serde_json::Value>,
+  pub speech_start_ms: Option<i32>,
+  pub speech_end_ms: Option<i32>,
+  pub stress: Option<serde_json::Value>,
+  pub duration: Option<serde_json::Value>,
+  pub vowels: Option<serde_json::Value>,
+  pub confidence: Option<f32>,
+  pub engine: Option<String>,
+  pub engine_version: Option<String>,
+  /// Why the recording could not be scored, when `status` is `Retry`
+  pub retry_reasons: Option<serde_json::Value>,
   pub job_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ScoreStatus {
   Pending,
   Processing,
   Completed,
+  /// The recording was too poor to score; the learner should record again
+  Retry,
   Failed,
 }
 
+/// A transcription to render with the built-in synthesizer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SynthesisQuery {
+  pub ipa: String,
+  pub dialect: Dialect,
+}
+
+/// How to draw a recording's waveform and spectrogram
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisualizationQuery {
+  #[serde(default)]
+  pub format: ImageFormat,
+  /// Also draw the reference recording, time-aligned to the learner's
+  #[serde(default)]
+  pub reference: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageTick {
   pub active_ms: i32,
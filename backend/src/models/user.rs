@@ -13,9 +13,35 @@ pub struct User {
   pub name: String,
   pub avatar_url: Option<String>,
   pub dialect: Dialect,
+  pub role: Role,
   pub created_at: DateTime<Utc>,
 }
 
+/// What a user may do beyond practising their own words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+  Learner,
+  /// Reviews learners' recordings and scores
+  Tutor,
+  /// Curates words, transcriptions and reference audio
+  Editor,
+  Admin,
+}
+
+impl Role {
+  /// Whether the user may change words, transcriptions and reference recordings
+  pub fn edits_vocabulary(self) -> bool {
+    matches!(self, Role::Editor | Role::Admin)
+  }
+
+  /// Whether the user may see other learners' recordings and scores
+  pub fn reviews_recordings(self) -> bool {
+    matches!(self, Role::Tutor | Role::Admin)
+  }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateUser {
   pub email: String,
@@ -1,4 +1,6 @@
 use super::{Dialect, PartOfSpeech};
+use crate::services::articulation::ArticulatoryFeatures;
+use crate::services::scoring::PhonemeBoundary;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -25,17 +27,88 @@ pub struct DialectVariant {
   pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReferenceSpeaker {
+  pub id: Uuid,
+  pub name: String,
+  pub gender: Option<String>,
+  pub birth_year: Option<i32>,
+  pub region: Option<String>,
+  pub notes: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReferenceRecording {
+  pub id: Uuid,
+  pub variant_id: Uuid,
+  pub speaker_id: Uuid,
+  pub audio_url: String,
+  pub weight: f32,
+  /// Array of [`PhonemeBoundary`], once the recording has been segmented
+  pub phoneme_boundaries: Option<serde_json::Value>,
+  /// `aligned` or `editor`
+  pub boundaries_source: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// A speaker who records reference pronunciations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateReferenceSpeaker {
+  pub name: String,
+  /// `female`, `male` or `other`
+  pub gender: Option<String>,
+  pub birth_year: Option<i32>,
+  pub region: Option<String>,
+  pub notes: Option<String>,
+}
+
+/// Another speaker's recording of a dialect variant, to score learners against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateReferenceRecording {
+  pub speaker_id: Uuid,
+  pub audio_url: String,
+  /// Relative trust in the recording when combining speakers; 1 when absent
+  pub weight: Option<f32>,
+}
+
+/// A reference recording's phoneme boundaries, for highlighting each sound during playback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceBoundaries {
+  pub reference_recording_id: Uuid,
+  /// `aligned` or `editor`; absent until the recording has been segmented
+  pub source: Option<String>,
+  pub phonemes: Vec<PhonemeBoundary>,
+}
+
+/// Boundaries set by an editor, one per phoneme of the variant's transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateReferenceBoundaries {
+  pub phonemes: Vec<PhonemeBoundary>,
+}
+
+/// A corrected transcription for a dialect variant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateVariantIpa {
+  pub ipa: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Phoneme {
   pub id: Uuid,
   pub symbol: String,
   pub description: Option<String>,
+  #[sqlx(flatten)]
+  #[serde(flatten)]
+  pub features: ArticulatoryFeatures,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 #[allow(dead_code)]
 pub struct WordPhoneme {
   pub word_id: Uuid,
+  /// Dialect variant whose transcription the phoneme comes from
+  pub variant_id: Uuid,
   pub phoneme_id: Uuid,
   pub order_index: i32,
 }
@@ -45,7 +118,12 @@ pub struct MinimalPair {
   pub id: Uuid,
   pub word_a_id: Uuid,
   pub word_b_id: Uuid,
+  /// The contrasting segments, word A's first, as `θ/s`
   pub phoneme_diff: String,
+  /// Dialect the words contrast in; `None` for curated pairs, which hold in every dialect
+  pub dialect: Option<Dialect>,
+  /// `editor` for curated pairs, `discovered` for those found by comparing transcriptions
+  pub source: String,
   pub created_at: DateTime<Utc>,
 }
 
@@ -73,7 +151,11 @@ pub struct CreateWord {
 pub struct CreateDialectVariant {
   pub dialect: Dialect,
   pub ipa: String,
+  /// Reference recording of the variant, stored as one of its reference speakers' recordings
   pub audio_url: Option<String>,
+  /// Speaker of `audio_url`; the recording is unattributed when absent
+  #[serde(default)]
+  pub speaker_id: Option<Uuid>,
   pub video_url: Option<String>,
 }
 
@@ -85,3 +167,19 @@ pub struct VocabSearchQuery {
   pub page: Option<i64>,
   pub limit: Option<i64>,
 }
+
+/// Which dialect to compare pronunciations in, and how many words to return
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarWordsQuery {
+  pub dialect: Dialect,
+  pub limit: Option<i64>,
+}
+
+/// A word that sounds like another, with the phoneme distance between their transcriptions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarWord {
+  #[serde(flatten)]
+  pub word: Word,
+  pub ipa: String,
+  pub distance: f32,
+}
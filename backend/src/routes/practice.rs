@@ -1,18 +1,36 @@
 use axum::{
-  extract::{Path, State},
-  response::Json,
+  extract::{Path, Query, State},
+  http::{header, StatusCode},
+  response::{IntoResponse, Json},
   routing::{get, post},
   Router,
 };
 
 use crate::config::Config;
 use crate::db::DbPool;
-use crate::models::{ScoreRequest, ScoreResponse, TimeCapSettings, UsageTick};
+use crate::models::{
+  Dialect, Recording, Score, ScoreRequest, ScoreResponse, ScoreStatus, SynthesisQuery,
+  TimeCapSettings, UsageTick, User, VisualizationQuery,
+};
+use crate::services::ipa::Transcription;
+use crate::services::scoring::denoise::{Denoiser, NoiseReduction};
+use crate::services::scoring::engine::{alignment_scorer, MFCC_DTW};
+use crate::services::scoring::synth::MAX_SYNTHESIS_PHONEMES;
+use crate::services::scoring::vowel_chart::{vowel_chart, VowelChart};
+use crate::services::scoring::{
+  AudioProcessor, FormantSynthesizer, PhonemeScore, QualityReport, Renderer,
+};
 
 pub fn router() -> Router<(DbPool, Config)> {
   Router::new()
     .route("/score", post(score_recording))
     .route("/score/:recording_id", get(get_score))
+    .route(
+      "/recordings/:recording_id/visualization",
+      get(visualize_recording),
+    )
+    .route("/vowel-chart/:dialect", get(get_vowel_chart))
+    .route("/synthesize", get(synthesize_reference))
     .route("/usage/tick", post(usage_tick))
     .route("/settings/timecap", post(update_timecap))
 }
@@ -26,21 +44,265 @@ async fn score_recording(
     status: crate::models::ScoreStatus::Pending,
     overall_pct: None,
     per_phoneme: None,
+    speech_start_ms: None,
+    speech_end_ms: None,
+    stress: None,
+    duration: None,
+    vowels: None,
+    confidence: None,
+    engine: None,
+    engine_version: None,
+    retry_reasons: None,
     job_id: Some("placeholder".to_string()),
   })
 }
 
+/// A recording's score once it has been scored, the reasons to record again if it failed the
+/// quality check, or where it stands in the queue otherwise
 async fn get_score(
-  State((_pool, _config)): State<(DbPool, Config)>,
-  Path(_recording_id): Path<uuid::Uuid>,
-) -> Json<ScoreResponse> {
-  // TODO: Implement score retrieval
-  Json(ScoreResponse {
-    status: crate::models::ScoreStatus::Pending,
+  State((pool, _config)): State<(DbPool, Config)>,
+  user: User,
+  Path(recording_id): Path<uuid::Uuid>,
+) -> Result<Json<ScoreResponse>, StatusCode> {
+  let recording = visible_recording(&pool, &user, recording_id).await?;
+  let score = sqlx::query_as::<_, Score>("SELECT * FROM scores WHERE recording_id = $1")
+    .bind(recording_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  let unscored = |status, retry_reasons| ScoreResponse {
+    status,
     overall_pct: None,
     per_phoneme: None,
+    speech_start_ms: None,
+    speech_end_ms: None,
+    stress: None,
+    duration: None,
+    vowels: None,
+    confidence: None,
+    engine: None,
+    engine_version: None,
+    retry_reasons,
     job_id: None,
+  };
+
+  if let Some(score) = score {
+    return Ok(Json(ScoreResponse {
+      status: ScoreStatus::Completed,
+      overall_pct: Some(score.overall_pct),
+      per_phoneme: Some(score.per_phoneme),
+      speech_start_ms: score.speech_start_ms,
+      speech_end_ms: score.speech_end_ms,
+      stress: score.stress,
+      duration: score.duration,
+      vowels: Some(score.vowels),
+      confidence: score.confidence,
+      engine: Some(score.engine),
+      engine_version: score.engine_version,
+      retry_reasons: None,
+      job_id: None,
+    }));
+  }
+  // The worker checks quality before scoring, so a report without a score is either a retry or a
+  // recording still being scored
+  let response = match recording.quality {
+    Some(quality) => {
+      let report = serde_json::from_value::<QualityReport>(quality)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+      if report.passed() {
+        unscored(ScoreStatus::Processing, None)
+      } else {
+        let reasons = serde_json::to_value(report.retry_reasons())
+          .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        unscored(ScoreStatus::Retry, Some(reasons))
+      }
+    }
+    None => unscored(ScoreStatus::Pending, None),
+  };
+  Ok(Json(response))
+}
+
+/// A recording the user may see: their own, or any learner's for tutors and admins
+async fn visible_recording(
+  pool: &DbPool,
+  user: &User,
+  recording_id: uuid::Uuid,
+) -> Result<Recording, StatusCode> {
+  let recording = sqlx::query_as::<_, Recording>("SELECT * FROM recordings WHERE id = $1")
+    .bind(recording_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)?;
+  if recording.user_id == user.id || user.role.reviews_recordings() {
+    Ok(recording)
+  } else {
+    Err(StatusCode::FORBIDDEN)
+  }
+}
+
+/// Draw a recording's waveform and spectrogram with its scored phoneme boundaries, optionally
+/// over the reference it was scored against, warped onto the learner's timeline
+async fn visualize_recording(
+  State((pool, _config)): State<(DbPool, Config)>,
+  user: User,
+  Path(recording_id): Path<uuid::Uuid>,
+  Query(query): Query<VisualizationQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+  let recording = visible_recording(&pool, &user, recording_id).await?;
+  let score: Option<StoredScore> = sqlx::query_as(
+    "SELECT per_phoneme, reference_recording_id, engine, noise_reduction
+           FROM scores WHERE recording_id = $1",
+  )
+  .bind(recording_id)
+  .fetch_optional(&pool)
+  .await
+  .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  let per_phoneme = match &score {
+    Some((per_phoneme, ..)) => serde_json::from_value::<Vec<PhonemeScore>>(per_phoneme.clone())
+      .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    None => Vec::new(),
+  };
+
+  let audio = download_audio(&recording.media_url).await?;
+  let reference = match (&score, query.reference) {
+    (_, false) => None,
+    (Some((_, scored_against, ..)), true) => {
+      Some(scored_reference(&pool, &recording, *scored_against).await?)
+    }
+    (None, true) => Some(first_reference(&pool, &recording).await?),
+  };
+  // Align as the score was aligned: the same engine, on the recording as it was denoised
+  let (engine, noise_reduction) = match &score {
+    Some((.., engine, noise_reduction)) => (engine.clone(), *noise_reduction),
+    None => (MFCC_DTW.name.to_string(), NoiseReduction::Off),
+  };
+
+  let image = tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<u8>> {
+    let warp = match &reference {
+      Some(reference) => {
+        let denoised = Denoiser::default().process(&audio, noise_reduction);
+        Some(alignment_scorer(&engine)?.time_warp(&denoised, reference)?)
+      }
+      None => None,
+    };
+    let reference = reference.as_deref().zip(warp.as_ref());
+    Renderer::default()
+      .plot(&audio, &per_phoneme, reference)?
+      .encode(query.format)
   })
+  .await
+  .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+  .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  Ok(([(header::CONTENT_TYPE, query.format.content_type())], image))
+}
+
+/// A score's phonemes, the reference recording it was made against if any, the engine that
+/// made it and the noise reduction applied first
+type StoredScore = (
+  serde_json::Value,
+  Option<uuid::Uuid>,
+  String,
+  NoiseReduction,
+);
+
+/// The reference a score was made against: that reference recording, or when there was none, a
+/// reading synthesized from the dialect's transcription as the scorer would have synthesized it
+async fn scored_reference(
+  pool: &DbPool,
+  recording: &Recording,
+  scored_against: Option<uuid::Uuid>,
+) -> Result<Vec<f32>, StatusCode> {
+  let Some(reference_recording_id) = scored_against else {
+    return synthesized_reference(pool, recording).await;
+  };
+  let audio_url: String =
+    sqlx::query_scalar("SELECT audio_url FROM reference_recordings WHERE id = $1")
+      .bind(reference_recording_id)
+      .fetch_optional(pool)
+      .await
+      .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+      .ok_or(StatusCode::NOT_FOUND)?;
+  download_audio(&audio_url).await
+}
+
+/// For a recording not scored yet, the word's first reference recording in the recording's
+/// dialect, else one synthesized from that dialect's transcription
+async fn first_reference(pool: &DbPool, recording: &Recording) -> Result<Vec<f32>, StatusCode> {
+  let audio_url: Option<String> = sqlx::query_scalar(
+    "SELECT rr.audio_url FROM reference_recordings rr
+           JOIN dialect_variants dv ON dv.id = rr.variant_id
+           WHERE dv.word_id = $1 AND dv.dialect = $2
+           ORDER BY rr.created_at
+           LIMIT 1",
+  )
+  .bind(recording.word_id)
+  .bind(recording.dialect)
+  .fetch_optional(pool)
+  .await
+  .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  match audio_url {
+    Some(audio_url) => download_audio(&audio_url).await,
+    None => synthesized_reference(pool, recording).await,
+  }
+}
+
+/// A reading of the recording's word synthesized from its dialect's transcription
+async fn synthesized_reference(
+  pool: &DbPool,
+  recording: &Recording,
+) -> Result<Vec<f32>, StatusCode> {
+  let ipa: String =
+    sqlx::query_scalar("SELECT ipa FROM dialect_variants WHERE word_id = $1 AND dialect = $2")
+      .bind(recording.word_id)
+      .bind(recording.dialect)
+      .fetch_optional(pool)
+      .await
+      .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+      .ok_or(StatusCode::NOT_FOUND)?;
+  synthesize(recording.dialect, &ipa).await
+}
+
+/// Render a transcription off the async runtime. Anything longer than a word is refused, since
+/// synthesis time grows with its length.
+async fn synthesize(dialect: Dialect, ipa: &str) -> Result<Vec<f32>, StatusCode> {
+  let target = Transcription::parse(ipa);
+  if target.phonemes.len() > MAX_SYNTHESIS_PHONEMES {
+    return Err(StatusCode::UNPROCESSABLE_ENTITY);
+  }
+  let synthesis = tokio::task::spawn_blocking(move || {
+    FormantSynthesizer::for_dialect(&dialect).synthesize(&target)
+  })
+  .await
+  .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+  .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+  Ok(synthesis.audio)
+}
+
+/// Fetch stored audio and decode it to 16kHz mono off the async runtime
+async fn download_audio(url: &str) -> Result<Vec<f32>, StatusCode> {
+  // In production, this would go through the S3/MinIO client, as the scoring worker's does
+  let fetch = async { reqwest::get(url).await?.error_for_status()?.bytes().await };
+  let bytes = fetch.await.map_err(|_| StatusCode::BAD_GATEWAY)?;
+  tokio::task::spawn_blocking(move || AudioProcessor::decode_audio(&bytes))
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)
+}
+
+async fn get_vowel_chart(Path(dialect): Path<Dialect>) -> Json<Option<VowelChart>> {
+  Json(vowel_chart(&dialect))
+}
+
+/// Render a transcription as a WAV clip, for words nobody has recorded. Signed-in users only,
+/// since each request keeps a blocking thread busy.
+async fn synthesize_reference(
+  _user: User,
+  Query(query): Query<SynthesisQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+  let audio = synthesize(query.dialect, &query.ipa).await?;
+  let wav = AudioProcessor::encode_wav(&audio).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  Ok(([(header::CONTENT_TYPE, "audio/wav")], wav))
 }
 
 async fn usage_tick(
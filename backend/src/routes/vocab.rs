@@ -1,19 +1,39 @@
 use axum::{
   extract::{Path, Query, State},
+  http::StatusCode,
   response::Json,
-  routing::{get, post},
+  routing::{get, post, put},
   Router,
 };
 
 use crate::config::Config;
 use crate::db::DbPool;
-use crate::models::{VocabSearchQuery, WordWithVariants};
+use crate::jobs::worker::{Job, JobQueue, JobType};
+use crate::models::{
+  CreateReferenceRecording, CreateReferenceSpeaker, CreateWord, Phoneme, ReferenceBoundaries,
+  ReferenceRecording, ReferenceSpeaker, SimilarWord, SimilarWordsQuery, UpdateReferenceBoundaries,
+  UpdateVariantIpa, User, VocabSearchQuery, Word, WordWithVariants,
+};
+use crate::services::ipa::Transcription;
+use crate::services::scoring::{check_boundaries, PhonemeBoundary};
+use crate::services::vocab;
 
 pub fn router() -> Router<(DbPool, Config)> {
   Router::new()
     .route("/", get(search_vocab))
     .route("/:id", get(get_word))
+    .route("/:id/similar", get(get_similar_words))
     .route("/", post(create_word))
+    .route("/variants/:variant_id/ipa", put(update_variant_ipa))
+    .route(
+      "/variants/:variant_id/references",
+      post(add_reference_recording),
+    )
+    .route("/speakers", post(create_speaker))
+    .route(
+      "/references/:reference_recording_id/boundaries",
+      get(get_reference_boundaries).put(update_reference_boundaries),
+    )
 }
 
 async fn search_vocab(
@@ -32,10 +52,269 @@ async fn get_word(
   Json(None)
 }
 
+/// Words that sound most like this one in a dialect, for near-miss practice and search
+async fn get_similar_words(
+  State((pool, _config)): State<(DbPool, Config)>,
+  Path(word_id): Path<uuid::Uuid>,
+  Query(query): Query<SimilarWordsQuery>,
+) -> Result<Json<Vec<SimilarWord>>, StatusCode> {
+  let limit = query.limit.unwrap_or(10).clamp(1, 100) as usize;
+  vocab::similar_words(&pool, word_id, query.dialect, limit)
+    .await
+    .map(Json)
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Create a word with its dialect variants, storing the phonemes of each transcription
 async fn create_word(
-  State((_pool, _config)): State<(DbPool, Config)>,
-  Json(word_data): Json<WordWithVariants>,
-) -> Json<WordWithVariants> {
-  // TODO: Implement word creation
-  Json(word_data)
+  State((pool, config)): State<(DbPool, Config)>,
+  user: User,
+  Json(word_data): Json<CreateWord>,
+) -> Result<Json<WordWithVariants>, StatusCode> {
+  require_editor(&user)?;
+  let mut tx = pool
+    .begin()
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  let word = sqlx::query_as::<_, Word>(
+    "INSERT INTO words (text, language, pos, difficulty)
+           VALUES ($1, $2, $3, $4)
+           RETURNING *",
+  )
+  .bind(&word_data.text)
+  .bind(&word_data.language)
+  .bind(&word_data.pos)
+  .bind(word_data.difficulty)
+  .fetch_one(&mut *tx)
+  .await
+  .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
+  let mut variants = Vec::with_capacity(word_data.variants.len());
+  let mut phonemes: Vec<Phoneme> = Vec::new();
+  for variant in &word_data.variants {
+    let (created, variant_phonemes) = vocab::create_variant(&mut tx, word.id, variant)
+      .await
+      .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    variants.push(created);
+    for phoneme in variant_phonemes {
+      if !phonemes.iter().any(|p| p.id == phoneme.id) {
+        phonemes.push(phoneme);
+      }
+    }
+  }
+  tx.commit()
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  queue_minimal_pair_discovery(&config, word.id).await;
+  for variant in variants.iter().filter(|v| v.audio_url.is_some()) {
+    queue_variant_segmentation(&pool, &config, variant.id).await;
+  }
+
+  Ok(Json(WordWithVariants {
+    word,
+    variants,
+    phonemes,
+    minimal_pairs: vec![],
+  }))
+}
+
+/// Replace a variant's transcription, returning the phonemes stored for it
+async fn update_variant_ipa(
+  State((pool, config)): State<(DbPool, Config)>,
+  user: User,
+  Path(variant_id): Path<uuid::Uuid>,
+  Json(update): Json<UpdateVariantIpa>,
+) -> Result<Json<Vec<Phoneme>>, StatusCode> {
+  require_editor(&user)?;
+  if Transcription::parse(&update.ipa).phonemes.is_empty() {
+    return Err(StatusCode::UNPROCESSABLE_ENTITY);
+  }
+  let (variant, phonemes) = vocab::update_variant_ipa(&pool, variant_id, &update.ipa)
+    .await
+    .map_err(|e| match e.downcast_ref::<sqlx::Error>() {
+      Some(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND,
+      _ => StatusCode::INTERNAL_SERVER_ERROR,
+    })?;
+  queue_minimal_pair_discovery(&config, variant.word_id).await;
+
+  // Aligned boundaries of the old transcription no longer fit; re-segment before anyone is scored
+  queue_variant_segmentation(&pool, &config, variant.id).await;
+  Ok(Json(phonemes))
+}
+
+/// Record a speaker whose reference recordings learners can be scored against
+async fn create_speaker(
+  State((pool, _config)): State<(DbPool, Config)>,
+  user: User,
+  Json(speaker): Json<CreateReferenceSpeaker>,
+) -> Result<Json<ReferenceSpeaker>, StatusCode> {
+  require_editor(&user)?;
+  vocab::create_speaker(&pool, &speaker)
+    .await
+    .map(Json)
+    .map_err(|e| insert_status(&e))
+}
+
+/// Add another speaker's recording of a dialect variant; learners are scored against all of them
+async fn add_reference_recording(
+  State((pool, config)): State<(DbPool, Config)>,
+  user: User,
+  Path(variant_id): Path<uuid::Uuid>,
+  Json(recording): Json<CreateReferenceRecording>,
+) -> Result<Json<ReferenceRecording>, StatusCode> {
+  require_editor(&user)?;
+  let mut conn = pool
+    .acquire()
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  let created = vocab::add_reference_recording(&mut conn, variant_id, &recording)
+    .await
+    .map_err(|e| insert_status(&e))?;
+
+  // Segment it now, so the first learner scored against it does not wait on alignment
+  queue_jobs(
+    &config,
+    vec![JobType::ReferenceSegmentation {
+      reference_recording_id: created.id,
+    }],
+  )
+  .await;
+  Ok(Json(created))
+}
+
+/// Only editors and admins may change the vocabulary and its reference audio
+fn require_editor(user: &User) -> Result<(), StatusCode> {
+  if user.role.edits_vocabulary() {
+    Ok(())
+  } else {
+    Err(StatusCode::FORBIDDEN)
+  }
+}
+
+/// Status for an insert the database refused: a duplicate, a reference to a missing row, or a
+/// value outside its column's constraints
+fn insert_status(error: &anyhow::Error) -> StatusCode {
+  match error.downcast_ref::<sqlx::Error>() {
+    Some(sqlx::Error::Database(e)) if e.is_unique_violation() => StatusCode::CONFLICT,
+    Some(sqlx::Error::Database(e)) if e.is_foreign_key_violation() => StatusCode::NOT_FOUND,
+    Some(sqlx::Error::Database(e)) if e.is_check_violation() => StatusCode::UNPROCESSABLE_ENTITY,
+    _ => StatusCode::INTERNAL_SERVER_ERROR,
+  }
+}
+
+/// Re-examine a word's minimal pairs in the background
+async fn queue_minimal_pair_discovery(config: &Config, word_id: uuid::Uuid) {
+  queue_jobs(
+    config,
+    vec![JobType::MinimalPairDiscovery {
+      word_id: Some(word_id),
+    }],
+  )
+  .await;
+}
+
+/// Segment a variant's reference recordings in the background, except those an editor has
+/// segmented by hand
+async fn queue_variant_segmentation(pool: &DbPool, config: &Config, variant_id: uuid::Uuid) {
+  match vocab::references_to_segment(pool, Some(variant_id)).await {
+    Ok(references) => {
+      let jobs = references
+        .into_iter()
+        .map(|reference_recording_id| JobType::ReferenceSegmentation {
+          reference_recording_id,
+        })
+        .collect();
+      queue_jobs(config, jobs).await;
+    }
+    Err(e) => tracing::warn!(
+      "Could not find reference recordings of variant {} to segment: {}",
+      variant_id,
+      e
+    ),
+  }
+}
+
+/// Queue background work following a change that is already saved, so a queue failure is only
+/// logged; full runs of the same jobs pick the work up later
+async fn queue_jobs(config: &Config, jobs: Vec<JobType>) {
+  if jobs.is_empty() {
+    return;
+  }
+  let queue = match JobQueue::new(&config.redis_url) {
+    Ok(queue) => queue,
+    Err(e) => {
+      tracing::warn!("Could not connect to the job queue: {}", e);
+      return;
+    }
+  };
+  for job_type in jobs {
+    let description = format!("{:?}", job_type);
+    if let Err(e) = queue.enqueue(Job::new(job_type)).await {
+      tracing::warn!("Could not queue {}: {}", description, e);
+    }
+  }
+}
+
+/// Where each phoneme falls in a reference recording; empty until it has been segmented
+async fn get_reference_boundaries(
+  State((pool, _config)): State<(DbPool, Config)>,
+  Path(reference_recording_id): Path<uuid::Uuid>,
+) -> Result<Json<ReferenceBoundaries>, StatusCode> {
+  let recording =
+    sqlx::query_as::<_, ReferenceRecording>("SELECT * FROM reference_recordings WHERE id = $1")
+      .bind(reference_recording_id)
+      .fetch_optional(&pool)
+      .await
+      .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+      .ok_or(StatusCode::NOT_FOUND)?;
+  let phonemes = match recording.phoneme_boundaries {
+    Some(json) => serde_json::from_value::<Vec<PhonemeBoundary>>(json)
+      .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    None => Vec::new(),
+  };
+  Ok(Json(ReferenceBoundaries {
+    reference_recording_id,
+    source: recording.boundaries_source,
+    phonemes,
+  }))
+}
+
+/// Replace a reference recording's boundaries with an editor's. They must follow the variant's
+/// transcription in order, and alignment never overwrites them afterwards.
+async fn update_reference_boundaries(
+  State((pool, _config)): State<(DbPool, Config)>,
+  user: User,
+  Path(reference_recording_id): Path<uuid::Uuid>,
+  Json(update): Json<UpdateReferenceBoundaries>,
+) -> Result<Json<ReferenceBoundaries>, StatusCode> {
+  require_editor(&user)?;
+  let ipa: String = sqlx::query_scalar(
+    "SELECT dv.ipa FROM reference_recordings rr
+           JOIN dialect_variants dv ON dv.id = rr.variant_id
+           WHERE rr.id = $1",
+  )
+  .bind(reference_recording_id)
+  .fetch_optional(&pool)
+  .await
+  .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+  .ok_or(StatusCode::NOT_FOUND)?;
+  check_boundaries(&update.phonemes, &Transcription::parse(&ipa).phonemes)
+    .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
+  let json =
+    serde_json::to_value(&update.phonemes).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  sqlx::query(
+    "UPDATE reference_recordings SET phoneme_boundaries = $2, boundaries_source = 'editor'
+           WHERE id = $1",
+  )
+  .bind(reference_recording_id)
+  .bind(json)
+  .execute(&pool)
+  .await
+  .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+  Ok(Json(ReferenceBoundaries {
+    reference_recording_id,
+    source: Some("editor".to_string()),
+    phonemes: update.phonemes,
+  }))
 }
@@ -0,0 +1,657 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::services::ipa;
+use Backness::*;
+use Height::*;
+use Manner::*;
+use Place::*;
+
+/// Where in the vocal tract a consonant is made, front to back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "place_of_articulation", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Place {
+  Bilabial,
+  Labiodental,
+  Dental,
+  Alveolar,
+  Postalveolar,
+  Retroflex,
+  Palatal,
+  Velar,
+  Uvular,
+  Pharyngeal,
+  Glottal,
+  /// Made at both lips and velum at once, as /w/
+  LabialVelar,
+}
+
+/// How the airflow of a consonant is obstructed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "manner_of_articulation", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Manner {
+  Plosive,
+  Nasal,
+  Trill,
+  Tap,
+  Fricative,
+  LateralFricative,
+  Affricate,
+  Approximant,
+  LateralApproximant,
+}
+
+/// Vowel height, close to open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "vowel_height", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Height {
+  Close,
+  NearClose,
+  CloseMid,
+  Mid,
+  OpenMid,
+  NearOpen,
+  Open,
+}
+
+/// Vowel backness, front to back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "vowel_backness", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Backness {
+  Front,
+  Central,
+  Back,
+}
+
+/// Articulatory features of a phoneme. Consonants have voicing, place and manner; vowels have
+/// height, backness, rounding and tenseness. All are empty for symbols outside the IPA charts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct ArticulatoryFeatures {
+  pub voiced: Option<bool>,
+  pub place: Option<Place>,
+  pub manner: Option<Manner>,
+  pub height: Option<Height>,
+  pub backness: Option<Backness>,
+  pub rounded: Option<bool>,
+  pub tense: Option<bool>,
+}
+
+impl ArticulatoryFeatures {
+  const fn consonant(voiced: bool, place: Place, manner: Manner) -> Self {
+    Self {
+      voiced: Some(voiced),
+      place: Some(place),
+      manner: Some(manner),
+      height: None,
+      backness: None,
+      rounded: None,
+      tense: None,
+    }
+  }
+
+  const fn vowel(height: Height, backness: Backness, rounded: bool, tense: bool) -> Self {
+    Self {
+      voiced: Some(true),
+      place: None,
+      manner: None,
+      height: Some(height),
+      backness: Some(backness),
+      rounded: Some(rounded),
+      tense: Some(tense),
+    }
+  }
+
+  pub fn is_vowel(&self) -> bool {
+    self.height.is_some()
+  }
+
+  pub fn is_consonant(&self) -> bool {
+    self.manner.is_some()
+  }
+}
+
+const CONSONANTS: &[(char, ArticulatoryFeatures)] = &[
+  (
+    'p',
+    ArticulatoryFeatures::consonant(false, Bilabial, Plosive),
+  ),
+  (
+    'b',
+    ArticulatoryFeatures::consonant(true, Bilabial, Plosive),
+  ),
+  (
+    't',
+    ArticulatoryFeatures::consonant(false, Alveolar, Plosive),
+  ),
+  (
+    'd',
+    ArticulatoryFeatures::consonant(true, Alveolar, Plosive),
+  ),
+  (
+    'ʈ',
+    ArticulatoryFeatures::consonant(false, Retroflex, Plosive),
+  ),
+  (
+    'ɖ',
+    ArticulatoryFeatures::consonant(true, Retroflex, Plosive),
+  ),
+  (
+    'c',
+    ArticulatoryFeatures::consonant(false, Palatal, Plosive),
+  ),
+  ('ɟ', ArticulatoryFeatures::consonant(true, Palatal, Plosive)),
+  ('k', ArticulatoryFeatures::consonant(false, Velar, Plosive)),
+  ('g', ArticulatoryFeatures::consonant(true, Velar, Plosive)),
+  ('q', ArticulatoryFeatures::consonant(false, Uvular, Plosive)),
+  ('ɢ', ArticulatoryFeatures::consonant(true, Uvular, Plosive)),
+  (
+    'ʔ',
+    ArticulatoryFeatures::consonant(false, Glottal, Plosive),
+  ),
+  ('m', ArticulatoryFeatures::consonant(true, Bilabial, Nasal)),
+  (
+    'ɱ',
+    ArticulatoryFeatures::consonant(true, Labiodental, Nasal),
+  ),
+  ('n', ArticulatoryFeatures::consonant(true, Alveolar, Nasal)),
+  ('ɳ', ArticulatoryFeatures::consonant(true, Retroflex, Nasal)),
+  ('ɲ', ArticulatoryFeatures::consonant(true, Palatal, Nasal)),
+  ('ŋ', ArticulatoryFeatures::consonant(true, Velar, Nasal)),
+  ('ɴ', ArticulatoryFeatures::consonant(true, Uvular, Nasal)),
+  ('ʙ', ArticulatoryFeatures::consonant(true, Bilabial, Trill)),
+  ('r', ArticulatoryFeatures::consonant(true, Alveolar, Trill)),
+  ('ʀ', ArticulatoryFeatures::consonant(true, Uvular, Trill)),
+  ('ⱱ', ArticulatoryFeatures::consonant(true, Labiodental, Tap)),
+  ('ɾ', ArticulatoryFeatures::consonant(true, Alveolar, Tap)),
+  ('ɽ', ArticulatoryFeatures::consonant(true, Retroflex, Tap)),
+  (
+    'ɸ',
+    ArticulatoryFeatures::consonant(false, Bilabial, Fricative),
+  ),
+  (
+    'β',
+    ArticulatoryFeatures::consonant(true, Bilabial, Fricative),
+  ),
+  (
+    'f',
+    ArticulatoryFeatures::consonant(false, Labiodental, Fricative),
+  ),
+  (
+    'v',
+    ArticulatoryFeatures::consonant(true, Labiodental, Fricative),
+  ),
+  (
+    'θ',
+    ArticulatoryFeatures::consonant(false, Dental, Fricative),
+  ),
+  (
+    'ð',
+    ArticulatoryFeatures::consonant(true, Dental, Fricative),
+  ),
+  (
+    's',
+    ArticulatoryFeatures::consonant(false, Alveolar, Fricative),
+  ),
+  (
+    'z',
+    ArticulatoryFeatures::consonant(true, Alveolar, Fricative),
+  ),
+  (
+    'ʃ',
+    ArticulatoryFeatures::consonant(false, Postalveolar, Fricative),
+  ),
+  (
+    'ʒ',
+    ArticulatoryFeatures::consonant(true, Postalveolar, Fricative),
+  ),
+  (
+    'ʂ',
+    ArticulatoryFeatures::consonant(false, Retroflex, Fricative),
+  ),
+  (
+    'ʐ',
+    ArticulatoryFeatures::consonant(true, Retroflex, Fricative),
+  ),
+  (
+    'ç',
+    ArticulatoryFeatures::consonant(false, Palatal, Fricative),
+  ),
+  (
+    'ʝ',
+    ArticulatoryFeatures::consonant(true, Palatal, Fricative),
+  ),
+  (
+    'x',
+    ArticulatoryFeatures::consonant(false, Velar, Fricative),
+  ),
+  ('ɣ', ArticulatoryFeatures::consonant(true, Velar, Fricative)),
+  (
+    'χ',
+    ArticulatoryFeatures::consonant(false, Uvular, Fricative),
+  ),
+  (
+    'ʁ',
+    ArticulatoryFeatures::consonant(true, Uvular, Fricative),
+  ),
+  (
+    'ħ',
+    ArticulatoryFeatures::consonant(false, Pharyngeal, Fricative),
+  ),
+  (
+    'ʕ',
+    ArticulatoryFeatures::consonant(true, Pharyngeal, Fricative),
+  ),
+  (
+    'h',
+    ArticulatoryFeatures::consonant(false, Glottal, Fricative),
+  ),
+  (
+    'ɦ',
+    ArticulatoryFeatures::consonant(true, Glottal, Fricative),
+  ),
+  (
+    'ʍ',
+    ArticulatoryFeatures::consonant(false, LabialVelar, Fricative),
+  ),
+  (
+    'ɬ',
+    ArticulatoryFeatures::consonant(false, Alveolar, LateralFricative),
+  ),
+  (
+    'ɮ',
+    ArticulatoryFeatures::consonant(true, Alveolar, LateralFricative),
+  ),
+  (
+    'ʋ',
+    ArticulatoryFeatures::consonant(true, Labiodental, Approximant),
+  ),
+  (
+    'ɹ',
+    ArticulatoryFeatures::consonant(true, Alveolar, Approximant),
+  ),
+  (
+    'ɻ',
+    ArticulatoryFeatures::consonant(true, Retroflex, Approximant),
+  ),
+  (
+    'j',
+    ArticulatoryFeatures::consonant(true, Palatal, Approximant),
+  ),
+  (
+    'ɰ',
+    ArticulatoryFeatures::consonant(true, Velar, Approximant),
+  ),
+  (
+    'w',
+    ArticulatoryFeatures::consonant(true, LabialVelar, Approximant),
+  ),
+  (
+    'l',
+    ArticulatoryFeatures::consonant(true, Alveolar, LateralApproximant),
+  ),
+  (
+    'ɫ',
+    ArticulatoryFeatures::consonant(true, Alveolar, LateralApproximant),
+  ),
+  (
+    'ɭ',
+    ArticulatoryFeatures::consonant(true, Retroflex, LateralApproximant),
+  ),
+  (
+    'ʎ',
+    ArticulatoryFeatures::consonant(true, Palatal, LateralApproximant),
+  ),
+  (
+    'ʟ',
+    ArticulatoryFeatures::consonant(true, Velar, LateralApproximant),
+  ),
+];
+
+const VOWELS: &[(char, ArticulatoryFeatures)] = &[
+  ('i', ArticulatoryFeatures::vowel(Close, Front, false, true)),
+  ('y', ArticulatoryFeatures::vowel(Close, Front, true, true)),
+  (
+    'ɨ',
+    ArticulatoryFeatures::vowel(Close, Central, false, true),
+  ),
+  ('ʉ', ArticulatoryFeatures::vowel(Close, Central, true, true)),
+  ('ɯ', ArticulatoryFeatures::vowel(Close, Back, false, true)),
+  ('u', ArticulatoryFeatures::vowel(Close, Back, true, true)),
+  (
+    'ɪ',
+    ArticulatoryFeatures::vowel(NearClose, Front, false, false),
+  ),
+  (
+    'ʏ',
+    ArticulatoryFeatures::vowel(NearClose, Front, true, false),
+  ),
+  (
+    'ʊ',
+    ArticulatoryFeatures::vowel(NearClose, Back, true, false),
+  ),
+  (
+    'e',
+    ArticulatoryFeatures::vowel(CloseMid, Front, false, true),
+  ),
+  (
+    'ø',
+    ArticulatoryFeatures::vowel(CloseMid, Front, true, true),
+  ),
+  (
+    'ɘ',
+    ArticulatoryFeatures::vowel(CloseMid, Central, false, true),
+  ),
+  (
+    'ɵ',
+    ArticulatoryFeatures::vowel(CloseMid, Central, true, true),
+  ),
+  (
+    'ɤ',
+    ArticulatoryFeatures::vowel(CloseMid, Back, false, true),
+  ),
+  ('o', ArticulatoryFeatures::vowel(CloseMid, Back, true, true)),
+  ('ə', ArticulatoryFeatures::vowel(Mid, Central, false, false)),
+  ('ɚ', ArticulatoryFeatures::vowel(Mid, Central, false, false)),
+  (
+    'ɛ',
+    ArticulatoryFeatures::vowel(OpenMid, Front, false, false),
+  ),
+  (
+    'œ',
+    ArticulatoryFeatures::vowel(OpenMid, Front, true, false),
+  ),
+  (
+    'ɜ',
+    ArticulatoryFeatures::vowel(OpenMid, Central, false, true),
+  ),
+  (
+    'ɝ',
+    ArticulatoryFeatures::vowel(OpenMid, Central, false, true),
+  ),
+  (
+    'ɞ',
+    ArticulatoryFeatures::vowel(OpenMid, Central, true, false),
+  ),
+  (
+    'ʌ',
+    ArticulatoryFeatures::vowel(OpenMid, Back, false, false),
+  ),
+  ('ɔ', ArticulatoryFeatures::vowel(OpenMid, Back, true, true)),
+  (
+    'æ',
+    ArticulatoryFeatures::vowel(NearOpen, Front, false, false),
+  ),
+  (
+    'ɐ',
+    ArticulatoryFeatures::vowel(NearOpen, Central, false, false),
+  ),
+  ('a', ArticulatoryFeatures::vowel(Open, Front, false, true)),
+  ('ɶ', ArticulatoryFeatures::vowel(Open, Front, true, true)),
+  ('ɑ', ArticulatoryFeatures::vowel(Open, Back, false, true)),
+  ('ɒ', ArticulatoryFeatures::vowel(Open, Back, true, false)),
+];
+
+/// Combining rings that devoice a segment (n̥, l̊)
+const VOICELESS_MARKS: &[char] = &['\u{0325}', '\u{030A}'];
+
+/// Combining caron that voices a segment (s̬)
+const VOICED_MARK: char = '\u{032C}';
+
+/// Weights of voicing, place and manner in the distance between consonants
+const CONSONANT_WEIGHTS: [f32; 3] = [0.2, 0.4, 0.4];
+
+/// Weights of height, backness, rounding and tenseness in the distance between vowels
+const VOWEL_WEIGHTS: [f32; 4] = [0.4, 0.3, 0.15, 0.15];
+
+/// Places this many steps apart are as different as places can be
+const PLACE_SPAN: f32 = 4.0;
+
+/// Distance between two spellings with the same features, such as a long and a short vowel or an
+/// aspirated and a plain stop
+const SECONDARY_DISTANCE: f32 = 0.1;
+
+/// Articulatory features of one phoneme segment, as split by `Transcription::parse`. Length,
+/// aspiration and other secondary marks are ignored; devoicing and voicing marks are applied.
+/// Affricates take the place of their fricative. A diphthong has the features of its onset and
+/// is tense, since the table has no place for the glide; `phoneme_distance` compares both halves.
+/// `None` for symbols outside the IPA charts.
+pub fn features(segment: &str) -> Option<ArticulatoryFeatures> {
+  if let Some((onset, offglide)) = ipa::diphthong_halves(segment) {
+    features(offglide)?;
+    return Some(ArticulatoryFeatures {
+      tense: Some(true),
+      ..features(onset)?
+    });
+  }
+  let base: Vec<char> = segment.chars().filter(|&c| lookup(c).is_some()).collect();
+  let mut features = match base[..] {
+    [c] => lookup(c)?,
+    [stop, fricative] => {
+      let stop = lookup(stop)?;
+      let fricative = lookup(fricative)?;
+      if stop.manner != Some(Plosive) || fricative.manner != Some(Fricative) {
+        return None;
+      }
+      ArticulatoryFeatures {
+        manner: Some(Affricate),
+        ..fricative
+      }
+    }
+    _ => return None,
+  };
+  if segment.chars().any(|c| VOICELESS_MARKS.contains(&c)) {
+    features.voiced = Some(false);
+  } else if segment.contains(VOICED_MARK) {
+    features.voiced = Some(true);
+  }
+  Some(features)
+}
+
+fn lookup(c: char) -> Option<ArticulatoryFeatures> {
+  CONSONANTS
+    .iter()
+    .chain(VOWELS)
+    .find(|(symbol, _)| *symbol == c)
+    .map(|&(_, features)| features)
+}
+
+/// How different two phonemes sound, from 0 for identical features to 1 for a vowel against a
+/// consonant. Each feature contributes by how far apart its values are, so /d/ is closer to /ð/
+/// than /m/ is. Missing features count as fully different.
+pub fn distance(a: &ArticulatoryFeatures, b: &ArticulatoryFeatures) -> f32 {
+  if a.is_consonant() && b.is_consonant() {
+    let voicing = flag_distance(a.voiced, b.voiced);
+    let place = match (a.place, b.place) {
+      (Some(a), Some(b)) => place_distance(a, b),
+      _ => 1.0,
+    };
+    let manner = match (a.manner, b.manner) {
+      (Some(a), Some(b)) => manner_distance(a, b),
+      _ => 1.0,
+    };
+    weighted(&CONSONANT_WEIGHTS, &[voicing, place, manner])
+  } else if a.is_vowel() && b.is_vowel() {
+    let height = match (a.height, b.height) {
+      (Some(a), Some(b)) => (a as i32 - b as i32).abs() as f32 / Open as i32 as f32,
+      _ => 1.0,
+    };
+    let backness = match (a.backness, b.backness) {
+      (Some(a), Some(b)) => (a as i32 - b as i32).abs() as f32 / Back as i32 as f32,
+      _ => 1.0,
+    };
+    let rounding = flag_distance(a.rounded, b.rounded);
+    let tenseness = flag_distance(a.tense, b.tense);
+    weighted(&VOWEL_WEIGHTS, &[height, backness, rounding, tenseness])
+  } else {
+    1.0
+  }
+}
+
+/// Distance between two phoneme segments by their features; symbols outside the IPA charts are
+/// only close to themselves. Diphthongs are compared half by half, a plain vowel standing for
+/// both halves, so /eɪ/ is nearer /e/ than /aɪ/ is.
+pub fn phoneme_distance(a: &str, b: &str) -> f32 {
+  if a == b {
+    return 0.0;
+  }
+  if ipa::is_diphthong(a) || ipa::is_diphthong(b) {
+    let halves = |segment| ipa::diphthong_halves(segment).unwrap_or((segment, segment));
+    let ((a_onset, a_offglide), (b_onset, b_offglide)) = (halves(a), halves(b));
+    let between = |x: &str, y: &str| match (features(x), features(y)) {
+      (Some(x), Some(y)) => distance(&x, &y),
+      _ => 1.0,
+    };
+    let glide = (between(a_onset, b_onset) + between(a_offglide, b_offglide)) / 2.0;
+    return glide.max(SECONDARY_DISTANCE);
+  }
+  match (features(a), features(b)) {
+    (Some(a), Some(b)) => distance(&a, &b).max(SECONDARY_DISTANCE),
+    _ => 1.0,
+  }
+}
+
+/// Cheapest way to turn one phoneme sequence into another, where adding or dropping a phoneme
+/// costs 1 and swapping one costs its `phoneme_distance`
+pub fn sequence_distance<S: AsRef<str>>(a: &[S], b: &[S]) -> f32 {
+  let mut previous: Vec<f32> = (0..=b.len()).map(|j| j as f32).collect();
+  for (i, x) in a.iter().enumerate() {
+    let mut row = vec![i as f32 + 1.0; b.len() + 1];
+    for (j, y) in b.iter().enumerate() {
+      row[j + 1] = (previous[j] + phoneme_distance(x.as_ref(), y.as_ref()))
+        .min(previous[j + 1] + 1.0)
+        .min(row[j] + 1.0);
+    }
+    previous = row;
+  }
+  previous[b.len()]
+}
+
+fn flag_distance(a: Option<bool>, b: Option<bool>) -> f32 {
+  match (a, b) {
+    (Some(a), Some(b)) if a == b => 0.0,
+    _ => 1.0,
+  }
+}
+
+fn weighted(weights: &[f32], distances: &[f32]) -> f32 {
+  weights.iter().zip(distances).map(|(w, d)| w * d).sum()
+}
+
+/// Steps between places along the vocal tract; labial-velars count from whichever end is nearer
+fn place_distance(a: Place, b: Place) -> f32 {
+  let positions = |place: Place| match place {
+    LabialVelar => vec![Bilabial as i32, Velar as i32],
+    place => vec![place as i32],
+  };
+  let steps = positions(a)
+    .iter()
+    .flat_map(|&a| positions(b).into_iter().map(move |b| (a - b).abs()))
+    .min()
+    .unwrap_or(0);
+  (steps as f32 / PLACE_SPAN).min(1.0)
+}
+
+/// Manners as continuant, sonorant, nasal, lateral and vibrant, with affricates half continuant
+fn manner_distance(a: Manner, b: Manner) -> f32 {
+  let classes = |manner: Manner| -> [f32; 5] {
+    match manner {
+      Plosive => [0.0, 0.0, 0.0, 0.0, 0.0],
+      Affricate => [0.5, 0.0, 0.0, 0.0, 0.0],
+      Fricative => [1.0, 0.0, 0.0, 0.0, 0.0],
+      LateralFricative => [1.0, 0.0, 0.0, 1.0, 0.0],
+      Nasal => [0.0, 1.0, 1.0, 0.0, 0.0],
+      Trill => [1.0, 1.0, 0.0, 0.0, 1.0],
+      Tap => [0.0, 1.0, 0.0, 0.0, 1.0],
+      Approximant => [1.0, 1.0, 0.0, 0.0, 0.0],
+      LateralApproximant => [1.0, 1.0, 0.0, 1.0, 0.0],
+    }
+  };
+  let differences: f32 = classes(a)
+    .iter()
+    .zip(classes(b))
+    .map(|(a, b)| (a - b).abs())
+    .sum();
+  (differences / 3.0).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_features_of_segments() {
+    assert_eq!(
+      features("ð"),
+      Some(ArticulatoryFeatures::consonant(true, Dental, Fricative))
+    );
+    assert_eq!(
+      features("tʃ"),
+      Some(ArticulatoryFeatures::consonant(
+        false,
+        Postalveolar,
+        Affricate
+      ))
+    );
+    assert_eq!(
+      features("dʒ"),
+      Some(ArticulatoryFeatures::consonant(
+        true,
+        Postalveolar,
+        Affricate
+      ))
+    );
+    assert_eq!(features("iː"), features("i"));
+    assert_eq!(features("pʰ"), features("p"));
+    assert_eq!(features("n̥").and_then(|f| f.voiced), Some(false));
+    assert!(features("ɪ").is_some_and(|f| f.is_vowel() && f.tense == Some(false)));
+    assert_eq!(features("?"), None);
+    assert_eq!(features("ʃt"), None);
+    assert_eq!(
+      features("aɪ"),
+      Some(ArticulatoryFeatures::vowel(Open, Front, false, true))
+    );
+    assert_eq!(features("aʔ"), None);
+  }
+
+  #[test]
+  fn test_distance_follows_features() {
+    // A /d/ for /ð/ is a nearer miss than an /m/
+    assert!(phoneme_distance("ð", "d") < phoneme_distance("ð", "m"));
+    assert!(phoneme_distance("ð", "θ") < phoneme_distance("ð", "s"));
+    assert!(phoneme_distance("ɪ", "i") < phoneme_distance("ɪ", "ɑ"));
+    assert!(phoneme_distance("w", "b") < phoneme_distance("w", "d"));
+    assert!(phoneme_distance("w", "g") < phoneme_distance("w", "d"));
+    assert_eq!(phoneme_distance("s", "z"), phoneme_distance("z", "s"));
+    assert!((phoneme_distance("s", "z") - CONSONANT_WEIGHTS[0]).abs() < 1e-6);
+    assert_eq!(phoneme_distance("i", "t"), 1.0);
+    assert_eq!(phoneme_distance("iː", "iː"), 0.0);
+    assert_eq!(phoneme_distance("iː", "i"), SECONDARY_DISTANCE);
+    assert_eq!(phoneme_distance("?", "!"), 1.0);
+    assert!(phoneme_distance("eɪ", "e") < phoneme_distance("eɪ", "aɪ"));
+    assert!(phoneme_distance("eɪ", "aɪ") < phoneme_distance("eɪ", "oʊ"));
+    assert_eq!(phoneme_distance("eɪ", "ɛ"), phoneme_distance("ɛ", "eɪ"));
+    assert_eq!(phoneme_distance("oʊ", "k"), 1.0);
+
+    let all: Vec<&(char, ArticulatoryFeatures)> = CONSONANTS.iter().chain(VOWELS).collect();
+    for (_, a) in &all {
+      for (_, b) in &all {
+        let d = distance(a, b);
+        assert!((0.0..=1.0).contains(&d), "{:?} {:?} {}", a, b, d);
+      }
+    }
+  }
+
+  #[test]
+  fn test_sequence_distance() {
+    let words = |ipa: &str| crate::services::ipa::Transcription::parse(ipa).phonemes;
+    let ship = words("ʃɪp");
+    assert_eq!(sequence_distance(&ship, &ship), 0.0);
+    assert!(sequence_distance(&ship, &words("ʃiːp")) < sequence_distance(&ship, &words("ʃɑp")));
+    assert!(sequence_distance(&ship, &words("tʃɪp")) < 1.0);
+    assert_eq!(sequence_distance(&ship, &words("ʃɪps")), 1.0);
+  }
+}
@@ -0,0 +1,408 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Marks that delimit or annotate a transcription without being a segment themselves
+const SUPRASEGMENTALS: &[char] = &['ˈ', 'ˌ', '.', '/', '[', ']', ' ', '‿', '|', '‖'];
+
+/// Suprasegmentals that separate syllables
+const SYLLABLE_BREAKS: &[char] = &['.', ' ', '|', '‖'];
+
+/// Spacing modifiers that belong to the preceding segment
+const MODIFIERS: &[char] = &['ː', 'ˑ', 'ʰ', 'ʷ', 'ʲ', 'ˠ', 'ˤ', 'ⁿ', 'ˡ', '˞'];
+
+/// IPA vowel letters, including the rhotacized schwas used in American transcriptions
+const VOWELS: &[char] = &[
+  'i', 'y', 'ɨ', 'ʉ', 'ɯ', 'u', 'ɪ', 'ʏ', 'ʊ', 'e', 'ø', 'ɘ', 'ɵ', 'ɤ', 'o', 'ə', 'ɛ', 'œ', 'ɜ',
+  'ɞ', 'ʌ', 'ɔ', 'æ', 'ɐ', 'a', 'ɶ', 'ɑ', 'ɒ', 'ɚ', 'ɝ',
+];
+
+/// Stop and fricative pairs that form an affricate even without a tie bar
+const AFFRICATES: &[(char, char)] = &[('t', 'ʃ'), ('d', 'ʒ')];
+
+/// Vowel pairs that glide within one syllable in English dialects, and are one segment; any
+/// other adjacent vowels are separate segments unless tied (a͡ɪ) or marked non-syllabic (aɪ̯)
+const DIPHTHONGS: &[(char, char)] = &[
+  ('e', 'ɪ'),
+  ('a', 'ɪ'),
+  ('ɔ', 'ɪ'),
+  ('o', 'ɪ'),
+  ('a', 'ʊ'),
+  ('o', 'ʊ'),
+  ('ə', 'ʊ'),
+  ('ɪ', 'ə'),
+  ('e', 'ə'),
+  ('ɛ', 'ə'),
+  ('ʊ', 'ə'),
+  ('æ', 'ɪ'),
+  ('ɑ', 'ɪ'),
+  ('ɒ', 'ɪ'),
+  ('ʌ', 'ɪ'),
+  ('ɐ', 'ɪ'),
+  ('æ', 'ʊ'),
+  ('ɐ', 'ʊ'),
+  ('ɑ', 'ʊ'),
+  ('ɛ', 'ɪ'),
+  ('ɜ', 'ʊ'),
+  ('ɔ', 'ʊ'),
+];
+
+/// Variant spellings replaced by the canonical symbol, so one phoneme has one spelling
+const EQUIVALENTS: &[(char, char)] = &[('ɡ', 'g'), (':', 'ː')];
+
+pub(crate) fn is_combining(c: char) -> bool {
+  ('\u{0300}'..='\u{036F}').contains(&c)
+}
+
+fn is_tie_bar(c: char) -> bool {
+  c == '\u{0361}' || c == '\u{035C}'
+}
+
+/// Combining marks that make a consonant the nucleus of its syllable (n̩, l̍)
+fn is_syllabic_mark(c: char) -> bool {
+  c == '\u{0329}' || c == '\u{030D}'
+}
+
+/// Combining mark for a vowel that glides off the one before it (aɪ̯)
+fn is_non_syllabic_mark(c: char) -> bool {
+  c == '\u{032F}'
+}
+
+/// Lexical stress of a syllable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stress {
+  Primary,
+  Secondary,
+  Unstressed,
+}
+
+/// A syllable, identified by the phonemes forming its nucleus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Syllable {
+  /// Index of the nucleus vowel, diphthong or syllabic consonant
+  pub nucleus: Range<usize>,
+  pub stress: Stress,
+}
+
+/// A target pronunciation: its phoneme segments and their syllables
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcription {
+  pub phonemes: Vec<String>,
+  pub syllables: Vec<Syllable>,
+}
+
+impl Transcription {
+  /// Parse an IPA transcription, keeping the stress marks as syllable stress
+  pub fn parse(ipa: &str) -> Self {
+    let tokens = tokenize(ipa);
+    let phonemes = tokens
+      .iter()
+      .filter_map(|token| match token {
+        Token::Segment(segment) => Some(segment.clone()),
+        _ => None,
+      })
+      .collect();
+
+    Self {
+      phonemes,
+      syllables: syllabify(&tokens),
+    }
+  }
+
+  /// Build from a bare phoneme sequence; syllables are found from the vowels and carry no stress
+  pub fn from_phonemes(phonemes: Vec<String>) -> Self {
+    let tokens: Vec<Token> = phonemes.iter().cloned().map(Token::Segment).collect();
+    Self {
+      syllables: syllabify(&tokens),
+      phonemes,
+    }
+  }
+
+  /// Index of the syllable carrying primary stress, if the transcription marks one
+  pub fn primary_stress(&self) -> Option<usize> {
+    self
+      .syllables
+      .iter()
+      .position(|s| s.stress == Stress::Primary)
+  }
+}
+
+enum Token {
+  Segment(String),
+  Stress(Stress),
+  Break,
+}
+
+fn tokenize(ipa: &str) -> Vec<Token> {
+  let mut tokens: Vec<Token> = Vec::new();
+  let mut tied = false;
+
+  for c in ipa.trim().chars() {
+    let c = EQUIVALENTS
+      .iter()
+      .find(|(variant, _)| *variant == c)
+      .map_or(c, |&(_, canonical)| canonical);
+    if SUPRASEGMENTALS.contains(&c) {
+      tied = false;
+      match c {
+        'ˈ' => tokens.push(Token::Stress(Stress::Primary)),
+        'ˌ' => tokens.push(Token::Stress(Stress::Secondary)),
+        c if SYLLABLE_BREAKS.contains(&c) => tokens.push(Token::Break),
+        _ => {}
+      }
+      continue;
+    }
+    if is_tie_bar(c) {
+      tied = true;
+      continue;
+    }
+
+    // Only a segment directly before this letter, with no mark between, can take it
+    let previous = match tokens.last_mut() {
+      Some(Token::Segment(segment)) => Some(segment),
+      _ => None,
+    };
+    let modifier = is_combining(c) || MODIFIERS.contains(&c);
+    let starts_vowel = VOWELS.contains(&c);
+    match previous {
+      Some(last) if modifier => {
+        last.push(c);
+        if is_non_syllabic_mark(c) {
+          glide_into_previous(&mut tokens);
+        }
+      }
+      Some(last) if tied => last.push(c),
+      Some(last)
+        if starts_vowel
+          && vowel_letters(last) == 1
+          && DIPHTHONGS
+            .iter()
+            .any(|&(onset, offglide)| last.starts_with(onset) && c == offglide) =>
+      {
+        last.push(c)
+      }
+      Some(last)
+        if AFFRICATES
+          .iter()
+          .any(|&(stop, fricative)| last.chars().eq([stop]) && c == fricative) =>
+      {
+        last.push(c)
+      }
+      // Diacritics with nothing to attach to are dropped
+      None if modifier => {}
+      _ => tokens.push(Token::Segment(c.to_string())),
+    }
+    tied = false;
+  }
+
+  tokens
+}
+
+/// One syllable per nucleus: a vowel, a diphthong or a syllabic consonant. A stress mark applies
+/// to the next nucleus.
+fn syllabify(tokens: &[Token]) -> Vec<Syllable> {
+  let mut syllables: Vec<Syllable> = Vec::new();
+  let mut pending_stress = None;
+  let mut index = 0;
+
+  for token in tokens {
+    match token {
+      Token::Stress(stress) => pending_stress = Some(*stress),
+      Token::Break => {}
+      Token::Segment(segment) => {
+        if is_vowel(segment) || segment.chars().any(is_syllabic_mark) {
+          syllables.push(Syllable {
+            nucleus: index..index + 1,
+            stress: pending_stress.take().unwrap_or(Stress::Unstressed),
+          });
+        }
+        index += 1;
+      }
+    }
+  }
+
+  syllables
+}
+
+/// Join a vowel marked non-syllabic onto a directly preceding single vowel, as one diphthong
+fn glide_into_previous(tokens: &mut Vec<Token>) {
+  let [.., Token::Segment(onset), Token::Segment(offglide)] = &tokens[..] else {
+    return;
+  };
+  if vowel_letters(onset) == 1 && vowel_letters(offglide) == 1 && is_vowel(offglide) {
+    let Some(Token::Segment(offglide)) = tokens.pop() else {
+      return;
+    };
+    if let Some(Token::Segment(onset)) = tokens.last_mut() {
+      onset.push_str(&offglide);
+    }
+  }
+}
+
+fn vowel_letters(segment: &str) -> usize {
+  segment.chars().filter(|c| VOWELS.contains(c)).count()
+}
+
+/// Whether a segment glides between two vowel qualities, as /aɪ/ does
+pub fn is_diphthong(segment: &str) -> bool {
+  is_vowel(segment) && vowel_letters(segment) > 1
+}
+
+/// The onset and offglide of a diphthong, each with its own marks (eːɪ̯ gives eː and ɪ̯)
+pub fn diphthong_halves(segment: &str) -> Option<(&str, &str)> {
+  if !is_diphthong(segment) {
+    return None;
+  }
+  let (offglide, _) = segment
+    .char_indices()
+    .filter(|(_, c)| VOWELS.contains(c))
+    .nth(1)?;
+  Some(segment.split_at(offglide))
+}
+
+/// Whether a segment is (or starts with) a vowel
+pub fn is_vowel(segment: &str) -> bool {
+  segment.chars().next().is_some_and(|c| VOWELS.contains(&c))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_drops_stress_and_keeps_length() {
+    assert_eq!(
+      Transcription::parse("ˈbjuːtɪfəl").phonemes,
+      vec!["b", "j", "uː", "t", "ɪ", "f", "ə", "l"]
+    );
+    assert_eq!(
+      Transcription::parse("/həˈloʊ/").phonemes,
+      vec!["h", "ə", "l", "oʊ"]
+    );
+  }
+
+  #[test]
+  fn test_parse_attaches_diacritics_and_ties() {
+    assert_eq!(Transcription::parse("pʰæ̃n").phonemes, vec!["pʰ", "æ̃", "n"]);
+    assert_eq!(
+      Transcription::parse("ˈt͡sɛlə").phonemes,
+      vec!["ts", "ɛ", "l", "ə"]
+    );
+  }
+
+  #[test]
+  fn test_affricates_are_one_segment_with_or_without_a_tie_bar() {
+    assert_eq!(Transcription::parse("t͡ʃɪn").phonemes, vec!["tʃ", "ɪ", "n"]);
+    assert_eq!(Transcription::parse("tʃɪn").phonemes, vec!["tʃ", "ɪ", "n"]);
+    assert_eq!(
+      Transcription::parse("ˈlæŋɡwɪdʒ").phonemes,
+      vec!["l", "æ", "ŋ", "g", "w", "ɪ", "dʒ"]
+    );
+    // Across a syllable boundary they are a stop and a fricative
+    assert_eq!(
+      Transcription::parse("ˈnʌt.ʃɛl").phonemes,
+      vec!["n", "ʌ", "t", "ʃ", "ɛ", "l"]
+    );
+    assert_eq!(
+      Transcription::parse("wɜ:ld").phonemes,
+      vec!["w", "ɜː", "l", "d"]
+    );
+  }
+
+  #[test]
+  fn test_diphthongs_are_one_segment() {
+    assert_eq!(Transcription::parse("baɪt").phonemes, vec!["b", "aɪ", "t"]);
+    assert_eq!(
+      Transcription::parse("ˈbeɪ.bi").phonemes,
+      vec!["b", "eɪ", "b", "i"]
+    );
+    assert_eq!(Transcription::parse("ˈbɪəd").phonemes, vec!["b", "ɪə", "d"]);
+    assert_eq!(Transcription::parse("ˈha͡ɛm").phonemes, vec!["h", "aɛ", "m"]);
+    assert_eq!(Transcription::parse("ˈhaɛ̯m").phonemes, vec!["h", "aɛ̯", "m"]);
+    // Vowels that are not a diphthong, or are split by a mark, stay apart
+    assert_eq!(
+      Transcription::parse("ˈfaɪəl").phonemes,
+      vec!["f", "aɪ", "ə", "l"]
+    );
+    assert_eq!(
+      Transcription::parse("riˈæl").phonemes,
+      vec!["r", "i", "æ", "l"]
+    );
+    assert_eq!(
+      Transcription::parse("ˈnaɪ.iv").phonemes,
+      vec!["n", "aɪ", "i", "v"]
+    );
+    assert_eq!(
+      Transcription::parse("ˈko.ɪn").phonemes,
+      vec!["k", "o", "ɪ", "n"]
+    );
+
+    assert!(is_diphthong("oʊ") && !is_diphthong("oː") && !is_diphthong("tʃ"));
+    assert_eq!(diphthong_halves("eːɪ̯"), Some(("eː", "ɪ̯")));
+    assert_eq!(diphthong_halves("ɪ"), None);
+  }
+
+  #[test]
+  fn test_each_vowel_or_diphthong_is_a_nucleus() {
+    let nuclei = |ipa: &str| {
+      let word = Transcription::parse(ipa);
+      word
+        .syllables
+        .iter()
+        .map(|s| word.phonemes[s.nucleus.clone()].concat())
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(nuclei("ˈfaɪəl"), vec!["aɪ", "ə"]);
+    assert_eq!(nuclei("riæləti"), vec!["i", "æ", "ə", "i"]);
+    assert_eq!(nuclei("ˈmiːdiə"), vec!["iː", "i", "ə"]);
+    assert_eq!(nuclei("ˈbɪəd"), vec!["ɪə"]);
+    // Tie bars and non-syllabic marks glide any two vowels
+    assert_eq!(nuclei("ˈha͡ɛm"), vec!["aɛ"]);
+    assert_eq!(nuclei("ˈhaɛ̯m"), vec!["aɛ̯"]);
+    let word = Transcription::parse("ˈbaɪ.oʊ");
+    assert_eq!(word.syllables.len(), 2);
+    assert_eq!(word.syllables[1].nucleus, 2..3);
+  }
+
+  #[test]
+  fn test_is_vowel() {
+    assert!(is_vowel("uː"));
+    assert!(is_vowel("ɚ"));
+    assert!(!is_vowel("t͡ʃ"));
+    assert!(!is_vowel(""));
+  }
+
+  #[test]
+  fn test_syllables_carry_stress_marks() {
+    let word = Transcription::parse("prəˌnʌnsiˈeɪʃən");
+    let nuclei: Vec<String> = word
+      .syllables
+      .iter()
+      .map(|s| word.phonemes[s.nucleus.clone()].concat())
+      .collect();
+    assert_eq!(nuclei, vec!["ə", "ʌ", "i", "eɪ", "ə"]);
+    let stress: Vec<Stress> = word.syllables.iter().map(|s| s.stress).collect();
+    assert_eq!(
+      stress,
+      vec![
+        Stress::Unstressed,
+        Stress::Secondary,
+        Stress::Unstressed,
+        Stress::Primary,
+        Stress::Unstressed
+      ]
+    );
+    assert_eq!(word.primary_stress(), Some(3));
+  }
+
+  #[test]
+  fn test_syllabic_consonants_and_unmarked_phonemes() {
+    let word = Transcription::parse("ˈbʌt.n̩");
+    assert_eq!(word.syllables.len(), 2);
+    assert_eq!(word.syllables[1].nucleus, 3..4);
+
+    let bare = Transcription::from_phonemes(Transcription::parse("ˈbjuːtɪfəl").phonemes);
+    assert_eq!(bare.syllables.len(), 3);
+    assert_eq!(bare.primary_stress(), None);
+  }
+}
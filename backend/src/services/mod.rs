@@ -1,3 +1,5 @@
+pub mod articulation;
+pub mod ipa;
 pub mod scoring;
 pub mod search;
 pub mod timecap;
@@ -0,0 +1,612 @@
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use super::diagnosis::{self, Diagnosis, Insertion, SUBSTITUTED_BELOW};
+use super::Distance;
+use crate::services::ipa;
+
+/// Weight of the duration prior relative to the spectral homogeneity of a segment
+const DURATION_WEIGHT: f32 = 2.0;
+
+/// Expected duration of a vowel relative to a consonant
+const VOWEL_DURATION: f32 = 2.0;
+
+/// Local distance at which a phoneme scores zero
+const DISTANCE_SCALE: f32 = 10.0;
+
+/// A phoneme the learner passes through this many times faster than the reference was skipped;
+/// the scorer's tempo window allows no faster
+const DELETION_RATIO: usize = 2;
+
+/// Shortest reference span, in frames, that can be judged deleted
+const MIN_DELETED_FRAMES: usize = 3;
+
+/// Shortest added sound reported as an insertion
+const INSERTION_MIN_MS: f32 = 60.0;
+
+/// Distance from the reference above which learner frames held at a boundary are a new sound
+const INSERTION_DISTANCE: f32 = 0.5 * DISTANCE_SCALE;
+
+/// Held frames must also be this much further from the reference than the word's average frame
+const INSERTION_CONTRAST: f32 = 1.5;
+
+/// Reference frames either side of a phoneme boundary where inserted sounds are held
+const BOUNDARY_FRAMES: usize = 2;
+
+/// Time-aligned score and diagnosis for one target phoneme, as stored in `scores.per_phoneme`:
+///
+/// ```json
+/// {
+///   "phoneme": "θ", "start_ms": 120, "end_ms": 210, "score": 31.5,
+///   "diagnosis": {"kind": "substituted", "produced": "s"},
+///   "inserted_before": [{"sound": "ə", "start_ms": 80, "end_ms": 120}]
+/// }
+/// ```
+///
+/// `diagnosis.kind` is `correct`, `substituted` or `deleted`; `produced` is null when the engine
+/// cannot name the sound. Insertions are empty lists when absent and then omitted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhonemeScore {
+  /// IPA symbol of the target phoneme
+  pub phoneme: String,
+  /// Start of the phoneme in the learner's recording
+  pub start_ms: u32,
+  /// End of the phoneme in the learner's recording
+  pub end_ms: u32,
+  pub score: f32,
+  #[serde(default)]
+  pub diagnosis: Diagnosis,
+  /// Sounds added just before this phoneme
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub inserted_before: Vec<Insertion>,
+  /// Sounds added after the word; only the last phoneme has any
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub inserted_after: Vec<Insertion>,
+}
+
+/// Where one phoneme of the transcription falls in a reference recording, as stored in
+/// `reference_recordings.phoneme_boundaries`. Times are ms into the recording.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhonemeBoundary {
+  pub phoneme: String,
+  pub start_ms: u32,
+  pub end_ms: u32,
+}
+
+/// Check that boundaries follow the transcription's phonemes in order without overlapping
+pub fn check_boundaries(boundaries: &[PhonemeBoundary], phonemes: &[String]) -> Result<()> {
+  ensure!(
+    boundaries.len() == phonemes.len(),
+    "{} boundaries for {} phonemes",
+    boundaries.len(),
+    phonemes.len()
+  );
+  let mut previous_end = 0;
+  for (boundary, phoneme) in boundaries.iter().zip(phonemes) {
+    ensure!(
+      &boundary.phoneme == phoneme,
+      "boundary for /{}/ where the transcription has /{}/",
+      boundary.phoneme,
+      phoneme
+    );
+    ensure!(
+      boundary.start_ms < boundary.end_ms,
+      "/{}/ ends before it starts",
+      phoneme
+    );
+    ensure!(
+      boundary.start_ms >= previous_end,
+      "/{}/ overlaps the phoneme before it",
+      phoneme
+    );
+    previous_end = boundary.end_ms;
+  }
+  Ok(())
+}
+
+/// Aligns a target phoneme sequence to feature frames and scores each phoneme
+pub struct ForcedAligner {
+  frame_ms: f32,
+}
+
+impl ForcedAligner {
+  pub fn new(hop_size: usize, sample_rate: u32) -> Self {
+    Self {
+      frame_ms: hop_size as f32 * 1000.0 / sample_rate as f32,
+    }
+  }
+
+  /// Duration of one feature frame
+  pub fn frame_ms(&self) -> f32 {
+    self.frame_ms
+  }
+
+  /// Split reference frames into one contiguous span per phoneme.
+  ///
+  /// Boundaries minimise the spectral variance inside each span, with a log-duration prior that
+  /// expects vowels to last longer than consonants.
+  pub fn segment(&self, features: &[Vec<f32>], phonemes: &[String]) -> Vec<Range<usize>> {
+    let frames = features.len();
+    let count = phonemes.len();
+
+    if count == 0 {
+      return Vec::new();
+    }
+    if frames < count {
+      return proportional_spans(frames, count);
+    }
+
+    let weights: Vec<f32> = phonemes
+      .iter()
+      .map(|p| {
+        if ipa::is_vowel(p) {
+          VOWEL_DURATION
+        } else {
+          1.0
+        }
+      })
+      .collect();
+    let total_weight: f32 = weights.iter().sum();
+    let expected: Vec<f32> = weights
+      .iter()
+      .map(|w| frames as f32 * w / total_weight)
+      .collect();
+    let max_len = expected
+      .iter()
+      .fold(1.0f32, |max, &e| max.max(e))
+      .mul_add(4.0, 1.0)
+      .ceil() as usize;
+
+    let sums = PrefixSums::new(features);
+    let frame_variance = (sums.sse(0, frames) / frames as f32).max(f32::EPSILON);
+
+    // cost[p][t]: best cost of placing the first p phonemes on the first t frames
+    let mut cost = vec![vec![f32::INFINITY; frames + 1]; count + 1];
+    let mut back = vec![vec![0usize; frames + 1]; count + 1];
+    cost[0][0] = 0.0;
+
+    for p in 1..=count {
+      let remaining = count - p;
+      for end in p..=frames - remaining {
+        let first_start = end.saturating_sub(max_len).max(p - 1);
+        for start in first_start..end {
+          let previous = cost[p - 1][start];
+          if !previous.is_finite() {
+            continue;
+          }
+          let len = (end - start) as f32;
+          let duration_penalty = DURATION_WEIGHT * (len / expected[p - 1]).ln().powi(2);
+          let total = previous + sums.sse(start, end) / frame_variance + duration_penalty;
+          if total < cost[p][end] {
+            cost[p][end] = total;
+            back[p][end] = start;
+          }
+        }
+      }
+    }
+
+    if !cost[count][frames].is_finite() {
+      return proportional_spans(frames, count);
+    }
+
+    let mut spans = vec![0..0; count];
+    let mut end = frames;
+    for p in (1..=count).rev() {
+      let start = back[p][end];
+      spans[p - 1] = start..end;
+      end = start;
+    }
+
+    spans
+  }
+
+  /// Frame spans of stored boundaries, for features computed from audio starting `offset_ms` into
+  /// the recording. Like [`ForcedAligner::segment`], spans are contiguous and cover every frame;
+  /// a gap between two phonemes is split at its midpoint.
+  pub fn spans_from_boundaries(
+    &self,
+    boundaries: &[PhonemeBoundary],
+    offset_ms: u32,
+    frames: usize,
+  ) -> Vec<Range<usize>> {
+    let to_frame = |ms: u32| {
+      let frame = (ms.saturating_sub(offset_ms) as f32 / self.frame_ms).round() as usize;
+      frame.min(frames)
+    };
+    let mut starts = vec![0];
+    for pair in boundaries.windows(2) {
+      let between = pair[0].end_ms + pair[1].start_ms.saturating_sub(pair[0].end_ms) / 2;
+      starts.push(to_frame(between).max(*starts.last().unwrap_or(&0)));
+    }
+    starts.push(frames);
+    starts.windows(2).map(|pair| pair[0]..pair[1]).collect()
+  }
+
+  /// Boundaries of frame spans from [`ForcedAligner::segment`], in ms into a recording whose
+  /// features start `offset_ms` in
+  pub fn boundaries_from_spans(
+    &self,
+    spans: &[Range<usize>],
+    offset_ms: u32,
+    phonemes: &[String],
+  ) -> Vec<PhonemeBoundary> {
+    let to_ms = |frame: usize| offset_ms + (frame as f32 * self.frame_ms).round() as u32;
+    spans
+      .iter()
+      .zip(phonemes)
+      .map(|(span, phoneme)| PhonemeBoundary {
+        phoneme: phoneme.clone(),
+        start_ms: to_ms(span.start),
+        end_ms: to_ms(span.end),
+      })
+      .collect()
+  }
+
+  /// Segment the reference, project the spans onto the learner's recording through the DTW
+  /// `path` and score each phoneme by the mean frame distance along that part of the path
+  pub fn align(
+    &self,
+    user_features: &[Vec<f32>],
+    reference_features: &[Vec<f32>],
+    path: &[(usize, usize)],
+    phonemes: &[String],
+  ) -> Vec<PhonemeScore> {
+    let spans = self.segment(reference_features, phonemes);
+    self.score_spans(user_features, reference_features, path, &spans, phonemes)
+  }
+
+  /// Score phonemes whose reference frames are already known; `spans` comes from
+  /// [`ForcedAligner::segment`]
+  pub fn score_spans(
+    &self,
+    user_features: &[Vec<f32>],
+    reference_features: &[Vec<f32>],
+    path: &[(usize, usize)],
+    spans: &[Range<usize>],
+    phonemes: &[String],
+  ) -> Vec<PhonemeScore> {
+    if user_features.is_empty() || reference_features.is_empty() || path.is_empty() {
+      return unaligned(phonemes);
+    }
+
+    phonemes
+      .iter()
+      .zip(spans.iter().cloned())
+      .map(|(phoneme, span)| {
+        // Empty spans only occur when there are fewer frames than phonemes
+        let span = if span.is_empty() {
+          let frame = span.start.min(reference_features.len() - 1);
+          frame..frame + 1
+        } else {
+          span
+        };
+
+        let steps: Vec<(usize, usize)> = path
+          .iter()
+          .copied()
+          .filter(|(_, j)| span.contains(j))
+          .collect();
+        let first = steps.iter().map(|&(i, _)| i).min().unwrap_or(0);
+        let last = steps.iter().map(|&(i, _)| i).max().unwrap_or(0);
+        let mean_distance = steps
+          .iter()
+          .map(|&(i, j)| Distance::Euclidean.between(&user_features[i], &reference_features[j]))
+          .sum::<f32>()
+          / steps.len().max(1) as f32;
+
+        PhonemeScore {
+          phoneme: phoneme.clone(),
+          start_ms: (first as f32 * self.frame_ms).round() as u32,
+          end_ms: ((last + 1) as f32 * self.frame_ms).round() as u32,
+          score: distance_to_pct(mean_distance),
+          diagnosis: Diagnosis::Correct,
+          inserted_before: Vec::new(),
+          inserted_after: Vec::new(),
+        }
+      })
+      .collect()
+  }
+
+  /// Diagnose scored phonemes from the shape of the warping path.
+  ///
+  /// A phoneme the learner passes through much faster than the reference was deleted, and one
+  /// scoring below [`SUBSTITUTED_BELOW`] was substituted. Learner frames held on the reference
+  /// frames around a phoneme boundary, and unlike them, are a sound the reference does not have.
+  /// Substituted and inserted sounds are left unnamed.
+  pub fn diagnose(
+    &self,
+    per_phoneme: &mut [PhonemeScore],
+    user_features: &[Vec<f32>],
+    reference_features: &[Vec<f32>],
+    path: &[(usize, usize)],
+    spans: &[Range<usize>],
+  ) {
+    if path.is_empty() || per_phoneme.len() != spans.len() {
+      return;
+    }
+
+    for (phoneme, span) in per_phoneme.iter_mut().zip(spans) {
+      let learner: Vec<usize> = path
+        .iter()
+        .filter(|(_, j)| span.contains(j))
+        .map(|&(i, _)| i)
+        .collect();
+      let learner_frames = match (learner.iter().min(), learner.iter().max()) {
+        (Some(first), Some(last)) => last - first + 1,
+        _ => 0,
+      };
+      phoneme.diagnosis = if phoneme.score >= SUBSTITUTED_BELOW {
+        Diagnosis::Correct
+      } else if span.len() >= MIN_DELETED_FRAMES && learner_frames * DELETION_RATIO <= span.len() {
+        Diagnosis::Deleted
+      } else {
+        Diagnosis::Substituted { produced: None }
+      };
+    }
+
+    // Reference frames each learner frame is matched to; the path is monotonic, so a range
+    let user_frames = path.iter().map(|&(i, _)| i).max().map_or(0, |i| i + 1);
+    let mut matched = vec![(usize::MAX, 0, f32::INFINITY); user_frames];
+    for &(i, j) in path {
+      let (low, high, distance) = &mut matched[i];
+      *low = (*low).min(j);
+      *high = (*high).max(j);
+      *distance =
+        distance.min(Distance::Euclidean.between(&user_features[i], &reference_features[j]));
+    }
+
+    let mean_distance = matched.iter().map(|m| m.2).sum::<f32>() / user_frames as f32;
+    let unlike = INSERTION_DISTANCE.max(INSERTION_CONTRAST * mean_distance);
+
+    let reference_frames = reference_features.len();
+    for position in 0..=spans.len() {
+      let boundary = match spans.get(position) {
+        Some(span) => span.start,
+        None => reference_frames,
+      };
+      let near = boundary.saturating_sub(BOUNDARY_FRAMES)..boundary + BOUNDARY_FRAMES;
+
+      // Longest run of unlike learner frames held near the boundary
+      let mut best: Option<Range<usize>> = None;
+      let mut run_start = None;
+      for i in 0..=user_frames {
+        let held = matched.get(i).is_some_and(|&(low, high, distance)| {
+          near.contains(&low) && near.contains(&high) && distance > unlike
+        });
+        match (held, run_start) {
+          (true, None) => run_start = Some(i),
+          (false, Some(start)) => {
+            if best.as_ref().is_none_or(|b| i - start > b.len()) {
+              best = Some(start..i);
+            }
+            run_start = None;
+          }
+          _ => {}
+        }
+      }
+
+      if let Some(run) = best.filter(|r| r.len() as f32 * self.frame_ms >= INSERTION_MIN_MS) {
+        diagnosis::attach_insertion(
+          per_phoneme,
+          position,
+          Insertion {
+            sound: None,
+            start_ms: (run.start as f32 * self.frame_ms).round() as u32,
+            end_ms: (run.end as f32 * self.frame_ms).round() as u32,
+          },
+        );
+      }
+    }
+  }
+}
+
+/// Zero scores for phonemes that could not be aligned
+fn unaligned(phonemes: &[String]) -> Vec<PhonemeScore> {
+  phonemes
+    .iter()
+    .map(|phoneme| PhonemeScore {
+      phoneme: phoneme.clone(),
+      start_ms: 0,
+      end_ms: 0,
+      score: 0.0,
+      diagnosis: Diagnosis::Deleted,
+      inserted_before: Vec::new(),
+      inserted_after: Vec::new(),
+    })
+    .collect()
+}
+
+/// Map a mean local distance to a 0-100 score
+pub(crate) fn distance_to_pct(distance: f32) -> f32 {
+  ((1.0 - (distance / DISTANCE_SCALE).min(1.0)) * 100.0).clamp(0.0, 100.0)
+}
+
+/// Evenly sized spans, used when there are too few frames to segment
+fn proportional_spans(frames: usize, count: usize) -> Vec<Range<usize>> {
+  (0..count)
+    .map(|p| p * frames / count..(p + 1) * frames / count)
+    .collect()
+}
+
+/// Per-dimension prefix sums for constant-time segment variance
+struct PrefixSums {
+  sum: Vec<Vec<f64>>,
+  sum_sq: Vec<Vec<f64>>,
+}
+
+impl PrefixSums {
+  fn new(features: &[Vec<f32>]) -> Self {
+    let dim = features.first().map_or(0, Vec::len);
+    let mut sum = vec![vec![0.0; dim]];
+    let mut sum_sq = vec![vec![0.0; dim]];
+
+    for frame in features {
+      let next: Vec<f64> = sum
+        .last()
+        .unwrap()
+        .iter()
+        .zip(frame)
+        .map(|(s, &x)| s + x as f64)
+        .collect();
+      let next_sq: Vec<f64> = sum_sq
+        .last()
+        .unwrap()
+        .iter()
+        .zip(frame)
+        .map(|(s, &x)| s + (x as f64) * (x as f64))
+        .collect();
+      sum.push(next);
+      sum_sq.push(next_sq);
+    }
+
+    Self { sum, sum_sq }
+  }
+
+  /// Sum of squared deviations from the mean over frames `start..end`
+  fn sse(&self, start: usize, end: usize) -> f32 {
+    let n = (end - start) as f64;
+    let sse: f64 = self.sum[end]
+      .iter()
+      .zip(&self.sum[start])
+      .zip(self.sum_sq[end].iter().zip(&self.sum_sq[start]))
+      .map(|((s_end, s_start), (q_end, q_start))| {
+        let s = s_end - s_start;
+        (q_end - q_start - s * s / n).max(0.0)
+      })
+      .sum();
+    sse as f32
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::DTWAligner;
+
+  fn phonemes(symbols: &[&str]) -> Vec<String> {
+    symbols.iter().map(|s| s.to_string()).collect()
+  }
+
+  /// Piecewise-constant features with a little deterministic jitter
+  fn blocks(levels: &[(f32, usize)]) -> Vec<Vec<f32>> {
+    levels
+      .iter()
+      .flat_map(|&(level, len)| std::iter::repeat_n(level, len))
+      .enumerate()
+      .map(|(i, level)| {
+        let jitter = ((i * 7919) % 13) as f32 / 130.0;
+        vec![level + jitter, -level + jitter, level * 0.5]
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_segment_finds_spectral_boundaries() {
+    let aligner = ForcedAligner::new(256, 16000);
+    let features = blocks(&[(0.0, 10), (5.0, 20), (-3.0, 8)]);
+    let spans = aligner.segment(&features, &phonemes(&["k", "æ", "t"]));
+    assert_eq!(spans, vec![0..10, 10..30, 30..38]);
+  }
+
+  #[test]
+  fn test_segment_with_too_few_frames() {
+    let aligner = ForcedAligner::new(256, 16000);
+    let features = blocks(&[(1.0, 2)]);
+    let spans = aligner.segment(&features, &phonemes(&["s", "t", "ɑ", "p"]));
+    assert_eq!(spans.len(), 4);
+    assert_eq!(spans.last().unwrap().end, 2);
+  }
+
+  #[test]
+  fn test_align_projects_boundaries_onto_user_timeline() {
+    let aligner = ForcedAligner::new(256, 16000);
+    let reference = blocks(&[(0.0, 10), (5.0, 20), (-3.0, 8)]);
+    // The learner holds the first consonant longer and rushes the vowel
+    let user = blocks(&[(0.0, 16), (5.0, 12), (-3.0, 10)]);
+
+    let path = DTWAligner::default().align(&user, &reference).path;
+    let scores = aligner.align(&user, &reference, &path, &phonemes(&["k", "æ", "t"]));
+    let keys: Vec<&str> = scores.iter().map(|s| s.phoneme.as_str()).collect();
+    assert_eq!(keys, vec!["k", "æ", "t"]);
+
+    assert_eq!(scores[0].start_ms, 0);
+    assert_eq!(scores[1].start_ms, 256);
+    assert_eq!(scores[2].start_ms, 448);
+    assert_eq!(scores[2].end_ms, 608);
+    assert!(scores.iter().all(|s| s.score > 90.0));
+  }
+
+  #[test]
+  fn test_align_flags_the_wrong_phoneme() {
+    let aligner = ForcedAligner::new(256, 16000);
+    let reference = blocks(&[(0.0, 10), (5.0, 20), (-3.0, 8)]);
+    let user = blocks(&[(0.0, 10), (2.0, 20), (-3.0, 8)]);
+
+    let path = DTWAligner::default().align(&user, &reference).path;
+    let scores = aligner.align(&user, &reference, &path, &phonemes(&["k", "æ", "t"]));
+    assert!(scores[1].score < scores[0].score);
+    assert!(scores[1].score < scores[2].score);
+  }
+
+  #[test]
+  fn test_diagnose_reads_deletions_and_insertions_off_the_path() {
+    let aligner = ForcedAligner::new(256, 16000);
+    let reference = blocks(&[(0.0, 10), (5.0, 8), (-3.0, 12)]);
+    let target = phonemes(&["k", "l", "æ"]);
+    let spans = aligner.segment(&reference, &target);
+    let diagnose = |user: &[Vec<f32>]| {
+      let path = DTWAligner::default().align(user, &reference).path;
+      let mut scores = aligner.score_spans(user, &reference, &path, &spans, &target);
+      aligner.diagnose(&mut scores, user, &reference, &path, &spans);
+      scores
+    };
+
+    let correct = diagnose(&blocks(&[(0.0, 12), (5.0, 7), (-3.0, 12)]));
+    assert!(correct.iter().all(|s| s.diagnosis == Diagnosis::Correct));
+    assert!(correct.iter().all(|s| s.inserted_before.is_empty()));
+
+    let deleted = diagnose(&blocks(&[(0.0, 10), (-3.0, 12)]));
+    assert_eq!(deleted[1].diagnosis, Diagnosis::Deleted);
+    assert_eq!(deleted[2].diagnosis, Diagnosis::Correct);
+
+    // Six frames of a sound unlike either neighbour before the /l/
+    let inserted = diagnose(&blocks(&[(0.0, 10), (9.0, 6), (5.0, 8), (-3.0, 12)]));
+    assert_eq!(
+      inserted[1].inserted_before,
+      vec![Insertion {
+        sound: None,
+        start_ms: 160,
+        end_ms: 256
+      }]
+    );
+    assert_eq!(inserted[1].diagnosis, Diagnosis::Correct);
+  }
+
+  #[test]
+  fn test_boundaries_round_trip_through_frame_spans() {
+    let aligner = ForcedAligner::new(256, 16000);
+    let symbols = phonemes(&["k", "æ", "t"]);
+    let spans = vec![0..10, 10..30, 30..38];
+    let boundaries = aligner.boundaries_from_spans(&spans, 200, &symbols);
+    assert_eq!(boundaries[1].start_ms, 360);
+    assert!(check_boundaries(&boundaries, &symbols).is_ok());
+    assert_eq!(aligner.spans_from_boundaries(&boundaries, 200, 38), spans);
+
+    // An editor's gap before the vowel is shared out, and times past the end are clamped
+    let edited =
+      [("k", 200, 300), ("æ", 340, 680), ("t", 680, 900)].map(|(p, start, end)| PhonemeBoundary {
+        phoneme: p.to_string(),
+        start_ms: start,
+        end_ms: end,
+      });
+    assert_eq!(
+      aligner.spans_from_boundaries(&edited, 200, 38),
+      vec![0..8, 8..30, 30..38]
+    );
+    assert!(check_boundaries(&edited[..2], &symbols).is_err());
+    let mut overlapping = edited.clone();
+    overlapping[1].start_ms = 250;
+    assert!(check_boundaries(&overlapping, &symbols).is_err());
+  }
+}
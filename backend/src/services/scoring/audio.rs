@@ -0,0 +1,327 @@
+use anyhow::{bail, Result};
+use hound::{WavSpec, WavWriter};
+use std::io::Cursor;
+use std::path::Path;
+
+use super::{decode, SAMPLE_RATE};
+
+/// Zero crossings of the sinc kernel on each side of the centre tap
+const SINC_ZERO_CROSSINGS: f32 = 16.0;
+
+/// Passband edge as a fraction of the lower Nyquist frequency
+const ROLLOFF: f32 = 0.92;
+
+/// Kaiser window shape; 8.0 gives roughly 80 dB of stopband attenuation
+const KAISER_BETA: f32 = 8.0;
+
+/// Upper bound on precomputed filter phases for awkward rate ratios
+const MAX_PHASES: usize = 1024;
+
+/// Band-limited rational resampler using a Kaiser-windowed sinc polyphase filter
+pub struct Resampler {
+  up: u64,
+  down: u64,
+  half_taps: usize,
+  phases: Vec<Vec<f32>>,
+}
+
+impl Resampler {
+  pub fn new(from_rate: u32, to_rate: u32) -> Result<Self> {
+    if from_rate == 0 || to_rate == 0 {
+      bail!("cannot resample {} Hz to {} Hz", from_rate, to_rate);
+    }
+
+    let divisor = gcd(from_rate as u64, to_rate as u64);
+    let up = to_rate as u64 / divisor;
+    let down = from_rate as u64 / divisor;
+
+    // Cutoff in cycles per input sample, below both the input and output Nyquist
+    let cutoff = 0.5 * ROLLOFF * (up as f32 / down as f32).min(1.0);
+    let half_taps = (SINC_ZERO_CROSSINGS / (2.0 * cutoff)).ceil() as usize;
+    let num_phases = (up as usize).min(MAX_PHASES);
+
+    let phases = (0..num_phases)
+      .map(|phase| {
+        let frac = phase as f32 / num_phases as f32;
+        let mut taps: Vec<f32> = (0..2 * half_taps)
+          .map(|m| {
+            let offset = frac + half_taps as f32 - 1.0 - m as f32;
+            windowed_sinc(offset, cutoff, half_taps as f32)
+          })
+          .collect();
+
+        // Unity DC gain for every phase
+        let sum: f32 = taps.iter().sum();
+        if sum.abs() > f32::EPSILON {
+          taps.iter_mut().for_each(|t| *t /= sum);
+        }
+        taps
+      })
+      .collect();
+
+    Ok(Self {
+      up,
+      down,
+      half_taps,
+      phases,
+    })
+  }
+
+  pub fn process(&self, input: &[f32]) -> Vec<f32> {
+    if self.up == self.down {
+      return input.to_vec();
+    }
+
+    let output_len = (input.len() as u64 * self.up).div_ceil(self.down) as usize;
+    let num_phases = self.phases.len() as u64;
+    let mut output = Vec::with_capacity(output_len);
+
+    for n in 0..output_len as u64 {
+      let position = n * self.down;
+      let base = (position / self.up) as isize;
+      let phase = ((position % self.up) * num_phases / self.up) as usize;
+      let first = base + 1 - self.half_taps as isize;
+
+      let sample = self.phases[phase]
+        .iter()
+        .enumerate()
+        .filter_map(|(m, &tap)| {
+          let index = first + m as isize;
+          (index >= 0 && (index as usize) < input.len()).then(|| tap * input[index as usize])
+        })
+        .sum();
+      output.push(sample);
+    }
+
+    output
+  }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
+
+/// Low-pass sinc at `cutoff` cycles per sample, tapered by a Kaiser window of half-width `half`
+fn windowed_sinc(offset: f32, cutoff: f32, half: f32) -> f32 {
+  if offset.abs() >= half {
+    return 0.0;
+  }
+
+  let x = 2.0 * cutoff * offset;
+  let sinc = if x.abs() < 1e-6 {
+    1.0
+  } else {
+    (std::f32::consts::PI * x).sin() / (std::f32::consts::PI * x)
+  };
+  let ratio = offset / half;
+  let window = bessel_i0(KAISER_BETA * (1.0 - ratio * ratio).sqrt()) / bessel_i0(KAISER_BETA);
+
+  2.0 * cutoff * sinc * window
+}
+
+/// Zeroth-order modified Bessel function of the first kind (power series)
+fn bessel_i0(x: f32) -> f32 {
+  let half_x = x / 2.0;
+  let mut term = 1.0;
+  let mut sum = 1.0;
+  for k in 1..32 {
+    term *= (half_x / k as f32).powi(2);
+    sum += term;
+    if term < sum * 1e-9 {
+      break;
+    }
+  }
+  sum
+}
+
+/// Audio processing utilities
+pub struct AudioProcessor;
+
+impl AudioProcessor {
+  /// Convert audio to 16kHz mono for processing
+  pub fn preprocess_audio(audio_data: &[f32], sample_rate: u32) -> Result<Vec<f32>> {
+    if sample_rate == SAMPLE_RATE {
+      return Ok(audio_data.to_vec());
+    }
+
+    Ok(Resampler::new(sample_rate, SAMPLE_RATE)?.process(audio_data))
+  }
+
+  /// Convert interleaved multichannel audio to 16kHz mono
+  pub fn preprocess_interleaved(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+  ) -> Result<Vec<f32>> {
+    let mono = Self::downmix(samples, channels)?;
+    Self::preprocess_audio(&mono, sample_rate)
+  }
+
+  /// Average interleaved channels into a single mono channel
+  pub fn downmix(samples: &[f32], channels: u16) -> Result<Vec<f32>> {
+    match channels {
+      0 => bail!("audio has no channels"),
+      1 => Ok(samples.to_vec()),
+      _ => {
+        let channels = channels as usize;
+        if !samples.len().is_multiple_of(channels) {
+          bail!(
+            "{} samples do not divide into {} channels",
+            samples.len(),
+            channels
+          );
+        }
+        Ok(
+          samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect(),
+        )
+      }
+    }
+  }
+
+  /// Decode a recording in any supported format and convert it to 16kHz mono
+  pub fn decode_audio(bytes: &[u8]) -> Result<Vec<f32>> {
+    let decoded = decode::decode(bytes)?;
+    Self::preprocess_interleaved(&decoded.samples, decoded.sample_rate, decoded.channels)
+  }
+
+  /// Load audio from a file, detecting the format from its contents
+  pub fn load_audio_file<P: AsRef<Path>>(path: P) -> Result<Vec<f32>> {
+    Self::decode_audio(&std::fs::read(path)?)
+  }
+
+  /// Save audio to WAV file
+  pub fn save_wav_file<P: AsRef<Path>>(path: P, audio_data: &[f32]) -> Result<()> {
+    std::fs::write(path, Self::encode_wav(audio_data)?)?;
+    Ok(())
+  }
+
+  /// Encode 16kHz mono audio as a 16-bit WAV file
+  pub fn encode_wav(audio_data: &[f32]) -> Result<Vec<u8>> {
+    let spec = WavSpec {
+      channels: 1,
+      sample_rate: SAMPLE_RATE,
+      bits_per_sample: 16,
+      sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut bytes = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut bytes, spec)?;
+    for &sample in audio_data {
+      let sample_int = (sample * i16::MAX as f32) as i16;
+      writer.write_sample(sample_int)?;
+    }
+    writer.finalize()?;
+
+    Ok(bytes.into_inner())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn tone(freq: f32, rate: u32, seconds: f32) -> Vec<f32> {
+    (0..(rate as f32 * seconds) as usize)
+      .map(|n| (2.0 * std::f32::consts::PI * freq * n as f32 / rate as f32).sin())
+      .collect()
+  }
+
+  /// RMS away from the edges, where the filter is only partially filled
+  fn steady_rms(signal: &[f32]) -> f32 {
+    let trim = signal.len() / 10;
+    let body = &signal[trim..signal.len() - trim];
+    (body.iter().map(|s| s * s).sum::<f32>() / body.len() as f32).sqrt()
+  }
+
+  #[test]
+  fn test_resampled_length_and_passband() {
+    for rate in [44100, 48000] {
+      let input = tone(1000.0, rate, 0.5);
+      let output = AudioProcessor::preprocess_audio(&input, rate).unwrap();
+      assert_eq!(output.len(), 8000);
+
+      // A 1 kHz sine keeps its RMS of 1/sqrt(2)
+      let rms = steady_rms(&output);
+      assert!(
+        (rms - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01,
+        "rms {}",
+        rms
+      );
+    }
+  }
+
+  #[test]
+  fn test_resampler_suppresses_aliasing() {
+    // Above the 8 kHz output Nyquist these would fold back to audible tones
+    for (rate, freq) in [(48000, 12000.0), (48000, 20000.0), (44100, 10000.0)] {
+      let input = tone(freq, rate, 0.5);
+      let output = AudioProcessor::preprocess_audio(&input, rate).unwrap();
+      let rms = steady_rms(&output);
+      // At least 40 dB below the input level
+      assert!(
+        rms < 0.01 * steady_rms(&input),
+        "{} Hz alias rms {}",
+        freq,
+        rms
+      );
+    }
+  }
+
+  #[test]
+  fn test_upsampling_preserves_tone() {
+    let input = tone(440.0, 8000, 0.5);
+    let output = AudioProcessor::preprocess_audio(&input, 8000).unwrap();
+    assert_eq!(output.len(), 8000);
+
+    let expected = tone(440.0, SAMPLE_RATE, 0.5);
+    let body = 800..7200;
+    let max_error = output[body.clone()]
+      .iter()
+      .zip(&expected[body])
+      .map(|(a, b)| (a - b).abs())
+      .fold(0.0, f32::max);
+    assert!(max_error < 0.01, "max error {}", max_error);
+  }
+
+  #[test]
+  fn test_wav_round_trip_through_decoder() {
+    let path = std::env::temp_dir().join(format!("ipa-audio-{}.wav", uuid::Uuid::new_v4()));
+    let input = tone(440.0, SAMPLE_RATE, 0.1);
+    AudioProcessor::save_wav_file(&path, &input).unwrap();
+    let loaded = AudioProcessor::load_audio_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.len(), input.len());
+    assert!(loaded.iter().zip(&input).all(|(a, b)| (a - b).abs() < 1e-3));
+  }
+
+  #[test]
+  fn test_downmix_stereo() {
+    let interleaved = vec![1.0, 0.0, 0.5, 0.5, -1.0, 1.0];
+    assert_eq!(
+      AudioProcessor::downmix(&interleaved, 2).unwrap(),
+      vec![0.5, 0.5, 0.0]
+    );
+    assert!(AudioProcessor::downmix(&interleaved, 4).is_err());
+    assert!(AudioProcessor::downmix(&interleaved, 0).is_err());
+  }
+
+  #[test]
+  fn test_stereo_48k_to_mono_16k() {
+    // Left carries the tone, right is silent: the mix is half amplitude, not a garbled interleave
+    let left = tone(500.0, 48000, 0.25);
+    let interleaved: Vec<f32> = left.iter().flat_map(|&s| [s, 0.0]).collect();
+    let output = AudioProcessor::preprocess_interleaved(&interleaved, 48000, 2).unwrap();
+    assert_eq!(output.len(), 4000);
+
+    let rms = steady_rms(&output);
+    assert!((rms - 0.5 * std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+  }
+}
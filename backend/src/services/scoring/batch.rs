@@ -0,0 +1,173 @@
+use anyhow::{ensure, Result};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+
+use super::confidence::ConfidenceModel;
+use super::engine::{scorer_from_config, EngineId, Scorer, ScoringInput};
+use super::PronunciationScore;
+
+/// A fixed set of scoring engines shared between threads, so attempts are scored in parallel
+/// with at most one attempt per engine at a time
+pub struct ScorerPool {
+  idle: Mutex<Vec<Box<dyn Scorer>>>,
+  returned: Condvar,
+  size: usize,
+  engine: EngineId,
+  uses_reference_audio: bool,
+}
+
+impl ScorerPool {
+  /// Pool the given engines, which must all be the same engine
+  pub fn new(scorers: Vec<Box<dyn Scorer>>) -> Result<Self> {
+    ensure!(
+      !scorers.is_empty(),
+      "a scorer pool needs at least one engine"
+    );
+    let engine = scorers[0].engine();
+    ensure!(
+      scorers.iter().all(|s| s.engine() == engine),
+      "all engines in a scorer pool must be {} {}",
+      engine.name,
+      engine.version
+    );
+    Ok(Self {
+      size: scorers.len(),
+      uses_reference_audio: scorers[0].uses_reference_audio(),
+      engine,
+      idle: Mutex::new(scorers),
+      returned: Condvar::new(),
+    })
+  }
+
+  /// `size` instances of the engine named in configuration
+  pub fn from_config(
+    name: &str,
+    gop_model_dir: Option<&Path>,
+    confidence: Option<&ConfidenceModel>,
+    size: usize,
+  ) -> Result<Self> {
+    let scorers = (0..size.max(1))
+      .map(|_| scorer_from_config(name, gop_model_dir, confidence))
+      .collect::<Result<Vec<_>>>()?;
+    Self::new(scorers)
+  }
+
+  /// How many attempts can be scored at once
+  pub fn size(&self) -> usize {
+    self.size
+  }
+
+  pub fn engine(&self) -> EngineId {
+    self.engine
+  }
+
+  pub fn uses_reference_audio(&self) -> bool {
+    self.uses_reference_audio
+  }
+
+  /// Score on the next idle engine, blocking until one is free
+  pub fn score(&self, input: &ScoringInput) -> Result<PronunciationScore> {
+    let mut scorer = {
+      let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+      loop {
+        match idle.pop() {
+          Some(scorer) => break scorer,
+          None => idle = self.returned.wait(idle).unwrap_or_else(|e| e.into_inner()),
+        }
+      }
+    };
+    let score = scorer.score(input);
+    self
+      .idle
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .push(scorer);
+    self.returned.notify_one();
+    score
+  }
+
+  /// Score every input, one thread per engine; results are in input order
+  pub fn score_batch(&self, inputs: &[ScoringInput]) -> Vec<Result<PronunciationScore>> {
+    let next = AtomicUsize::new(0);
+    let mut scored: Vec<(usize, Result<PronunciationScore>)> = std::thread::scope(|scope| {
+      let threads: Vec<_> = (0..self.size.min(inputs.len()))
+        .map(|_| {
+          scope.spawn(|| {
+            let mut scored = Vec::new();
+            loop {
+              let i = next.fetch_add(1, Ordering::Relaxed);
+              let Some(input) = inputs.get(i) else {
+                return scored;
+              };
+              scored.push((i, self.score(input)));
+            }
+          })
+        })
+        .collect();
+      threads
+        .into_iter()
+        .flat_map(|thread| thread.join().expect("scoring thread panicked"))
+        .collect()
+    });
+    scored.sort_by_key(|(i, _)| *i);
+    scored.into_iter().map(|(_, score)| score).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::Dialect;
+  use crate::services::ipa::Transcription;
+  use crate::services::scoring::{
+    FormantSynthesizer, PronunciationScorer, QualityGate, QualityReport, Reference, MFCC_DTW,
+  };
+
+  #[test]
+  fn test_batch_matches_scoring_one_at_a_time() {
+    let words = ["ˈnɑlmi", "ˈglæd", "ˈmɑnə", "ˈlɑmə", "ˈnoʊm"];
+    let synthesizer = FormantSynthesizer::for_dialect(&Dialect::GA);
+    let targets: Vec<Transcription> = words.iter().map(|w| Transcription::parse(w)).collect();
+    let audio: Vec<Vec<f32>> = targets
+      .iter()
+      .map(|t| synthesizer.synthesize(t).unwrap().audio)
+      .collect();
+    // Each word is attempted against the next word's reference, so scores differ
+    let references: Vec<[Reference; 1]> = (0..words.len())
+      .map(|i| [Reference::new(&audio[(i + 1) % words.len()])])
+      .collect();
+    let quality: Vec<QualityReport> = audio
+      .iter()
+      .map(|a| QualityGate::default().check(a, None))
+      .collect();
+    let inputs: Vec<ScoringInput> = (0..words.len())
+      .map(|i| ScoringInput {
+        audio: &audio[i],
+        quality: &quality[i],
+        target: &targets[i],
+        dialect: &Dialect::GA,
+        references: &references[i],
+        competitors: &[],
+      })
+      .collect();
+
+    let pool = ScorerPool::from_config("mfcc-dtw", None, None, 3).unwrap();
+    assert_eq!((pool.size(), pool.engine()), (3, MFCC_DTW));
+    let batch = pool.score_batch(&inputs);
+    assert_eq!(batch.len(), inputs.len());
+
+    let mut scorer = PronunciationScorer::new();
+    for (input, pooled) in inputs.iter().zip(batch) {
+      let alone = scorer.score(input).unwrap();
+      assert_eq!(pooled.unwrap().overall_pct, alone.overall_pct);
+    }
+    assert!(pool.score_batch(&[]).is_empty());
+  }
+
+  #[test]
+  fn test_pool_needs_a_known_engine() {
+    assert!(ScorerPool::new(Vec::new()).is_err());
+    assert!(ScorerPool::from_config("wav2vec", None, None, 2).is_err());
+  }
+}
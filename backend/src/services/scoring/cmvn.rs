@@ -0,0 +1,187 @@
+use serde::Deserialize;
+
+/// Standard deviations below this are treated as constant and only mean-normalised
+const MIN_STD: f64 = 1e-5;
+
+/// Cepstral mean and variance normalisation applied to every feature dimension.
+///
+/// A fixed microphone or channel response multiplies the spectrum, which becomes a constant
+/// offset on the cepstrum; removing each dimension's mean cancels it, and scaling to unit
+/// variance evens out differences in level and dynamic range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cmvn {
+  Off,
+  /// Statistics over the whole utterance
+  Utterance,
+  /// Statistics over a centred window of `frames` frames, for recordings whose channel drifts
+  Sliding {
+    frames: usize,
+  },
+}
+
+impl Cmvn {
+  pub fn apply(self, features: &mut [Vec<f32>]) {
+    if features.is_empty() {
+      return;
+    }
+
+    match self {
+      Cmvn::Off => {}
+      Cmvn::Utterance => {
+        let stats = Stats::over(features);
+        for frame in features.iter_mut() {
+          stats.normalize(frame);
+        }
+      }
+      Cmvn::Sliding { frames } => {
+        let sums = PrefixSums::new(features);
+        let half = frames.max(1) / 2;
+        for (t, frame) in features.iter_mut().enumerate() {
+          let start = t.saturating_sub(half);
+          let end = (t + half + 1).min(sums.len());
+          sums.stats(start, end).normalize(frame);
+        }
+      }
+    }
+  }
+}
+
+/// Per-dimension mean and standard deviation
+struct Stats {
+  mean: Vec<f64>,
+  std: Vec<f64>,
+}
+
+impl Stats {
+  fn over(features: &[Vec<f32>]) -> Self {
+    PrefixSums::new(features).stats(0, features.len())
+  }
+
+  fn normalize(&self, frame: &mut [f32]) {
+    for ((x, mean), std) in frame.iter_mut().zip(&self.mean).zip(&self.std) {
+      let centred = *x as f64 - mean;
+      *x = if *std > MIN_STD {
+        centred / std
+      } else {
+        centred
+      } as f32;
+    }
+  }
+}
+
+/// Running sums of each dimension and its square
+struct PrefixSums {
+  sum: Vec<Vec<f64>>,
+  sum_sq: Vec<Vec<f64>>,
+}
+
+impl PrefixSums {
+  fn new(features: &[Vec<f32>]) -> Self {
+    let dim = features.first().map_or(0, Vec::len);
+    let mut sum = vec![vec![0.0; dim]];
+    let mut sum_sq = vec![vec![0.0; dim]];
+
+    for frame in features {
+      let (last, last_sq) = (sum.last().unwrap(), sum_sq.last().unwrap());
+      let next = last.iter().zip(frame).map(|(s, &x)| s + x as f64).collect();
+      let next_sq = last_sq
+        .iter()
+        .zip(frame)
+        .map(|(s, &x)| s + (x as f64) * (x as f64))
+        .collect();
+      sum.push(next);
+      sum_sq.push(next_sq);
+    }
+
+    Self { sum, sum_sq }
+  }
+
+  /// Number of frames covered
+  fn len(&self) -> usize {
+    self.sum.len() - 1
+  }
+
+  fn stats(&self, start: usize, end: usize) -> Stats {
+    let n = (end - start) as f64;
+    let (mean, std) = self.sum[end]
+      .iter()
+      .zip(&self.sum[start])
+      .zip(self.sum_sq[end].iter().zip(&self.sum_sq[start]))
+      .map(|((s_end, s_start), (q_end, q_start))| {
+        let mean = (s_end - s_start) / n;
+        let variance = ((q_end - q_start) / n - mean * mean).max(0.0);
+        (mean, variance.sqrt())
+      })
+      .unzip();
+    Stats { mean, std }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn features() -> Vec<Vec<f32>> {
+    (0..50)
+      .map(|t| {
+        let t = t as f32;
+        vec![(t * 0.3).sin() * 4.0 + 10.0, (t * 0.11).cos() - 3.0, 7.0]
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_utterance_cmvn_gives_zero_mean_unit_variance() {
+    let mut normalized = features();
+    Cmvn::Utterance.apply(&mut normalized);
+
+    for d in 0..2 {
+      let values: Vec<f32> = normalized.iter().map(|f| f[d]).collect();
+      let mean = values.iter().sum::<f32>() / values.len() as f32;
+      let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+      assert!(mean.abs() < 1e-4, "dim {} mean {}", d, mean);
+      assert!(
+        (variance - 1.0).abs() < 1e-3,
+        "dim {} variance {}",
+        d,
+        variance
+      );
+    }
+    // A constant dimension is centred but not blown up
+    assert!(normalized.iter().all(|f| f[2].abs() < 1e-6));
+  }
+
+  #[test]
+  fn test_cmvn_removes_channel_offset_and_gain() {
+    let original = features();
+    // A channel adds a constant to each cepstral dimension; a level change scales it
+    let shifted: Vec<Vec<f32>> = original
+      .iter()
+      .map(|f| vec![f[0] * 2.0 + 5.0, f[1] - 1.5, f[2] + 3.0])
+      .collect();
+
+    for mode in [Cmvn::Utterance, Cmvn::Sliding { frames: 21 }] {
+      let (mut a, mut b) = (original.clone(), shifted.clone());
+      mode.apply(&mut a);
+      mode.apply(&mut b);
+      for (x, y) in a.iter().flatten().zip(b.iter().flatten()) {
+        assert!((x - y).abs() < 1e-3, "{:?}: {} vs {}", mode, x, y);
+      }
+    }
+  }
+
+  #[test]
+  fn test_sliding_window_covering_utterance_matches_utterance() {
+    let mut utterance = features();
+    let mut sliding = features();
+    Cmvn::Utterance.apply(&mut utterance);
+    Cmvn::Sliding { frames: 1000 }.apply(&mut sliding);
+    assert_eq!(utterance, sliding);
+
+    // A short window tracks local statistics instead
+    let mut local = features();
+    Cmvn::Sliding { frames: 5 }.apply(&mut local);
+    assert_ne!(utterance, local);
+  }
+}
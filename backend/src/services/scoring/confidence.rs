@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::quality::{QualityReport, MAX_SNR_DB};
+
+/// Scores below this confidence should not be presented as a verdict on the learner
+pub const LOW_CONFIDENCE: f32 = 0.5;
+
+const FEATURES: usize = 5;
+
+/// Evidence about how far a score can be trusted, independent of the score itself
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceFeatures {
+  /// Loud against quiet frames of the learner's recording, in dB
+  pub snr_db: f32,
+  /// Fraction of samples at full scale
+  pub clipped_fraction: f32,
+  /// Fraction of DTW path steps that advance both recordings, or for posterior-based engines the
+  /// fraction of frames where the model's best phone matches the alignment
+  pub path_stability: f32,
+  /// `(competitor - target) / (competitor + target)` alignment cost against the closest
+  /// minimal-pair word; positive when the learner sounds more like the target
+  pub competitor_margin: Option<f32>,
+  /// Length of detected speech
+  pub speech_ms: u32,
+}
+
+impl ConfidenceFeatures {
+  /// Collect the evidence for one scored attempt; recording conditions come from the quality
+  /// check of the recording as made
+  pub fn measure(
+    quality: &QualityReport,
+    path_stability: f32,
+    speech_ms: u32,
+    competitor_margin: Option<f32>,
+  ) -> Self {
+    Self {
+      snr_db: quality.snr_db,
+      clipped_fraction: quality.clipped_fraction,
+      path_stability,
+      competitor_margin,
+      speech_ms,
+    }
+  }
+
+  fn values(&self) -> [Option<f32>; FEATURES] {
+    [
+      Some(self.snr_db.clamp(0.0, MAX_SNR_DB)),
+      Some(self.clipped_fraction),
+      Some(self.path_stability),
+      self.competitor_margin,
+      Some((self.speech_ms.max(1) as f32).ln()),
+    ]
+  }
+}
+
+/// Logistic model from confidence features to the probability that a score agrees with a
+/// listener's judgement.
+///
+/// Features are centred on `means`, so a missing feature (no minimal pairs to compare against)
+/// contributes nothing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceModel {
+  pub bias: f32,
+  pub weights: [f32; FEATURES],
+  pub means: [f32; FEATURES],
+}
+
+impl Default for ConfidenceModel {
+  /// Hand-set starting coefficients; refit with [`ConfidenceModel::fit`] on listener-labelled scores
+  fn default() -> Self {
+    Self {
+      bias: 1.0,
+      // SNR, clipping, path stability, competitor margin, log speech length
+      weights: [0.12, -25.0, 4.0, 6.0, 1.5],
+      means: [25.0, 0.0, 0.7, 0.0, 500f32.ln()],
+    }
+  }
+}
+
+impl ConfidenceModel {
+  pub fn predict(&self, features: &ConfidenceFeatures) -> f32 {
+    let logit = features
+      .values()
+      .iter()
+      .zip(self.weights.iter().zip(&self.means))
+      .map(|(value, (weight, mean))| value.map_or(0.0, |v| weight * (v - mean)))
+      .sum::<f32>()
+      + self.bias;
+    sigmoid(logit)
+  }
+
+  /// Fit by L2-regularised logistic regression on scores labelled `true` where a listener agreed
+  /// with the automatic verdict
+  pub fn fit(samples: &[(ConfidenceFeatures, bool)]) -> Self {
+    const ITERATIONS: usize = 3000;
+    const LEARNING_RATE: f32 = 0.5;
+    const L2: f32 = 1e-3;
+
+    let rows: Vec<[Option<f32>; FEATURES]> = samples.iter().map(|(f, _)| f.values()).collect();
+    let mut means = [0.0; FEATURES];
+    let mut scales = [1.0; FEATURES];
+    for k in 0..FEATURES {
+      let present: Vec<f32> = rows.iter().filter_map(|row| row[k]).collect();
+      if present.is_empty() {
+        continue;
+      }
+      let mean = present.iter().sum::<f32>() / present.len() as f32;
+      let variance = present.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / present.len() as f32;
+      means[k] = mean;
+      scales[k] = variance.sqrt().max(1e-6);
+    }
+
+    // Gradient descent on standardised features, mapped back to raw units at the end
+    let standardized: Vec<[f32; FEATURES]> = rows
+      .iter()
+      .map(|row| std::array::from_fn(|k| row[k].map_or(0.0, |v| (v - means[k]) / scales[k])))
+      .collect();
+    let n = samples.len().max(1) as f32;
+    let (mut bias, mut weights) = (0.0f32, [0.0f32; FEATURES]);
+    for _ in 0..ITERATIONS {
+      let mut bias_gradient = 0.0;
+      let mut gradient = [0.0; FEATURES];
+      for (x, (_, label)) in standardized.iter().zip(samples) {
+        let logit = bias + x.iter().zip(&weights).map(|(x, w)| x * w).sum::<f32>();
+        let error = sigmoid(logit) - if *label { 1.0 } else { 0.0 };
+        bias_gradient += error;
+        for k in 0..FEATURES {
+          gradient[k] += error * x[k];
+        }
+      }
+      bias -= LEARNING_RATE * bias_gradient / n;
+      for k in 0..FEATURES {
+        weights[k] -= LEARNING_RATE * (gradient[k] / n + L2 * weights[k]);
+      }
+    }
+
+    Self {
+      bias,
+      weights: std::array::from_fn(|k| weights[k] / scales[k]),
+      means,
+    }
+  }
+
+  /// The coefficients fitted for `engine` in a file written by [`Self::save`], if it has any.
+  /// Engines weigh the same evidence differently, so each is fitted separately.
+  pub fn load(path: &Path, engine: &str) -> Result<Option<Self>> {
+    let mut models = read_models(path)?;
+    Ok(models.remove(engine))
+  }
+
+  /// Store these coefficients for `engine`, keeping those of other engines already in the file
+  pub fn save(&self, path: &Path, engine: &str) -> Result<()> {
+    let mut models = if path.exists() {
+      read_models(path)?
+    } else {
+      BTreeMap::new()
+    };
+    models.insert(engine.to_string(), self.clone());
+    std::fs::write(path, serde_json::to_string_pretty(&models)?)
+      .with_context(|| format!("Writing {}", path.display()))
+  }
+
+  /// Expected calibration error over ten equal-width bins: the mean gap, weighted by bin size,
+  /// between predicted confidence and the observed agreement rate
+  pub fn calibration_error(&self, samples: &[(ConfidenceFeatures, bool)]) -> f32 {
+    const BINS: usize = 10;
+    let mut predicted = [0.0f32; BINS];
+    let mut observed = [0.0f32; BINS];
+    let mut counts = [0usize; BINS];
+    for (features, label) in samples {
+      let p = self.predict(features);
+      let bin = ((p * BINS as f32) as usize).min(BINS - 1);
+      predicted[bin] += p;
+      observed[bin] += if *label { 1.0 } else { 0.0 };
+      counts[bin] += 1;
+    }
+
+    (0..BINS)
+      .filter(|&bin| counts[bin] > 0)
+      .map(|bin| (predicted[bin] - observed[bin]).abs())
+      .sum::<f32>()
+      / samples.len().max(1) as f32
+  }
+}
+
+fn read_models(path: &Path) -> Result<BTreeMap<String, ConfidenceModel>> {
+  let text =
+    std::fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))?;
+  serde_json::from_str(&text).with_context(|| format!("Parsing {}", path.display()))
+}
+
+/// Fraction of alignment steps that move diagonally; long stalls mean one recording was stretched
+/// to fit the other
+pub fn path_stability(path: &[(usize, usize)]) -> f32 {
+  if path.len() < 2 {
+    return 0.0;
+  }
+  let diagonal = path
+    .windows(2)
+    .filter(|step| step[1].0 > step[0].0 && step[1].1 > step[0].1)
+    .count();
+  diagonal as f32 / (path.len() - 1) as f32
+}
+
+fn sigmoid(x: f32) -> f32 {
+  1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::signals;
+
+  fn features(
+    snr_db: f32,
+    path_stability: f32,
+    margin: Option<f32>,
+    speech_ms: u32,
+  ) -> ConfidenceFeatures {
+    ConfidenceFeatures {
+      snr_db,
+      clipped_fraction: 0.0,
+      path_stability,
+      competitor_margin: margin,
+      speech_ms,
+    }
+  }
+
+  /// Scores labelled by a simulated listener whose agreement follows a known logistic curve
+  fn labelled_set(len: usize) -> Vec<(ConfidenceFeatures, bool)> {
+    let mut draws = signals::noise(5 * len, 0.5, 17).into_iter();
+    let mut uniform = move || draws.next().unwrap() + 0.5;
+    (0..len)
+      .map(|_| {
+        let sample = features(
+          uniform() * 40.0,
+          0.4 + uniform() * 0.6,
+          Some(uniform() - 0.5),
+          200 + (uniform() * 1000.0) as u32,
+        );
+        let logit = 0.1 * (sample.snr_db - 20.0)
+          + 3.0 * (sample.path_stability - 0.7)
+          + 4.0 * sample.competitor_margin.unwrap();
+        (sample, uniform() < sigmoid(logit))
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_fitted_model_is_calibrated() {
+    let (train, held_out) = (labelled_set(3000), labelled_set(1000));
+    let fitted = ConfidenceModel::fit(&train);
+    let fitted_error = fitted.calibration_error(&held_out);
+    assert!(fitted_error < 0.05, "calibration error {}", fitted_error);
+    assert!(fitted_error < ConfidenceModel::default().calibration_error(&held_out));
+    // The simulated listener weighs the margin most heavily
+    assert!(fitted.weights[3] > 2.0, "{:?}", fitted.weights);
+  }
+
+  #[test]
+  fn test_models_are_stored_per_engine() {
+    let path = std::env::temp_dir().join(format!("ipa-confidence-{}.json", uuid::Uuid::new_v4()));
+    let fitted = ConfidenceModel::fit(&labelled_set(500));
+    fitted.save(&path, "mfcc-dtw").unwrap();
+    ConfidenceModel::default().save(&path, "gop").unwrap();
+
+    let loaded = ConfidenceModel::load(&path, "mfcc-dtw").unwrap();
+    let gop = ConfidenceModel::load(&path, "gop").unwrap();
+    let missing = ConfidenceModel::load(&path, "wav2vec").unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, Some(fitted));
+    assert_eq!(gop, Some(ConfidenceModel::default()));
+    assert_eq!(missing, None);
+  }
+
+  #[test]
+  fn test_confidence_rises_with_evidence() {
+    let model = ConfidenceModel::default();
+    let base = model.predict(&features(20.0, 0.7, None, 500));
+    assert!(model.predict(&features(35.0, 0.7, None, 500)) > base);
+    assert!(model.predict(&features(20.0, 0.95, None, 500)) > base);
+    assert!(model.predict(&features(20.0, 0.7, Some(0.3), 500)) > base);
+    assert!(model.predict(&features(20.0, 0.7, None, 80)) < base);
+    assert!(model.predict(&features(3.0, 0.4, Some(-0.2), 60)) < LOW_CONFIDENCE);
+    // No competitors to compare against is neutral, not evidence either way
+    assert_eq!(base, model.predict(&features(20.0, 0.7, Some(0.0), 500)));
+  }
+
+  #[test]
+  fn test_path_stability() {
+    assert_eq!(path_stability(&[(0, 0), (1, 1), (2, 2)]), 1.0);
+    assert_eq!(
+      path_stability(&[(0, 0), (1, 0), (2, 0), (3, 1), (3, 2)]),
+      0.25
+    );
+    assert_eq!(path_stability(&[(0, 0)]), 0.0);
+  }
+}
@@ -0,0 +1,481 @@
+use std::fmt;
+use std::io::Cursor;
+use std::sync::OnceLock;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CodecRegistry, CodecType, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::opus::OpusDecoder;
+
+/// Container formats recognised from the leading bytes of an upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+  Wav,
+  Flac,
+  Ogg,
+  WebM,
+  Mp4,
+}
+
+impl AudioFormat {
+  /// Identify the container from its magic bytes, ignoring any file name or MIME type
+  pub fn sniff(bytes: &[u8]) -> Option<Self> {
+    match bytes {
+      [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Self::Wav),
+      [b'f', b'L', b'a', b'C', ..] => Some(Self::Flac),
+      [b'O', b'g', b'g', b'S', ..] => Some(Self::Ogg),
+      [0x1A, 0x45, 0xDF, 0xA3, ..] => Some(Self::WebM),
+      [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(Self::Mp4),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for AudioFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      Self::Wav => "WAV",
+      Self::Flac => "FLAC",
+      Self::Ogg => "Ogg",
+      Self::WebM => "WebM",
+      Self::Mp4 => "MP4",
+    };
+    f.write_str(name)
+  }
+}
+
+/// Reasons an uploaded recording cannot be decoded
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+  #[error("audio data is empty")]
+  Empty,
+  #[error("unrecognised audio format")]
+  UnknownFormat,
+  #[error("{codec} audio in a {format} container is not supported")]
+  UnsupportedCodec { format: AudioFormat, codec: String },
+  #[error("{0} file has no audio track")]
+  NoAudioTrack(AudioFormat),
+  #[error("malformed {format} audio: {reason}")]
+  Malformed { format: AudioFormat, reason: String },
+}
+
+/// Decoded interleaved samples with their native rate and channel count
+#[derive(Debug, Clone)]
+pub struct DecodedAudio {
+  pub format: AudioFormat,
+  pub samples: Vec<f32>,
+  pub sample_rate: u32,
+  pub channels: u16,
+}
+
+/// Symphonia's enabled codecs plus Opus, which it has no decoder for
+fn codecs() -> &'static CodecRegistry {
+  static CODECS: OnceLock<CodecRegistry> = OnceLock::new();
+  CODECS.get_or_init(|| {
+    let mut registry = CodecRegistry::new();
+    symphonia::default::register_enabled_codecs(&mut registry);
+    registry.register_all::<OpusDecoder>();
+    registry
+  })
+}
+
+fn codec_name(codec: CodecType) -> String {
+  codecs()
+    .get_codec(codec)
+    .map(|descriptor| descriptor.short_name.to_string())
+    .unwrap_or_else(|| codec.to_string())
+}
+
+/// Decode WAV (8/16/24/32-bit integer or float), FLAC, Ogg Vorbis or Opus, WebM Opus or PCM, and
+/// MP4/AAC audio.
+pub fn decode(bytes: &[u8]) -> Result<DecodedAudio, DecodeError> {
+  if bytes.is_empty() {
+    return Err(DecodeError::Empty);
+  }
+  let format = AudioFormat::sniff(bytes).ok_or(DecodeError::UnknownFormat)?;
+  let malformed = |err: SymphoniaError| DecodeError::Malformed {
+    format,
+    reason: err.to_string(),
+  };
+
+  let source = MediaSourceStream::new(Box::new(Cursor::new(bytes.to_vec())), Default::default());
+  let mut reader = symphonia::default::get_probe()
+    .format(
+      &Hint::new(),
+      source,
+      &FormatOptions::default(),
+      &MetadataOptions::default(),
+    )
+    .map_err(malformed)?
+    .format;
+
+  let track = reader
+    .tracks()
+    .iter()
+    .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+    .ok_or(DecodeError::NoAudioTrack(format))?;
+  let track_id = track.id;
+  let codec = track.codec_params.codec;
+
+  // The Matroska demuxer leaves the packet size unset for PCM, which the PCM decoder requires;
+  // browsers write far less than a second of audio per block
+  let mut params = track.codec_params.clone();
+  if params.max_frames_per_packet.is_none() {
+    if let Some(rate) = params.sample_rate {
+      params.with_max_frames_per_packet(rate as u64);
+    }
+  }
+
+  let mut decoder =
+    codecs()
+      .make(&params, &DecoderOptions::default())
+      .map_err(|err| match err {
+        SymphoniaError::Unsupported(_) => DecodeError::UnsupportedCodec {
+          format,
+          codec: codec_name(codec),
+        },
+        other => malformed(other),
+      })?;
+
+  let mut samples = Vec::new();
+  let mut sample_rate = params.sample_rate.unwrap_or(0);
+  let mut channels = params
+    .channels
+    .map_or(0, |channels| channels.count() as u16);
+
+  loop {
+    let packet = match reader.next_packet() {
+      Ok(packet) => packet,
+      Err(SymphoniaError::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+      Err(SymphoniaError::ResetRequired) => break,
+      Err(err) => return Err(malformed(err)),
+    };
+    if packet.track_id() != track_id {
+      continue;
+    }
+
+    match decoder.decode(&packet) {
+      Ok(decoded) => {
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        channels = spec.channels.count() as u16;
+
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+      }
+      // A corrupt packet loses a few milliseconds; keep going
+      Err(SymphoniaError::DecodeError(_)) => continue,
+      Err(err) => return Err(malformed(err)),
+    }
+  }
+
+  if sample_rate == 0 || channels == 0 {
+    return Err(DecodeError::NoAudioTrack(format));
+  }
+
+  Ok(DecodedAudio {
+    format,
+    samples,
+    sample_rate,
+    channels,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use hound::{SampleFormat, WavSpec, WavWriter};
+  use std::path::Path;
+
+  /// Half a second of a 440 Hz tone at half scale, encoded at 48 kHz mono
+  fn tone_fixture(name: &str) -> DecodedAudio {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+      .join("tests/fixtures/audio")
+      .join(name);
+    decode(&std::fs::read(path).unwrap()).unwrap()
+  }
+
+  fn assert_is_tone(decoded: &DecodedAudio) {
+    assert_eq!(decoded.sample_rate, 48000);
+    assert_eq!(decoded.channels, 1);
+    // Encoder priming and padding may add up to a couple of frames
+    let len = decoded.samples.len();
+    assert!((24000..=24000 + 2048).contains(&len), "{} samples", len);
+
+    let peak = decoded
+      .samples
+      .iter()
+      .fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!((0.4..0.6).contains(&peak), "peak {}", peak);
+    // Count zero crossings over the audible part only, since AAC leads with priming silence
+    let loud = |s: &f32| s.abs() > 0.25;
+    let start = decoded.samples.iter().position(loud).unwrap();
+    let end = decoded.samples.iter().rposition(loud).unwrap();
+    let crossings = decoded.samples[start..=end]
+      .windows(2)
+      .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+      .count();
+    let frequency = crossings as f32 / 2.0 / ((end - start) as f32 / 48000.0);
+    assert!((420.0..460.0).contains(&frequency), "{} Hz", frequency);
+  }
+
+  fn ramp(len: usize) -> Vec<f32> {
+    (0..len).map(|i| (i as f32 / len as f32) - 0.5).collect()
+  }
+
+  fn wav_bytes(spec: WavSpec, samples: &[f32]) -> Vec<u8> {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut cursor, spec).unwrap();
+    for &sample in samples {
+      match (spec.sample_format, spec.bits_per_sample) {
+        (SampleFormat::Float, _) => writer.write_sample(sample).unwrap(),
+        (SampleFormat::Int, 8) => writer.write_sample((sample * 127.0) as i8).unwrap(),
+        (SampleFormat::Int, 16) => writer.write_sample((sample * 32767.0) as i16).unwrap(),
+        (SampleFormat::Int, bits) => {
+          let scale = ((1i64 << (bits - 1)) - 1) as f32;
+          writer.write_sample((sample * scale) as i32).unwrap()
+        }
+      }
+    }
+    writer.finalize().unwrap();
+    cursor.into_inner()
+  }
+
+  /// Minimal FLAC stream made of verbatim subframes
+  fn flac_bytes(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    const BLOCK: usize = 1024;
+    let mut out = b"fLaC".to_vec();
+
+    // STREAMINFO, marked as the last metadata block
+    out.extend_from_slice(&[0x80, 0, 0, 34]);
+    out.extend_from_slice(&(BLOCK as u16).to_be_bytes());
+    out.extend_from_slice(&(BLOCK as u16).to_be_bytes());
+    out.extend_from_slice(&[0; 6]);
+    let packed: u64 = ((sample_rate as u64) << 44) | (15 << 36) | samples.len() as u64;
+    out.extend_from_slice(&packed.to_be_bytes());
+    out.extend_from_slice(&[0; 16]);
+
+    for (index, block) in samples.chunks(BLOCK).enumerate() {
+      let start = out.len();
+      out.extend_from_slice(&[0xFF, 0xF8, 0x70, 0x08, index as u8]);
+      out.extend_from_slice(&((block.len() - 1) as u16).to_be_bytes());
+      let crc8 = out[start..].iter().fold(0u8, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |c, _| {
+          if c & 0x80 != 0 {
+            (c << 1) ^ 0x07
+          } else {
+            c << 1
+          }
+        })
+      });
+      out.push(crc8);
+
+      // Verbatim subframe
+      out.push(0x02);
+      for sample in block {
+        out.extend_from_slice(&sample.to_be_bytes());
+      }
+
+      let crc16 = out[start..].iter().fold(0u16, |crc, &byte| {
+        (0..8).fold(crc ^ ((byte as u16) << 8), |c, _| {
+          if c & 0x8000 != 0 {
+            (c << 1) ^ 0x8005
+          } else {
+            c << 1
+          }
+        })
+      });
+      out.extend_from_slice(&crc16.to_be_bytes());
+    }
+
+    out
+  }
+
+  fn ebml(id: &[u8], body: &[u8]) -> Vec<u8> {
+    let mut out = id.to_vec();
+    out.push(0x01);
+    out.extend_from_slice(&(body.len() as u64).to_be_bytes()[1..]);
+    out.extend_from_slice(body);
+    out
+  }
+
+  /// Single-cluster WebM file with one audio track
+  fn webm_bytes(codec_id: &str, codec_private: &[u8], channels: u8, payload: &[u8]) -> Vec<u8> {
+    let header = [
+      ebml(&[0x42, 0x86], &[1]),
+      ebml(&[0x42, 0xF7], &[1]),
+      ebml(&[0x42, 0xF2], &[4]),
+      ebml(&[0x42, 0xF3], &[8]),
+      ebml(&[0x42, 0x82], b"webm"),
+      ebml(&[0x42, 0x87], &[4]),
+      ebml(&[0x42, 0x85], &[2]),
+    ]
+    .concat();
+
+    let audio = [
+      ebml(&[0xB5], &48000f64.to_be_bytes()),
+      ebml(&[0x9F], &[channels]),
+      ebml(&[0x62, 0x64], &[16]),
+    ]
+    .concat();
+    let mut entry = [
+      ebml(&[0xD7], &[1]),
+      ebml(&[0x73, 0xC5], &[1]),
+      ebml(&[0x83], &[2]),
+      ebml(&[0x86], codec_id.as_bytes()),
+      ebml(&[0xE1], &audio),
+    ]
+    .concat();
+    if !codec_private.is_empty() {
+      entry.extend(ebml(&[0x63, 0xA2], codec_private));
+    }
+
+    let mut block = vec![0x81, 0, 0, 0x80];
+    block.extend_from_slice(payload);
+    let segment = [
+      ebml(
+        &[0x15, 0x49, 0xA9, 0x66],
+        &ebml(&[0x2A, 0xD7, 0xB1], &[0x0F, 0x42, 0x40]),
+      ),
+      ebml(&[0x16, 0x54, 0xAE, 0x6B], &ebml(&[0xAE], &entry)),
+      ebml(
+        &[0x1F, 0x43, 0xB6, 0x75],
+        &[ebml(&[0xE7], &[0]), ebml(&[0xA3], &block)].concat(),
+      ),
+    ]
+    .concat();
+
+    [
+      ebml(&[0x1A, 0x45, 0xDF, 0xA3], &header),
+      ebml(&[0x18, 0x53, 0x80, 0x67], &segment),
+    ]
+    .concat()
+  }
+
+  #[test]
+  fn test_decode_wav_bit_depths() {
+    let samples = ramp(800);
+    let cases = [
+      (SampleFormat::Int, 8, 1.0 / 64.0),
+      (SampleFormat::Int, 16, 1e-3),
+      (SampleFormat::Int, 24, 1e-3),
+      (SampleFormat::Int, 32, 1e-3),
+      (SampleFormat::Float, 32, 1e-6),
+    ];
+
+    for (sample_format, bits_per_sample, tolerance) in cases {
+      let spec = WavSpec {
+        channels: 1,
+        sample_rate: 22050,
+        bits_per_sample,
+        sample_format,
+      };
+      let decoded = decode(&wav_bytes(spec, &samples)).unwrap();
+      assert_eq!(decoded.format, AudioFormat::Wav);
+      assert_eq!(decoded.sample_rate, 22050);
+      assert_eq!(decoded.samples.len(), samples.len());
+      for (got, want) in decoded.samples.iter().zip(&samples) {
+        assert!(
+          (got - want).abs() < tolerance,
+          "{}-bit {:?}: {} vs {}",
+          bits_per_sample,
+          sample_format,
+          got,
+          want
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_decode_stereo_wav_keeps_interleaving() {
+    let spec = WavSpec {
+      channels: 2,
+      sample_rate: 48000,
+      bits_per_sample: 16,
+      sample_format: SampleFormat::Int,
+    };
+    let decoded = decode(&wav_bytes(spec, &[0.5, -0.5, 0.25, -0.25])).unwrap();
+    assert_eq!(decoded.channels, 2);
+    assert!((decoded.samples[1] + 0.5).abs() < 1e-3);
+  }
+
+  #[test]
+  fn test_decode_flac() {
+    let samples: Vec<i16> = (0..3000)
+      .map(|i| ((i % 200) * 100 - 10000) as i16)
+      .collect();
+    let decoded = decode(&flac_bytes(&samples, 16000)).unwrap();
+    assert_eq!(decoded.format, AudioFormat::Flac);
+    assert_eq!(decoded.sample_rate, 16000);
+    assert_eq!(decoded.samples.len(), samples.len());
+    assert!((decoded.samples[100] - samples[100] as f32 / 32768.0).abs() < 1e-4);
+  }
+
+  #[test]
+  fn test_decode_webm_pcm() {
+    let payload: Vec<u8> = (0..960i16)
+      .flat_map(|i| {
+        let s = i * 30;
+        [s.to_le_bytes(), (-s).to_le_bytes()].concat()
+      })
+      .collect();
+    let decoded = decode(&webm_bytes("A_PCM/INT/LIT", &[], 2, &payload)).unwrap();
+    assert_eq!(decoded.format, AudioFormat::WebM);
+    assert_eq!(decoded.sample_rate, 48000);
+    assert_eq!(decoded.channels, 2);
+    assert_eq!(decoded.samples.len(), 1920);
+  }
+
+  #[test]
+  fn test_decode_ogg_vorbis() {
+    let decoded = tone_fixture("tone.ogg");
+    assert_eq!(decoded.format, AudioFormat::Ogg);
+    assert_is_tone(&decoded);
+  }
+
+  #[test]
+  fn test_decode_mp4_aac() {
+    let decoded = tone_fixture("tone.m4a");
+    assert_eq!(decoded.format, AudioFormat::Mp4);
+    assert_is_tone(&decoded);
+  }
+
+  #[test]
+  fn test_decode_webm_opus() {
+    let decoded = tone_fixture("tone.webm");
+    assert_eq!(decoded.format, AudioFormat::WebM);
+    assert_is_tone(&decoded);
+  }
+
+  #[test]
+  fn test_decode_ogg_opus() {
+    let decoded = tone_fixture("tone.opus");
+    assert_eq!(decoded.format, AudioFormat::Ogg);
+    assert_is_tone(&decoded);
+  }
+
+  #[test]
+  fn test_format_is_sniffed_from_content() {
+    assert_eq!(AudioFormat::sniff(b"OggS\0\x02"), Some(AudioFormat::Ogg));
+    assert_eq!(
+      AudioFormat::sniff(b"\0\0\0\x20ftypM4A "),
+      Some(AudioFormat::Mp4)
+    );
+    assert!(matches!(decode(&[]), Err(DecodeError::Empty)));
+    assert!(matches!(
+      decode(b"ID3\x04 not audio we handle"),
+      Err(DecodeError::UnknownFormat)
+    ));
+    assert!(matches!(
+      decode(b"RIFF\0\0\0\0WAVEjunk"),
+      Err(DecodeError::Malformed { .. })
+    ));
+  }
+}
@@ -0,0 +1,319 @@
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use rustfft::num_complex::Complex32;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::vad::VoiceActivityDetector;
+
+/// How background noise is removed before scoring
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "noise_reduction", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseReduction {
+  #[default]
+  Off,
+  /// Subtract the noise power spectrum from every frame
+  SpectralSubtraction,
+  /// Scale every bin by its estimated speech-to-total power ratio; smoother than subtraction,
+  /// with less of its warbling residue
+  Wiener,
+}
+
+/// Denoising parameters
+#[derive(Debug, Clone)]
+pub struct DenoiseConfig {
+  pub frame_size: usize,
+  pub hop_size: usize,
+  /// Multiple of the noise spectrum removed by spectral subtraction; above 1 to clear
+  /// fluctuations above the mean
+  pub over_subtraction: f32,
+  /// Smallest gain applied to any bin, so removed noise leaves a faint floor rather than holes
+  pub gain_floor: f32,
+  /// Weight of the previous frame's estimate in the Wiener filter's speech-to-noise ratio
+  pub smoothing: f32,
+  /// Fewest non-speech frames needed to estimate the noise; recordings with less silence
+  /// around the word are left as they are
+  pub min_noise_frames: usize,
+}
+
+impl Default for DenoiseConfig {
+  fn default() -> Self {
+    Self {
+      frame_size: 512,
+      hop_size: 256,
+      over_subtraction: 2.0,
+      gain_floor: 0.05,
+      smoothing: 0.98,
+      min_noise_frames: 5,
+    }
+  }
+}
+
+/// Removes stationary background noise, such as hum and fan or engine rumble, using a noise
+/// spectrum measured where the voice activity detector finds no speech
+pub struct Denoiser {
+  config: DenoiseConfig,
+  vad: VoiceActivityDetector,
+  forward: Arc<dyn RealToComplex<f32>>,
+  inverse: Arc<dyn ComplexToReal<f32>>,
+  /// Square root of a periodic Hann window, applied before analysis and after synthesis so
+  /// half-overlapping frames add back to the input
+  window: Vec<f32>,
+}
+
+impl Default for Denoiser {
+  fn default() -> Self {
+    Self::new(DenoiseConfig::default())
+  }
+}
+
+impl Denoiser {
+  pub fn new(config: DenoiseConfig) -> Self {
+    let mut planner = RealFftPlanner::<f32>::new();
+    let frame_size = config.frame_size;
+    let window = (0..frame_size)
+      .map(|i| {
+        let phase = std::f32::consts::PI * i as f32 / frame_size as f32;
+        phase.sin()
+      })
+      .collect();
+    Self {
+      forward: planner.plan_fft_forward(frame_size),
+      inverse: planner.plan_fft_inverse(frame_size),
+      window,
+      vad: VoiceActivityDetector::default(),
+      config,
+    }
+  }
+
+  /// Denoise 16 kHz audio. Audio is returned unchanged when `mode` is off, no speech is found,
+  /// or there is too little silence to measure the noise from.
+  pub fn process(&self, audio: &[f32], mode: NoiseReduction) -> Vec<f32> {
+    if mode == NoiseReduction::Off {
+      return audio.to_vec();
+    }
+    let Some(speech) = self.vad.detect(audio) else {
+      return audio.to_vec();
+    };
+
+    let hop = self.config.hop_size;
+    let frame_size = self.config.frame_size;
+    // One hop of leading zeros puts every sample under two frames
+    let frames = (audio.len() + hop).div_ceil(hop);
+    let mut padded = vec![0.0; hop];
+    padded.extend_from_slice(audio);
+    padded.resize((frames - 1) * hop + frame_size, 0.0);
+
+    let spectra: Vec<Vec<Complex32>> = (0..frames)
+      .map(|k| self.analyse(&padded[k * hop..k * hop + frame_size]))
+      .collect();
+
+    // Frames wholly before or after the detected speech hold only noise
+    let is_noise = |k: usize| {
+      let (start, end) = ((k * hop).saturating_sub(hop), k * hop + frame_size - hop);
+      end <= speech.start_sample || start >= speech.end_sample
+    };
+    let noise_frames: Vec<&Vec<Complex32>> = (1..frames - 1)
+      .filter(|&k| is_noise(k))
+      .map(|k| &spectra[k])
+      .collect();
+    if noise_frames.len() < self.config.min_noise_frames {
+      return audio.to_vec();
+    }
+    let bins = frame_size / 2 + 1;
+    let noise: Vec<f32> = (0..bins)
+      .map(|b| {
+        let total: f32 = noise_frames.iter().map(|s| s[b].norm_sqr()).sum();
+        (total / noise_frames.len() as f32).max(f32::MIN_POSITIVE)
+      })
+      .collect();
+
+    let mut output = vec![0.0; padded.len()];
+    let mut previous_speech = vec![0.0; bins];
+    for (k, mut spectrum) in spectra.into_iter().enumerate() {
+      for (b, bin) in spectrum.iter_mut().enumerate() {
+        let power = bin.norm_sqr();
+        let gain = match mode {
+          NoiseReduction::SpectralSubtraction => {
+            let remaining = power - self.config.over_subtraction * noise[b];
+            if power > 0.0 {
+              (remaining.max(0.0) / power).sqrt()
+            } else {
+              0.0
+            }
+          }
+          // Decision-directed estimate of the a priori speech-to-noise ratio
+          _ => {
+            let posterior = power / noise[b];
+            let prior = self.config.smoothing * previous_speech[b] / noise[b]
+              + (1.0 - self.config.smoothing) * (posterior - 1.0).max(0.0);
+            prior / (1.0 + prior)
+          }
+        }
+        .max(self.config.gain_floor);
+        *bin *= gain;
+        previous_speech[b] = bin.norm_sqr();
+      }
+      let frame = self.synthesise(spectrum);
+      for (out, sample) in output[k * hop..].iter_mut().zip(frame) {
+        *out += sample;
+      }
+    }
+    output[hop..hop + audio.len()].to_vec()
+  }
+
+  fn analyse(&self, frame: &[f32]) -> Vec<Complex32> {
+    let mut input: Vec<f32> = frame.iter().zip(&self.window).map(|(s, w)| s * w).collect();
+    let mut spectrum = self.forward.make_output_vec();
+    self
+      .forward
+      .process(&mut input, &mut spectrum)
+      .expect("buffers are sized by the plan");
+    spectrum
+  }
+
+  fn synthesise(&self, mut spectrum: Vec<Complex32>) -> Vec<f32> {
+    // A real signal's DC and Nyquist bins have no imaginary part
+    let last = spectrum.len() - 1;
+    spectrum[0].im = 0.0;
+    spectrum[last].im = 0.0;
+    let mut frame = self.inverse.make_output_vec();
+    self
+      .inverse
+      .process(&mut spectrum, &mut frame)
+      .expect("buffers are sized by the plan");
+    let scale = 1.0 / self.config.frame_size as f32;
+    frame
+      .iter()
+      .zip(&self.window)
+      .map(|(s, w)| s * w * scale)
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::Dialect;
+  use crate::services::ipa::Transcription;
+  use crate::services::scoring::signals::noise;
+  use crate::services::scoring::{FormantSynthesizer, PronunciationScorer, SAMPLE_RATE};
+
+  /// Mains hum at 50 Hz with its first harmonics, as from a classroom projector
+  fn hum(len: usize, amplitude: f32) -> Vec<f32> {
+    (0..len)
+      .map(|n| {
+        let t = n as f32 / SAMPLE_RATE as f32;
+        (1..=4)
+          .map(|h| amplitude / h as f32 * (2.0 * std::f32::consts::PI * 50.0 * h as f32 * t).sin())
+          .sum()
+      })
+      .collect()
+  }
+
+  /// A clean word with half a second of silence either side
+  fn clean_word(ipa: &str) -> Vec<f32> {
+    let speech = FormantSynthesizer::for_dialect(&Dialect::GA)
+      .synthesize(&Transcription::parse(ipa))
+      .unwrap()
+      .audio;
+    let pause = vec![0.0; SAMPLE_RATE as usize / 2];
+    [pause.clone(), speech, pause].concat()
+  }
+
+  fn add(a: &[f32], b: &[f32]) -> Vec<f32> {
+    a.iter().zip(b).map(|(x, y)| x + y).collect()
+  }
+
+  /// Signal-to-noise ratio of `processed` against the clean signal
+  fn snr_db(clean: &[f32], processed: &[f32]) -> f32 {
+    let signal: f32 = clean.iter().map(|s| s * s).sum();
+    let error: f32 = clean
+      .iter()
+      .zip(processed)
+      .map(|(c, p)| (c - p).powi(2))
+      .sum();
+    10.0 * (signal / error).log10()
+  }
+
+  #[test]
+  fn test_reconstructs_input_when_nothing_is_removed() {
+    let denoiser = Denoiser::new(DenoiseConfig {
+      over_subtraction: 0.0,
+      gain_floor: 1.0,
+      ..DenoiseConfig::default()
+    });
+    let audio = add(&clean_word("ˈmɑnə"), &noise(32000, 0.01, 11));
+    for mode in [NoiseReduction::SpectralSubtraction, NoiseReduction::Wiener] {
+      let output = denoiser.process(&audio, mode);
+      assert_eq!(output.len(), audio.len());
+      assert!(snr_db(&audio, &output) > 60.0, "{mode:?} alters the signal");
+    }
+  }
+
+  #[test]
+  fn test_removes_hum_and_hiss() {
+    let clean = clean_word("ˈnɑlmi");
+    let peak = clean.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let background = add(
+      &hum(clean.len(), 0.1 * peak),
+      &noise(clean.len(), 0.05 * peak, 11),
+    );
+    let noisy = add(&clean, &background);
+    let before = snr_db(&clean, &noisy);
+
+    let denoiser = Denoiser::default();
+    for mode in [NoiseReduction::SpectralSubtraction, NoiseReduction::Wiener] {
+      let after = snr_db(&clean, &denoiser.process(&noisy, mode));
+      assert!(
+        after > before + 6.0,
+        "{mode:?}: {before:.1} dB -> {after:.1} dB"
+      );
+    }
+    assert_eq!(denoiser.process(&noisy, NoiseReduction::Off), noisy);
+  }
+
+  #[test]
+  fn test_recordings_without_silence_are_left_alone() {
+    let denoiser = Denoiser::default();
+    let speech = FormantSynthesizer::for_dialect(&Dialect::GA)
+      .synthesize(&Transcription::parse("ˈmɑnə"))
+      .unwrap()
+      .audio;
+    let noisy = add(&speech, &noise(speech.len(), 0.01, 11));
+    assert_eq!(denoiser.process(&noisy, NoiseReduction::Wiener), noisy);
+    assert!(denoiser
+      .process(&[], NoiseReduction::SpectralSubtraction)
+      .is_empty());
+  }
+
+  #[test]
+  fn test_denoised_attempt_scores_closer_to_clean() {
+    let clean = clean_word("ˈglæd");
+    let peak = clean.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let noisy = add(
+      &clean,
+      &add(
+        &hum(clean.len(), 0.1 * peak),
+        &noise(clean.len(), 0.05 * peak, 11),
+      ),
+    );
+    let target = Transcription::parse("ˈglæd");
+    let mut scorer = PronunciationScorer::new();
+    let mut score = |audio: &[f32]| {
+      scorer
+        .score_pronunciation(audio, &clean, &target)
+        .unwrap()
+        .overall_pct
+    };
+
+    let noisy_pct = score(&noisy);
+    for mode in [NoiseReduction::SpectralSubtraction, NoiseReduction::Wiener] {
+      let denoised_pct = score(&Denoiser::default().process(&noisy, mode));
+      assert!(
+        denoised_pct > noisy_pct + 10.0,
+        "{mode:?}: {noisy_pct:.1}% noisy, {denoised_pct:.1}% denoised"
+      );
+    }
+  }
+}
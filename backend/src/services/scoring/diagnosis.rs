@@ -0,0 +1,318 @@
+use serde::{Deserialize, Serialize};
+
+use super::formant::{Formants, VowelFormants};
+use super::vowel_chart::{ipa_vowel_formants, nearest_vowel};
+use super::PhonemeScore;
+use crate::services::{articulation, ipa};
+
+/// Phonemes scoring below this were mispronounced rather than merely imperfect
+pub const SUBSTITUTED_BELOW: f32 = 50.0;
+
+/// Substitutions closer than this to the target, by articulatory features, earn partial credit
+pub const PARTIAL_CREDIT_WITHIN: f32 = 0.5;
+
+/// How the learner realised one target phoneme
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Diagnosis {
+  #[default]
+  Correct,
+  /// Another sound was said in its place; `produced` is the most likely one, if the engine can
+  /// tell
+  Substituted { produced: Option<String> },
+  /// Nothing was said for it
+  Deleted,
+}
+
+/// A sound the learner added that the target does not have, such as an epenthetic vowel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Insertion {
+  /// IPA symbol of the added sound, if the engine can tell
+  pub sound: Option<String>,
+  pub start_ms: u32,
+  pub end_ms: u32,
+}
+
+/// One step of the cheapest edit turning a target phone sequence into a recognised one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+  Match { target: usize, recognised: usize },
+  Substitute { target: usize, recognised: usize },
+  Delete { target: usize },
+  Insert { recognised: usize },
+}
+
+/// Levenshtein alignment of two sequences, in order. Ties prefer a substitution over a deletion
+/// and insertion pair, so a wrong sound is reported as one error.
+pub fn edit_script<T: PartialEq>(target: &[T], recognised: &[T]) -> Vec<Edit> {
+  let (n, m) = (target.len(), recognised.len());
+  let mut cost = vec![vec![0usize; m + 1]; n + 1];
+  cost[0] = (0..=m).collect();
+  for (t, row) in cost.iter_mut().enumerate() {
+    row[0] = t;
+  }
+  for t in 1..=n {
+    for r in 1..=m {
+      let substitution = usize::from(target[t - 1] != recognised[r - 1]);
+      cost[t][r] = (cost[t - 1][r - 1] + substitution)
+        .min(cost[t - 1][r] + 1)
+        .min(cost[t][r - 1] + 1);
+    }
+  }
+
+  let mut edits = Vec::with_capacity(n.max(m));
+  let (mut t, mut r) = (n, m);
+  while t > 0 || r > 0 {
+    if t > 0 && r > 0 {
+      let same = target[t - 1] == recognised[r - 1];
+      if cost[t][r] == cost[t - 1][r - 1] + usize::from(!same) {
+        let (target, recognised) = (t - 1, r - 1);
+        edits.push(if same {
+          Edit::Match { target, recognised }
+        } else {
+          Edit::Substitute { target, recognised }
+        });
+        t -= 1;
+        r -= 1;
+        continue;
+      }
+    }
+    if t > 0 && cost[t][r] == cost[t - 1][r] + 1 {
+      edits.push(Edit::Delete { target: t - 1 });
+      t -= 1;
+    } else {
+      edits.push(Edit::Insert { recognised: r - 1 });
+      r -= 1;
+    }
+  }
+  edits.reverse();
+  edits
+}
+
+/// Record a sound inserted before target phoneme `position`, or after the last phoneme when
+/// `position` is past the end
+pub(crate) fn attach_insertion(
+  per_phoneme: &mut [PhonemeScore],
+  position: usize,
+  insertion: Insertion,
+) {
+  if let Some(phoneme) = per_phoneme.get_mut(position) {
+    phoneme.inserted_before.push(insertion);
+  } else if let Some(last) = per_phoneme.last_mut() {
+    last.inserted_after.push(insertion);
+  }
+}
+
+/// Name substituted vowels, and inserted sounds that `measure` finds formants for, by the
+/// nearest vowel in the IPA table.
+///
+/// Measurements are first scaled to the table's speaker, using the learner's own correctly said
+/// vowels if there are any, else the reference speaker's, so a higher voice is not heard as a
+/// more open vowel.
+pub(crate) fn name_vowels(
+  per_phoneme: &mut [PhonemeScore],
+  vowels: &[VowelFormants],
+  measure: impl Fn(&Insertion) -> Option<Formants>,
+) {
+  let vowel_indices: Vec<usize> = (0..per_phoneme.len())
+    .filter(|&p| ipa::is_vowel(&per_phoneme[p].phoneme))
+    .collect();
+  let calibration = |pick: &dyn Fn(usize, &VowelFormants) -> Option<Formants>| {
+    let pairs: Vec<(Formants, [f32; 3])> = vowel_indices
+      .iter()
+      .zip(vowels)
+      .filter_map(|(&p, vowel)| Some((pick(p, vowel)?, ipa_vowel_formants(&vowel.phoneme)?)))
+      .collect();
+    speaker_scale(&pairs)
+  };
+  let scale = calibration(&|p, vowel| {
+    (per_phoneme[p].diagnosis == Diagnosis::Correct)
+      .then_some(vowel.learner)
+      .flatten()
+  })
+  .or_else(|| calibration(&|_, vowel| vowel.reference))
+  .unwrap_or([1.0; 3]);
+  let name = |formants: Formants| {
+    nearest_vowel(&Formants {
+      f1: formants.f1 * scale[0],
+      f2: formants.f2 * scale[1],
+      f3: formants.f3 * scale[2],
+    })
+  };
+
+  for (&p, vowel) in vowel_indices.iter().zip(vowels) {
+    let phoneme = &mut per_phoneme[p];
+    if let (Diagnosis::Substituted { produced: None }, Some(learner)) =
+      (&phoneme.diagnosis, vowel.learner)
+    {
+      let heard = name(learner);
+      if !phoneme.phoneme.starts_with(heard) {
+        phoneme.diagnosis = Diagnosis::Substituted {
+          produced: Some(heard.to_string()),
+        };
+      }
+    }
+  }
+
+  for phoneme in per_phoneme.iter_mut() {
+    let insertions = phoneme
+      .inserted_before
+      .iter_mut()
+      .chain(phoneme.inserted_after.iter_mut());
+    for insertion in insertions.filter(|i| i.sound.is_none()) {
+      insertion.sound = measure(insertion).map(|formants| name(formants).to_string());
+    }
+  }
+}
+
+/// Raise the score of substitutions that are near misses, so a /d/ for /ð/ scores above an /m/.
+/// Credit falls from just under `SUBSTITUTED_BELOW` for a sound differing only in length or
+/// aspiration to nothing at `PARTIAL_CREDIT_WITHIN`; the diagnosis is kept.
+pub(crate) fn give_partial_credit(per_phoneme: &mut [PhonemeScore]) {
+  for phoneme in per_phoneme {
+    if let Diagnosis::Substituted {
+      produced: Some(produced),
+    } = &phoneme.diagnosis
+    {
+      let distance = articulation::phoneme_distance(&phoneme.phoneme, produced);
+      let credit = SUBSTITUTED_BELOW * (1.0 - distance / PARTIAL_CREDIT_WITHIN);
+      phoneme.score = phoneme.score.max(credit);
+    }
+  }
+}
+
+/// Mean ratio of table to measured formants, kept within the range of adult speakers
+fn speaker_scale(pairs: &[(Formants, [f32; 3])]) -> Option<[f32; 3]> {
+  if pairs.is_empty() {
+    return None;
+  }
+  let ratio = |k: usize| {
+    let sum: f32 = pairs
+      .iter()
+      .map(|(measured, table)| {
+        let measured = [measured.f1, measured.f2, measured.f3][k];
+        table[k] / measured.max(1.0)
+      })
+      .sum();
+    (sum / pairs.len() as f32).clamp(0.7, 1.4)
+  };
+  Some([ratio(0), ratio(1), ratio(2)])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_edit_script() {
+    let edits = edit_script(&['s', 'p', 'o', 't', 's'], &['ə', 's', 'p', 'o', 's']);
+    assert_eq!(
+      edits,
+      vec![
+        Edit::Insert { recognised: 0 },
+        Edit::Match {
+          target: 0,
+          recognised: 1
+        },
+        Edit::Match {
+          target: 1,
+          recognised: 2
+        },
+        Edit::Match {
+          target: 2,
+          recognised: 3
+        },
+        Edit::Delete { target: 3 },
+        Edit::Match {
+          target: 4,
+          recognised: 4
+        },
+      ]
+    );
+    assert_eq!(
+      edit_script(&['θ', 'ɪ', 'n'], &['s', 'ɪ', 'n'])[0],
+      Edit::Substitute {
+        target: 0,
+        recognised: 0
+      }
+    );
+    assert!(edit_script::<char>(&[], &[]).is_empty());
+  }
+
+  #[test]
+  fn test_schema() {
+    let mut per_phoneme = vec![PhonemeScore {
+      phoneme: "s".into(),
+      start_ms: 120,
+      end_ms: 210,
+      score: 35.0,
+      diagnosis: Diagnosis::Substituted {
+        produced: Some("θ".into()),
+      },
+      ..Default::default()
+    }];
+    attach_insertion(
+      &mut per_phoneme,
+      1,
+      Insertion {
+        sound: Some("ə".into()),
+        start_ms: 210,
+        end_ms: 260,
+      },
+    );
+    assert_eq!(
+      serde_json::to_value(&per_phoneme).unwrap(),
+      serde_json::json!([{
+        "phoneme": "s",
+        "start_ms": 120,
+        "end_ms": 210,
+        "score": 35.0,
+        "diagnosis": {"kind": "substituted", "produced": "θ"},
+        "inserted_after": [{"sound": "ə", "start_ms": 210, "end_ms": 260}],
+      }])
+    );
+
+    // Scores stored before diagnosis was added still load
+    let old: PhonemeScore = serde_json::from_value(serde_json::json!({
+      "phoneme": "s", "start_ms": 0, "end_ms": 90, "score": 80.0
+    }))
+    .unwrap();
+    assert_eq!(old.diagnosis, Diagnosis::Correct);
+  }
+
+  #[test]
+  fn test_near_misses_earn_partial_credit() {
+    let substituted = |produced: &str| PhonemeScore {
+      phoneme: "ð".into(),
+      score: 5.0,
+      diagnosis: Diagnosis::Substituted {
+        produced: Some(produced.into()),
+      },
+      ..Default::default()
+    };
+    let mut per_phoneme = vec![
+      substituted("d"),
+      substituted("m"),
+      substituted("ɑ"),
+      PhonemeScore {
+        phoneme: "ð".into(),
+        score: 5.0,
+        diagnosis: Diagnosis::Deleted,
+        ..Default::default()
+      },
+    ];
+    give_partial_credit(&mut per_phoneme);
+
+    let scores: Vec<f32> = per_phoneme.iter().map(|p| p.score).collect();
+    assert!(
+      scores[0] > 20.0 && scores[0] < SUBSTITUTED_BELOW,
+      "{:?}",
+      scores
+    );
+    assert_eq!(scores[1..], [5.0, 5.0, 5.0]);
+    assert!(matches!(
+      per_phoneme[0].diagnosis,
+      Diagnosis::Substituted { .. }
+    ));
+  }
+}
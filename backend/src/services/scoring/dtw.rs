@@ -0,0 +1,550 @@
+use super::simd;
+
+/// Frame-to-frame distance used to fill the DTW cost matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distance {
+  Euclidean,
+  /// One minus the cosine similarity; insensitive to overall feature scale
+  Cosine,
+}
+
+impl Distance {
+  pub fn between(self, a: &[f32], b: &[f32]) -> f32 {
+    match self {
+      Distance::Euclidean => simd::squared_distance(a, b).sqrt(),
+      Distance::Cosine => {
+        let dot = simd::dot(a, b);
+        let norm_a = simd::sum_of_squares(a).sqrt();
+        let norm_b = simd::sum_of_squares(b).sqrt();
+        if norm_a == 0.0 && norm_b == 0.0 {
+          0.0
+        } else if norm_a == 0.0 || norm_b == 0.0 {
+          1.0
+        } else {
+          (1.0 - dot / (norm_a * norm_b)).max(0.0)
+        }
+      }
+    }
+  }
+}
+
+/// Allowed moves through the cost matrix and how they are weighted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepPattern {
+  /// Horizontal, vertical and diagonal moves, all weighted once; normalised by path length
+  Symmetric1,
+  /// As `Symmetric1` but diagonal moves count twice, so every path weighs `n + m`
+  Symmetric2,
+  /// Every move advances the first sequence by one frame and the second by 0-2; normalised by
+  /// the length of the first sequence, which must be at least half as long as the second
+  Asymmetric,
+}
+
+impl StepPattern {
+  /// `(di, dj, weight)` of each move, in tie-breaking order
+  fn steps(self) -> &'static [(usize, usize, f32)] {
+    match self {
+      StepPattern::Symmetric1 => &[(1, 1, 1.0), (1, 0, 1.0), (0, 1, 1.0)],
+      StepPattern::Symmetric2 => &[(1, 1, 2.0), (1, 0, 1.0), (0, 1, 1.0)],
+      StepPattern::Asymmetric => &[(1, 1, 1.0), (1, 0, 1.0), (1, 2, 1.0)],
+    }
+  }
+
+  /// Weight of the first cell, chosen so a purely diagonal path sums to the normaliser
+  fn start_weight(self) -> f32 {
+    match self {
+      StepPattern::Symmetric2 => 2.0,
+      _ => 1.0,
+    }
+  }
+
+  fn normalizer(self, n: usize, m: usize, path_len: usize) -> f32 {
+    match self {
+      StepPattern::Symmetric1 => path_len as f32,
+      StepPattern::Symmetric2 => (n + m) as f32,
+      StepPattern::Asymmetric => n as f32,
+    }
+  }
+}
+
+/// Global constraint on how far the warping path may stray from the diagonal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+  None,
+  /// Band of `radius` frames around the (length-scaled) diagonal
+  SakoeChiba {
+    radius: usize,
+  },
+  /// Parallelogram limiting the local tempo ratio to `1 / max_slope..=max_slope`. Sequences
+  /// whose lengths differ by `max_slope` or more fit no such path and are aligned unconstrained.
+  Itakura {
+    max_slope: f32,
+  },
+}
+
+/// DTW parameters
+#[derive(Debug, Clone)]
+pub struct DtwConfig {
+  pub window: Window,
+  pub step_pattern: StepPattern,
+  pub distance: Distance,
+}
+
+impl Default for DtwConfig {
+  fn default() -> Self {
+    Self {
+      window: Window::None,
+      step_pattern: StepPattern::Symmetric2,
+      distance: Distance::Euclidean,
+    }
+  }
+}
+
+/// Result of aligning two sequences
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+  /// Accumulated weighted cost; infinite when the constraints admit no path
+  pub cost: f32,
+  /// Cost divided by the step pattern's normaliser, comparable across lengths
+  pub normalized_cost: f32,
+  /// `(seq1, seq2)` index pairs in time order
+  pub path: Vec<(usize, usize)>,
+}
+
+impl Alignment {
+  fn unreachable() -> Self {
+    Self {
+      cost: f32::INFINITY,
+      normalized_cost: f32::INFINITY,
+      path: Vec::new(),
+    }
+  }
+}
+
+/// Dynamic Time Warping for alignment
+pub struct DTWAligner {
+  config: DtwConfig,
+}
+
+impl Default for DTWAligner {
+  fn default() -> Self {
+    Self::new(DtwConfig::default())
+  }
+}
+
+impl DTWAligner {
+  pub fn new(config: DtwConfig) -> Self {
+    Self { config }
+  }
+
+  pub fn config(&self) -> &DtwConfig {
+    &self.config
+  }
+
+  /// Align two sequences within the configured window.
+  ///
+  /// Only cells inside the window are stored, so a Sakoe-Chiba band of radius `r` needs
+  /// `O(n * r)` memory rather than `O(n * m)`.
+  pub fn align(&self, seq1: &[Vec<f32>], seq2: &[Vec<f32>]) -> Alignment {
+    if seq1.is_empty() || seq2.is_empty() {
+      return Alignment::unreachable();
+    }
+
+    let band = self.window_band(seq1.len(), seq2.len());
+    self.align_in_band(seq1, seq2, &band)
+  }
+
+  /// Memory-bounded alignment for long recordings.
+  ///
+  /// Aligns progressively halved copies of both sequences and only searches within `radius`
+  /// frames of the coarser path at each finer level, so memory grows linearly with length. The
+  /// result may be slightly worse than [`DTWAligner::align`] when the best path is not visible at
+  /// the coarse resolutions.
+  pub fn align_multiscale(&self, seq1: &[Vec<f32>], seq2: &[Vec<f32>], radius: usize) -> Alignment {
+    let (n, m) = (seq1.len(), seq2.len());
+    if n == 0 || m == 0 {
+      return Alignment::unreachable();
+    }
+
+    let min_len = radius + 2;
+    if n <= min_len || m <= min_len {
+      return self.align(seq1, seq2);
+    }
+
+    let coarse = self.align_multiscale(&halve(seq1), &halve(seq2), radius);
+    if coarse.path.is_empty() {
+      return self.align(seq1, seq2);
+    }
+
+    let window = self.window_band(n, m);
+    let projected = Band::around_path(&coarse.path, n, m, radius);
+    let band = projected.intersect(&window, m);
+    self.align_in_band(seq1, seq2, &band)
+  }
+
+  fn window_band(&self, n: usize, m: usize) -> Band {
+    match self.config.window {
+      Window::None => Band::full(n, m),
+      Window::SakoeChiba { radius } => Band::sakoe_chiba(n, m, radius),
+      Window::Itakura { max_slope } if (n.max(m) as f32) < max_slope * n.min(m) as f32 => {
+        Band::itakura(n, m, max_slope)
+      }
+      // A path forced through the collapsed parallelogram matches sounds with whatever lies on
+      // the diagonal, so a learner speaking at a third of the pace looks like they said nothing
+      Window::Itakura { .. } => Band::full(n, m),
+    }
+  }
+
+  fn align_in_band(&self, seq1: &[Vec<f32>], seq2: &[Vec<f32>], band: &Band) -> Alignment {
+    let (n, m) = (seq1.len(), seq2.len());
+    let steps = self.config.step_pattern.steps();
+
+    // Accumulated cost and chosen step for each cell in the band, row by row
+    let mut cost = vec![f32::INFINITY; band.cells()];
+    let mut back = vec![0u8; band.cells()];
+
+    for (i, frame) in seq1.iter().enumerate() {
+      let (lo, hi) = band.rows[i];
+      for (j, target) in seq2.iter().enumerate().take(hi + 1).skip(lo) {
+        let local = self.config.distance.between(frame, target);
+        let cell = band.index(i, j).unwrap();
+
+        if i == 0 && j == 0 {
+          cost[cell] = self.config.step_pattern.start_weight() * local;
+          continue;
+        }
+
+        let mut best = f32::INFINITY;
+        for (k, &(di, dj, weight)) in steps.iter().enumerate() {
+          if di > i || dj > j {
+            continue;
+          }
+          let Some(previous) = band.index(i - di, j - dj) else {
+            continue;
+          };
+          let total = cost[previous] + weight * local;
+          if total < best {
+            best = total;
+            back[cell] = k as u8;
+          }
+        }
+        cost[cell] = best;
+      }
+    }
+
+    let total = band
+      .index(n - 1, m - 1)
+      .map_or(f32::INFINITY, |cell| cost[cell]);
+    if !total.is_finite() {
+      return Alignment::unreachable();
+    }
+
+    let mut path = vec![(n - 1, m - 1)];
+    let (mut i, mut j) = (n - 1, m - 1);
+    while i > 0 || j > 0 {
+      let (di, dj, _) = steps[back[band.index(i, j).unwrap()] as usize];
+      i -= di;
+      j -= dj;
+      path.push((i, j));
+    }
+    path.reverse();
+
+    Alignment {
+      cost: total,
+      normalized_cost: total / self.config.step_pattern.normalizer(n, m, path.len()),
+      path,
+    }
+  }
+}
+
+/// Inclusive range of columns searched in each row of the cost matrix
+#[derive(Debug, Clone, PartialEq)]
+struct Band {
+  rows: Vec<(usize, usize)>,
+  /// Offset of each row's first cell in flat storage
+  offsets: Vec<usize>,
+}
+
+impl Band {
+  fn new(rows: Vec<(usize, usize)>) -> Self {
+    let mut offsets = Vec::with_capacity(rows.len() + 1);
+    let mut total = 0;
+    for &(lo, hi) in &rows {
+      offsets.push(total);
+      total += hi + 1 - lo;
+    }
+    offsets.push(total);
+    Self { rows, offsets }
+  }
+
+  fn full(n: usize, m: usize) -> Self {
+    Self::new(vec![(0, m - 1); n])
+  }
+
+  fn sakoe_chiba(n: usize, m: usize, radius: usize) -> Self {
+    let slope = if n > 1 {
+      (m - 1) as f32 / (n - 1) as f32
+    } else {
+      0.0
+    };
+    let rows = (0..n)
+      .map(|i| {
+        let centre = i as f32 * slope;
+        let lo = (centre - radius as f32).floor().max(0.0) as usize;
+        let hi = (centre + radius as f32).ceil() as usize;
+        (lo, hi)
+      })
+      .collect();
+    Self::repaired(rows, m)
+  }
+
+  fn itakura(n: usize, m: usize, max_slope: f32) -> Self {
+    let slope = max_slope.max(1.0);
+    let (last_i, last_j) = ((n - 1) as f32, (m - 1) as f32);
+    let rows = (0..n)
+      .map(|i| {
+        let i = i as f32;
+        let lower = (i / slope).max(last_j - slope * (last_i - i));
+        let upper = (i * slope).min(last_j - (last_i - i) / slope);
+        let lo = (lower - 1e-4).ceil().max(0.0) as usize;
+        let hi = (upper + 1e-4).floor().max(0.0) as usize;
+        (lo, hi)
+      })
+      .collect();
+    Self::repaired(rows, m)
+  }
+
+  /// Cells covered by a path at half resolution, widened by `radius` in both directions
+  fn around_path(coarse: &[(usize, usize)], n: usize, m: usize, radius: usize) -> Self {
+    let mut projected = vec![(usize::MAX, 0); n];
+    for &(ci, cj) in coarse {
+      for i in (2 * ci..=2 * ci + 1).filter(|&i| i < n) {
+        let (lo, hi) = &mut projected[i];
+        *lo = (*lo).min(2 * cj);
+        *hi = (*hi).max(2 * cj + 1);
+      }
+    }
+
+    let rows = (0..n)
+      .map(|i| {
+        let neighbours = &projected[i.saturating_sub(radius)..(i + radius + 1).min(n)];
+        let lo = neighbours.iter().map(|r| r.0).min().unwrap();
+        let hi = neighbours.iter().map(|r| r.1).max().unwrap();
+        (lo.saturating_sub(radius), hi + radius)
+      })
+      .collect();
+    Self::repaired(rows, m)
+  }
+
+  fn intersect(&self, other: &Band, m: usize) -> Band {
+    let rows = self
+      .rows
+      .iter()
+      .zip(&other.rows)
+      .map(|(a, b)| (a.0.max(b.0), a.1.min(b.1)))
+      .collect();
+    Self::repaired(rows, m)
+  }
+
+  /// Clamp rows to the matrix and widen where needed so a path from the first to the last cell
+  /// exists. Rows may arrive empty (`lo > hi`) when the constraint cannot be met, e.g. a Sakoe-Chiba
+  /// band too narrow for the difference in lengths.
+  fn repaired(rows: Vec<(usize, usize)>, m: usize) -> Band {
+    let mut rows: Vec<(usize, usize)> = rows
+      .into_iter()
+      .map(|(lo, hi)| (lo.min(m - 1), hi.min(m - 1)))
+      .collect();
+    let n = rows.len();
+    rows[0].0 = 0;
+    rows[n - 1].1 = m - 1;
+
+    for i in 1..n {
+      rows[i].0 = rows[i].0.max(rows[i - 1].0);
+      rows[i].1 = rows[i].1.max(rows[i - 1].1);
+    }
+    for row in &mut rows {
+      row.1 = row.1.max(row.0);
+    }
+    // Each row must start no further right than one past the previous row's end
+    for i in 1..n {
+      if rows[i].0 > rows[i - 1].1 + 1 {
+        rows[i - 1].1 = rows[i].0 - 1;
+      }
+    }
+
+    Band::new(rows)
+  }
+
+  fn cells(&self) -> usize {
+    *self.offsets.last().unwrap()
+  }
+
+  fn index(&self, i: usize, j: usize) -> Option<usize> {
+    let (lo, hi) = self.rows[i];
+    (lo..=hi).contains(&j).then(|| self.offsets[i] + j - lo)
+  }
+}
+
+/// Average adjacent frame pairs, keeping a trailing odd frame as is
+fn halve(features: &[Vec<f32>]) -> Vec<Vec<f32>> {
+  features
+    .chunks(2)
+    .map(|pair| match pair {
+      [a, b] => a.iter().zip(b).map(|(x, y)| (x + y) / 2.0).collect(),
+      [a] => a.clone(),
+      _ => unreachable!(),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sequence(values: &[f32]) -> Vec<Vec<f32>> {
+    values.iter().map(|&v| vec![v, v * 0.5]).collect()
+  }
+
+  /// A slow sweep with a plateau, sampled at two different tempos
+  fn sweep(len: usize, hold_at: f32) -> Vec<Vec<f32>> {
+    (0..len)
+      .map(|i| {
+        let t = i as f32 / (len - 1) as f32;
+        let x = if t < hold_at {
+          t
+        } else {
+          hold_at + (t - hold_at) * 0.2
+        };
+        vec![(x * 6.0).sin(), (x * 4.0).cos(), x]
+      })
+      .collect()
+  }
+
+  fn unconstrained(step_pattern: StepPattern) -> DTWAligner {
+    DTWAligner::new(DtwConfig {
+      step_pattern,
+      ..Default::default()
+    })
+  }
+
+  #[test]
+  fn test_step_patterns_and_normalisation() {
+    let a = sequence(&[0.0, 1.0, 2.0, 3.0]);
+    let b = sequence(&[0.0, 1.0, 1.0, 2.0, 3.0, 3.0]);
+
+    let symmetric1 = unconstrained(StepPattern::Symmetric1).align(&a, &b);
+    assert_eq!(symmetric1.cost, 0.0);
+    assert_eq!(symmetric1.path.first(), Some(&(0, 0)));
+    assert_eq!(symmetric1.path.last(), Some(&(3, 5)));
+
+    // An offset orthogonal to the data costs its length per unit of normaliser
+    let offset: Vec<Vec<f32>> = a.iter().map(|f| vec![f[0] - 3.0, f[1] + 6.0]).collect();
+    let expected = 45.0f32.sqrt();
+    for pattern in [
+      StepPattern::Symmetric1,
+      StepPattern::Symmetric2,
+      StepPattern::Asymmetric,
+    ] {
+      let alignment = unconstrained(pattern).align(&a, &offset);
+      assert!(
+        (alignment.normalized_cost - expected).abs() < 1e-4,
+        "{:?} {:?}",
+        pattern,
+        alignment
+      );
+    }
+
+    // Asymmetric moves always advance the first sequence
+    let asymmetric = unconstrained(StepPattern::Asymmetric).align(&a, &b);
+    assert_eq!(asymmetric.path.len(), a.len());
+    assert!(asymmetric.path.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+    // ...so the second sequence cannot be more than twice as long
+    let long = sequence(&[0.0; 9]);
+    assert!(unconstrained(StepPattern::Asymmetric)
+      .align(&a, &long)
+      .cost
+      .is_infinite());
+  }
+
+  #[test]
+  fn test_cosine_distance_ignores_scale() {
+    assert!(Distance::Cosine.between(&[1.0, 2.0], &[2.0, 4.0]) < 1e-6);
+    assert!((Distance::Cosine.between(&[1.0, 0.0], &[0.0, 3.0]) - 1.0).abs() < 1e-6);
+    assert_eq!(Distance::Cosine.between(&[0.0, 0.0], &[0.0, 0.0]), 0.0);
+    assert_eq!(Distance::Euclidean.between(&[0.0, 0.0], &[3.0, 4.0]), 5.0);
+  }
+
+  #[test]
+  fn test_windows_bound_the_path() {
+    let a = sweep(40, 0.5);
+    let b = sweep(60, 0.3);
+
+    let sakoe = DTWAligner::new(DtwConfig {
+      window: Window::SakoeChiba { radius: 3 },
+      ..Default::default()
+    })
+    .align(&a, &b);
+    for &(i, j) in &sakoe.path {
+      let centre = i as f32 * 59.0 / 39.0;
+      assert!((j as f32 - centre).abs() <= 4.0, "({}, {})", i, j);
+    }
+
+    let itakura = DTWAligner::new(DtwConfig {
+      window: Window::Itakura { max_slope: 2.0 },
+      ..Default::default()
+    })
+    .align(&a, &b);
+    for &(i, j) in &itakura.path {
+      assert!(j as f32 <= 2.0 * i as f32 + 1e-3 && i as f32 <= 2.0 * j as f32 + 1e-3);
+    }
+
+    // Constraints can only make the optimum worse
+    let free = DTWAligner::default().align(&a, &b);
+    assert!(free.cost <= sakoe.cost && free.cost <= itakura.cost);
+  }
+
+  #[test]
+  fn test_window_too_narrow_for_lengths_still_connects() {
+    // Radius 0 on very different lengths would leave gaps without repair...
+    let a = sweep(10, 0.5);
+    let b = sweep(35, 0.5);
+    // ...and an Itakura parallelogram, which has no cells at all beyond a 2x length ratio, is
+    // dropped altogether
+    for window in [
+      Window::SakoeChiba { radius: 0 },
+      Window::Itakura { max_slope: 2.0 },
+    ] {
+      let alignment = DTWAligner::new(DtwConfig {
+        window,
+        ..Default::default()
+      })
+      .align(&a, &b);
+      assert!(alignment.cost.is_finite(), "{:?}", window);
+      assert_eq!(alignment.path.last(), Some(&(9, 34)));
+    }
+  }
+
+  #[test]
+  fn test_multiscale_matches_full_alignment() {
+    let a = sweep(400, 0.6);
+    let b = sweep(520, 0.4);
+    let aligner = DTWAligner::default();
+
+    let full = aligner.align(&a, &b);
+    let fast = aligner.align_multiscale(&a, &b, 4);
+    assert!(fast.cost >= full.cost);
+    assert!(
+      fast.cost <= full.cost * 1.05 + 1e-3,
+      "{} vs {}",
+      fast.cost,
+      full.cost
+    );
+    assert_eq!(fast.path.first(), Some(&(0, 0)));
+    assert_eq!(fast.path.last(), Some(&(399, 519)));
+
+    // The finest search only covers a narrow corridor around the coarse path
+    let coarse = aligner.align_multiscale(&halve(&a), &halve(&b), 4);
+    let band = Band::around_path(&coarse.path, a.len(), b.len(), 4);
+    assert!(band.cells() < a.len() * b.len() / 10);
+  }
+}
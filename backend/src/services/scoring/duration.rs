@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use super::{Diagnosis, PhonemeScore};
+use crate::services::ipa::{self, Transcription};
+
+/// Vowels distinguished from each other mainly by length, long first
+const LENGTH_PAIRS: &[(&str, &str)] = &[
+  ("iː", "ɪ"),
+  ("uː", "ʊ"),
+  ("ɑː", "ʌ"),
+  ("ɔː", "ɒ"),
+  ("ɜː", "ə"),
+  ("ɛː", "ɛ"),
+  ("æː", "æ"),
+];
+
+/// Thresholds for judging segment length
+#[derive(Debug, Clone)]
+pub struct DurationConfig {
+  /// Tempo-adjusted length ratio above which a vowel is over-lengthened
+  pub lengthened: f32,
+  /// Tempo-adjusted length ratio below which a vowel is clipped
+  pub clipped: f32,
+  /// Tempo-adjusted ratio at or below which a long vowel is heard as its short partner; a short
+  /// vowel at or above the reciprocal is heard as its long partner
+  pub contrast: f32,
+  /// Reference segments shorter than this are too brief to judge
+  pub min_reference_ms: u32,
+}
+
+impl Default for DurationConfig {
+  fn default() -> Self {
+    Self {
+      lengthened: 1.6,
+      clipped: 0.6,
+      contrast: 0.75,
+      min_reference_ms: 30,
+    }
+  }
+}
+
+/// A vowel held noticeably longer or shorter than the reference, allowing for overall tempo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthIssue {
+  Lengthened,
+  Clipped,
+}
+
+/// How long the learner held one target phoneme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhonemeDuration {
+  pub phoneme: String,
+  pub learner_ms: u32,
+  pub reference_ms: u32,
+  /// Learner's length over the reference's, divided by the tempo of the rest of the word; 1 is
+  /// in proportion
+  pub relative: f32,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub issue: Option<LengthIssue>,
+}
+
+/// How long the learner took over one syllable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyllableDuration {
+  pub start_ms: u32,
+  pub end_ms: u32,
+  pub reference_ms: u32,
+  /// Learner's length over the reference's, divided by the word's tempo
+  pub relative: f32,
+}
+
+/// A long vowel said short enough to pass for its short partner, or the reverse
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LengthContrast {
+  /// Position of the vowel in the target's phonemes
+  pub phoneme_index: usize,
+  pub expected: String,
+  pub heard_as: String,
+}
+
+/// Pace of the whole word
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpeakingRate {
+  pub learner_syllables_per_sec: f32,
+  pub reference_syllables_per_sec: f32,
+  pub learner_phones_per_sec: f32,
+  pub reference_phones_per_sec: f32,
+  /// Learner's duration over the reference's; above 1 is slower
+  pub tempo: f32,
+}
+
+/// Segment timing compared against the reference
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationScore {
+  pub rate: SpeakingRate,
+  pub phonemes: Vec<PhonemeDuration>,
+  pub syllables: Vec<SyllableDuration>,
+  pub missing_contrasts: Vec<LengthContrast>,
+}
+
+/// Compares the learner's aligned segment durations with a reference's
+#[derive(Debug, Clone, Default)]
+pub struct DurationAnalyzer {
+  config: DurationConfig,
+}
+
+impl DurationAnalyzer {
+  pub fn new(config: DurationConfig) -> Self {
+    Self { config }
+  }
+
+  /// Compare phoneme and syllable lengths, after allowing for the learner speaking faster or
+  /// slower overall.
+  ///
+  /// `reference` holds each target phoneme's span in the reference, in ms. Returns `None` when
+  /// either side does not cover the transcription's phonemes or takes no time.
+  pub fn analyze(
+    &self,
+    per_phoneme: &[PhonemeScore],
+    reference: &[Range<u32>],
+    target: &Transcription,
+  ) -> Option<DurationScore> {
+    let n = target.phonemes.len();
+    if n == 0 || per_phoneme.len() != n || reference.len() != n {
+      return None;
+    }
+    let learner_total = per_phoneme[n - 1]
+      .end_ms
+      .saturating_sub(per_phoneme[0].start_ms);
+    let reference_total = reference[n - 1].end.saturating_sub(reference[0].start);
+    if learner_total == 0 || reference_total == 0 {
+      return None;
+    }
+    let tempo = learner_total as f32 / reference_total as f32;
+    let relative = |learner_ms: u32, reference_ms: u32, tempo: f32| {
+      learner_ms as f32 / reference_ms.max(1) as f32 / tempo
+    };
+
+    let phonemes: Vec<PhonemeDuration> = per_phoneme
+      .iter()
+      .zip(reference)
+      .map(|(phoneme, span)| {
+        let learner_ms = phoneme.end_ms.saturating_sub(phoneme.start_ms);
+        let reference_ms = span.end.saturating_sub(span.start);
+        // Pace of the rest of the word, so a clipped vowel does not also set the tempo it is
+        // measured against
+        let rest = (
+          learner_total.saturating_sub(learner_ms),
+          reference_total.saturating_sub(reference_ms),
+        );
+        let pace = match rest {
+          (0, _) | (_, 0) => tempo,
+          (learner, reference) => learner as f32 / reference as f32,
+        };
+        let relative = relative(learner_ms, reference_ms, pace);
+        // Deleted vowels are already reported; they are not merely short
+        let judged = ipa::is_vowel(&phoneme.phoneme)
+          && phoneme.diagnosis != Diagnosis::Deleted
+          && reference_ms >= self.config.min_reference_ms;
+        let issue = if !judged {
+          None
+        } else if relative > self.config.lengthened {
+          Some(LengthIssue::Lengthened)
+        } else if relative < self.config.clipped {
+          Some(LengthIssue::Clipped)
+        } else {
+          None
+        };
+        PhonemeDuration {
+          phoneme: phoneme.phoneme.clone(),
+          learner_ms,
+          reference_ms,
+          relative,
+          issue,
+        }
+      })
+      .collect();
+
+    let syllables = syllable_extents(target)
+      .into_iter()
+      .map(|phones| {
+        let (first, last) = (phones.start, phones.end - 1);
+        let (start_ms, end_ms) = (per_phoneme[first].start_ms, per_phoneme[last].end_ms);
+        let reference_ms = reference[last].end.saturating_sub(reference[first].start);
+        SyllableDuration {
+          start_ms,
+          end_ms,
+          reference_ms,
+          relative: relative(end_ms.saturating_sub(start_ms), reference_ms, tempo),
+        }
+      })
+      .collect();
+
+    let missing_contrasts = phonemes
+      .iter()
+      .enumerate()
+      .filter(|(i, d)| {
+        per_phoneme[*i].diagnosis != Diagnosis::Deleted
+          && d.reference_ms >= self.config.min_reference_ms
+      })
+      .filter_map(|(phoneme_index, d)| {
+        let heard_as = LENGTH_PAIRS.iter().find_map(|&(long, short)| {
+          if d.phoneme == long && d.relative <= self.config.contrast {
+            Some(short)
+          } else if d.phoneme == short && d.relative >= 1.0 / self.config.contrast {
+            Some(long)
+          } else {
+            None
+          }
+        })?;
+        Some(LengthContrast {
+          phoneme_index,
+          expected: d.phoneme.clone(),
+          heard_as: heard_as.to_string(),
+        })
+      })
+      .collect();
+
+    // A word without marked syllables still has one per vowel
+    let syllable_count = if target.syllables.is_empty() {
+      target.phonemes.iter().filter(|p| ipa::is_vowel(p)).count()
+    } else {
+      target.syllables.len()
+    };
+    let per_sec = |count: usize, ms: u32| count as f32 * 1000.0 / ms as f32;
+    Some(DurationScore {
+      rate: SpeakingRate {
+        learner_syllables_per_sec: per_sec(syllable_count, learner_total),
+        reference_syllables_per_sec: per_sec(syllable_count, reference_total),
+        learner_phones_per_sec: per_sec(n, learner_total),
+        reference_phones_per_sec: per_sec(n, reference_total),
+        tempo,
+      },
+      phonemes,
+      syllables,
+      missing_contrasts,
+    })
+  }
+}
+
+/// Phonemes of each syllable. Consonants between two nuclei are split with the larger half, or
+/// a lone consonant, going to the following onset.
+fn syllable_extents(target: &Transcription) -> Vec<Range<usize>> {
+  let nuclei = &target.syllables;
+  (0..nuclei.len())
+    .map(|i| {
+      let start = match i {
+        0 => 0,
+        _ => {
+          let (previous, this) = (nuclei[i - 1].nucleus.end, nuclei[i].nucleus.start);
+          previous + this.saturating_sub(previous) / 2
+        }
+      };
+      let end = match nuclei.get(i + 1) {
+        None => target.phonemes.len(),
+        Some(next) => {
+          let this = nuclei[i].nucleus.end;
+          this + next.nucleus.start.saturating_sub(this) / 2
+        }
+      };
+      start..end.max(start + 1)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Learner phoneme times laid end to end from per-phoneme durations
+  fn timed(target: &Transcription, durations: &[u32]) -> Vec<PhonemeScore> {
+    let mut start_ms = 200;
+    target
+      .phonemes
+      .iter()
+      .zip(durations)
+      .map(|(phoneme, &ms)| {
+        start_ms += ms;
+        PhonemeScore {
+          phoneme: phoneme.clone(),
+          start_ms: start_ms - ms,
+          end_ms: start_ms,
+          score: 100.0,
+          ..Default::default()
+        }
+      })
+      .collect()
+  }
+
+  fn spans(durations: &[u32]) -> Vec<Range<u32>> {
+    let mut start = 0;
+    durations
+      .iter()
+      .map(|&ms| {
+        start += ms;
+        start - ms..start
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_uniformly_slower_speech_has_no_issues() {
+    let target = Transcription::parse("bəˈnænə");
+    let reference = [60, 50, 60, 150, 60, 70];
+    let learner: Vec<u32> = reference.iter().map(|ms| ms * 2).collect();
+
+    let result = DurationAnalyzer::default()
+      .analyze(&timed(&target, &learner), &spans(&reference), &target)
+      .unwrap();
+    assert!((result.rate.tempo - 2.0).abs() < 1e-3);
+    assert!((result.rate.reference_syllables_per_sec - 3000.0 / 450.0).abs() < 1e-3);
+    assert!(result.phonemes.iter().all(|p| p.issue.is_none()));
+    assert!(result.missing_contrasts.is_empty());
+    // bə | ˈnæ | nə
+    assert_eq!(result.syllables.len(), 3);
+    assert_eq!(result.syllables[1].reference_ms, 210);
+    assert!(result
+      .syllables
+      .iter()
+      .all(|s| (s.relative - 1.0).abs() < 1e-3));
+  }
+
+  #[test]
+  fn test_flags_lengthened_and_clipped_vowels() {
+    let target = Transcription::parse("bəˈnænə");
+    let reference = [60, 50, 60, 150, 60, 70];
+    let learner = [60, 120, 60, 60, 60, 70];
+
+    let result = DurationAnalyzer::default()
+      .analyze(&timed(&target, &learner), &spans(&reference), &target)
+      .unwrap();
+    let issues: Vec<Option<LengthIssue>> = result.phonemes.iter().map(|p| p.issue).collect();
+    assert_eq!(
+      issues,
+      [
+        None,
+        Some(LengthIssue::Lengthened),
+        None,
+        Some(LengthIssue::Clipped),
+        None,
+        None
+      ]
+    );
+  }
+
+  #[test]
+  fn test_clipped_long_vowel_loses_its_contrast() {
+    let target = Transcription::parse("ˈliːd");
+    let reference = [70, 260, 80];
+
+    let short = DurationAnalyzer::default()
+      .analyze(&timed(&target, &[70, 120, 80]), &spans(&reference), &target)
+      .unwrap();
+    assert_eq!(
+      short.missing_contrasts,
+      [LengthContrast {
+        phoneme_index: 1,
+        expected: "iː".to_string(),
+        heard_as: "ɪ".to_string(),
+      }]
+    );
+
+    let held = DurationAnalyzer::default()
+      .analyze(&timed(&target, &[70, 240, 80]), &spans(&reference), &target)
+      .unwrap();
+    assert!(held.missing_contrasts.is_empty());
+  }
+
+  #[test]
+  fn test_mismatched_lengths_are_not_analyzed() {
+    let target = Transcription::parse("ˈliːd");
+    let analyzer = DurationAnalyzer::default();
+    assert!(analyzer
+      .analyze(&timed(&target, &[70, 120]), &spans(&[70, 260, 80]), &target)
+      .is_none());
+    assert!(analyzer
+      .analyze(&timed(&target, &[0, 0, 0]), &spans(&[70, 260, 80]), &target)
+      .is_none());
+  }
+}
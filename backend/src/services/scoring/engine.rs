@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use super::confidence::ConfidenceModel;
+use super::gop::{GopScorer, GOP};
+use super::quality::QualityReport;
+use super::{PronunciationScore, PronunciationScorer, Reference};
+use crate::models::Dialect;
+use crate::services::ipa::Transcription;
+
+/// Which implementation and revision produced a score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EngineId {
+  pub name: &'static str,
+  /// Bumped whenever a change alters the scores produced for the same input, with the change
+  /// noted beside the engine's constant
+  pub version: &'static str,
+}
+
+/// MFCC features aligned to reference speakers with DTW.
+///
+/// - 1.1: substituted, deleted and inserted sounds diagnosed per phoneme
+/// - 1.2: phoneme spans taken from the reference's stored boundaries
+/// - 1.3: affricates and diphthongs scored as single phonemes
+/// - 1.4: substitutions earn partial credit by articulatory distance
+/// - 1.5: confidence judged on the recording before noise reduction
+/// - 1.6: recordings at least twice as fast or slow as the reference aligned without a window
+/// - 1.7: SNR and clipping taken from the quality check, which measures SNR without DC offset
+/// - 1.8: confidence compares against at most eight minimal-pair neighbours, one recording each
+/// - 1.9: synthesized references take their consonants from the shared articulatory features
+pub const MFCC_DTW: EngineId = EngineId {
+  name: "mfcc-dtw",
+  version: "1.9",
+};
+
+/// Everything an engine may use to score one attempt
+pub struct ScoringInput<'a> {
+  /// The learner's recording, 16 kHz mono
+  pub audio: &'a [f32],
+  /// The quality check of the recording as made, before any noise reduction; confidence judges
+  /// recording conditions such as SNR and clipping from it
+  pub quality: &'a QualityReport,
+  pub target: &'a Transcription,
+  pub dialect: &'a Dialect,
+  /// Reference speakers' recordings of the word; engines that do not compare against a
+  /// reference ignore them
+  pub references: &'a [Reference<'a>],
+  /// Reference recordings of the word's minimal pairs
+  pub competitors: &'a [&'a [f32]],
+}
+
+/// A pronunciation scoring engine
+pub trait Scorer: Send {
+  fn engine(&self) -> EngineId;
+
+  /// Whether `score` needs reference recordings; callers can skip fetching them otherwise
+  fn uses_reference_audio(&self) -> bool {
+    true
+  }
+
+  fn score(&mut self, input: &ScoringInput) -> Result<PronunciationScore>;
+}
+
+/// Build the engine named in configuration. `gop_model_dir` holds the acoustic model for the
+/// GOP engine, and `confidence` coefficients fitted for the engine replace the hand-set ones.
+pub fn scorer_from_config(
+  name: &str,
+  gop_model_dir: Option<&Path>,
+  confidence: Option<&ConfidenceModel>,
+) -> Result<Box<dyn Scorer>> {
+  let confidence = confidence.cloned().unwrap_or_default();
+  match name {
+    name if name == MFCC_DTW.name => Ok(Box::new(
+      alignment_scorer(name)?.with_confidence_model(confidence),
+    )),
+    name if name == GOP.name => {
+      let dir = gop_model_dir.context("The GOP engine needs GOP_MODEL_DIR")?;
+      Ok(Box::new(
+        GopScorer::load(dir)?.with_confidence_model(confidence),
+      ))
+    }
+    other => bail!("Unknown scoring engine '{}'", other),
+  }
+}
+
+/// A scorer that aligns recordings as the named engine's scores were aligned, for drawing a
+/// recording over its reference. GOP compares against acoustic models rather than a reference,
+/// so its scores are drawn over the MFCC/DTW alignment.
+pub fn alignment_scorer(name: &str) -> Result<PronunciationScorer> {
+  match name {
+    name if name == MFCC_DTW.name || name == GOP.name => Ok(PronunciationScorer::new()),
+    other => bail!("Unknown scoring engine '{}'", other),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::QualityGate;
+
+  #[test]
+  fn test_engine_is_selected_by_name() {
+    let mut scorer = scorer_from_config("mfcc-dtw", None, None).unwrap();
+    assert_eq!(scorer.engine(), MFCC_DTW);
+    assert!(scorer.uses_reference_audio());
+    assert!(scorer_from_config("wav2vec", None, None).is_err());
+    assert!(scorer_from_config("gop", None, None).is_err());
+    // GOP scores are drawn over the MFCC/DTW alignment, which needs no model
+    assert!(alignment_scorer("gop").is_ok());
+    assert!(alignment_scorer("wav2vec").is_err());
+
+    let audio = vec![0.1; 4000];
+    let score = scorer
+      .score(&ScoringInput {
+        audio: &audio,
+        quality: &QualityGate::default().check(&audio, None),
+        target: &Transcription::parse("ˈkæt"),
+        dialect: &Dialect::GA,
+        references: &[Reference::new(&audio)],
+        competitors: &[],
+      })
+      .unwrap();
+    assert_eq!(score.engine, MFCC_DTW);
+    assert_eq!(score.per_phoneme.len(), 3);
+  }
+}
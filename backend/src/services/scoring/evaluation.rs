@@ -0,0 +1,574 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::audio::AudioProcessor;
+use super::confidence::ConfidenceFeatures;
+use super::diagnosis::Diagnosis;
+use super::engine::{EngineId, Scorer, ScoringInput};
+use super::quality::QualityGate;
+use super::synth::FormantSynthesizer;
+use super::{PhonemeScore, PronunciationScore, Reference, SAMPLE_RATE};
+use crate::models::Dialect;
+use crate::services::ipa::Transcription;
+
+/// Marks a target phoneme nothing was heard for
+pub const DELETED: &str = "-";
+
+/// Stands for a wrong sound the engine could not name
+pub const UNKNOWN: &str = "?";
+
+/// An overall score agrees with the tutors when its rank among the scored attempts is within
+/// this fraction of the way the tutors ranked the same attempt
+pub const AGREEMENT_RANK_GAP: f32 = 0.25;
+
+/// One tutor-rated attempt, a line of JSON in an evaluation manifest:
+///
+/// ```json
+/// {"recording": "clips/0001.wav", "reference": "refs/cat.wav", "ipa": "ˈkæt", "rating": 4,
+///  "heard": ["k", "ɛ", "-"]}
+/// ```
+///
+/// Relative paths are resolved against the manifest's directory. Without a `reference`,
+/// engines that need one score against a synthesized reading, as the worker does.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestRow {
+  pub recording: PathBuf,
+  #[serde(default)]
+  pub reference: Option<PathBuf>,
+  pub ipa: String,
+  /// The tutor's overall rating, on any scale where higher is better
+  pub rating: f32,
+  #[serde(default = "default_dialect")]
+  pub dialect: Dialect,
+  /// What the tutor heard for each target phoneme, `-` for nothing
+  #[serde(default)]
+  pub heard: Option<Vec<String>>,
+}
+
+fn default_dialect() -> Dialect {
+  Dialect::GA
+}
+
+/// Read a JSON Lines manifest; blank lines and lines starting with `#` are skipped
+pub fn read_manifest(path: &Path) -> Result<Vec<ManifestRow>> {
+  let text = std::fs::read_to_string(path)
+    .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+  let base = path.parent().unwrap_or(Path::new(""));
+
+  text
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+    .map(|(index, line)| {
+      let mut row: ManifestRow = serde_json::from_str(line)
+        .with_context(|| format!("Invalid manifest row at {}:{}", path.display(), index + 1))?;
+      row.recording = base.join(&row.recording);
+      row.reference = row.reference.map(|reference| base.join(reference));
+      Ok(row)
+    })
+    .collect()
+}
+
+/// A row that could not be loaded or scored
+#[derive(Debug, Clone, Serialize)]
+pub struct Failure {
+  /// 1-based position among the manifest's rows
+  pub row: usize,
+  pub recording: PathBuf,
+  pub error: String,
+}
+
+/// How well the engine's per-phoneme diagnosis matches what the tutors heard
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhonemeAgreement {
+  /// Target phonemes with a tutor label
+  pub labelled: usize,
+  /// Fraction of labelled phonemes where tutor and engine agree whether it was said right
+  pub agreement: Option<f32>,
+  /// The same agreement with chance agreement discounted (Cohen's kappa)
+  pub kappa: Option<f32>,
+  /// Counts of right/wrong verdicts, indexed `[tutor][engine]` with 0 for right
+  pub verdicts: [[usize; 2]; 2],
+  /// How often each sound the tutor heard was reported as each sound by the engine; `-` is a
+  /// deletion and `?` a substitution the engine could not name
+  pub confusion: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl PhonemeAgreement {
+  fn add(&mut self, target: &str, tutor: &str, engine: &str) {
+    let tutor_wrong = usize::from(tutor != target);
+    let engine_wrong = usize::from(engine != target);
+    self.labelled += 1;
+    self.verdicts[tutor_wrong][engine_wrong] += 1;
+    *self
+      .confusion
+      .entry(tutor.to_string())
+      .or_default()
+      .entry(engine.to_string())
+      .or_default() += 1;
+  }
+
+  fn finish(&mut self) {
+    if self.labelled == 0 {
+      return;
+    }
+    let n = self.labelled as f32;
+    let [[both_right, engine_wrong], [tutor_wrong, both_wrong]] = self.verdicts;
+    let observed = (both_right + both_wrong) as f32 / n;
+    let tutor_right = (both_right + engine_wrong) as f32 / n;
+    let engine_right = (both_right + tutor_wrong) as f32 / n;
+    let chance = tutor_right * engine_right + (1.0 - tutor_right) * (1.0 - engine_right);
+
+    self.agreement = Some(observed);
+    self.kappa = (chance < 1.0).then(|| (observed - chance) / (1.0 - chance));
+  }
+}
+
+/// Time spent in the engine, excluding audio decoding
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Runtime {
+  pub total_ms: f32,
+  pub mean_ms: f32,
+  pub p95_ms: f32,
+  /// Scoring time as a fraction of the audio's duration
+  pub real_time_factor: f32,
+}
+
+/// Agreement of one engine with tutor ratings over a manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct EvaluationReport {
+  pub engine: EngineId,
+  pub rows: usize,
+  pub scored: usize,
+  /// Correlation of overall scores with tutor ratings
+  pub pearson: Option<f32>,
+  pub spearman: Option<f32>,
+  pub phonemes: PhonemeAgreement,
+  pub runtime: Runtime,
+  pub failures: Vec<Failure>,
+  /// Each scored row's confidence evidence, labelled with whether its score agreed with the
+  /// tutors; the training set for [`super::ConfidenceModel::fit`]
+  #[serde(skip)]
+  pub confidence_samples: Vec<(ConfidenceFeatures, bool)>,
+}
+
+/// Score every row and compare the results with the tutors'. Rows that fail are reported
+/// rather than ending the run.
+pub fn evaluate(scorer: &mut dyn Scorer, rows: &[ManifestRow]) -> EvaluationReport {
+  let mut ratings = Vec::new();
+  let mut scores = Vec::new();
+  let mut features = Vec::new();
+  let mut timings = Vec::new();
+  let mut audio_ms = 0.0;
+  let mut phonemes = PhonemeAgreement::default();
+  let mut failures = Vec::new();
+
+  for (index, row) in rows.iter().enumerate() {
+    match score_row(scorer, row) {
+      Ok((score, elapsed, duration_ms)) => {
+        ratings.push(row.rating);
+        scores.push(score.overall_pct);
+        features.push(score.confidence_features);
+        timings.push(elapsed.as_secs_f32() * 1000.0);
+        audio_ms += duration_ms;
+        if let Some(heard) = &row.heard {
+          for (phoneme, tutor) in score.per_phoneme.iter().zip(heard) {
+            phonemes.add(&phoneme.phoneme, tutor, &engine_heard(phoneme));
+          }
+        }
+      }
+      Err(error) => failures.push(Failure {
+        row: index + 1,
+        recording: row.recording.clone(),
+        error: format!("{:#}", error),
+      }),
+    }
+  }
+  phonemes.finish();
+
+  let total_ms: f32 = timings.iter().sum();
+  timings.sort_by(f32::total_cmp);
+  let runtime = Runtime {
+    total_ms,
+    mean_ms: total_ms / timings.len().max(1) as f32,
+    p95_ms: timings
+      .get((timings.len() * 95).div_ceil(100).saturating_sub(1))
+      .copied()
+      .unwrap_or(0.0),
+    real_time_factor: if audio_ms > 0.0 {
+      total_ms / audio_ms
+    } else {
+      0.0
+    },
+  };
+
+  let confidence_samples = features
+    .into_iter()
+    .zip(listener_agreement(&scores, &ratings))
+    .collect();
+
+  EvaluationReport {
+    engine: scorer.engine(),
+    rows: rows.len(),
+    scored: scores.len(),
+    pearson: pearson(&scores, &ratings),
+    spearman: spearman(&scores, &ratings),
+    phonemes,
+    runtime,
+    failures,
+    confidence_samples,
+  }
+}
+
+/// Whether each score agrees with its tutor rating, judged by rank so that neither needs to be
+/// on the other's scale. Empty with fewer than two scores.
+pub fn listener_agreement(scores: &[f32], ratings: &[f32]) -> Vec<bool> {
+  let n = scores.len().min(ratings.len());
+  if n < 2 {
+    return Vec::new();
+  }
+  let percentile = |values: &[f32]| -> Vec<f32> {
+    ranks(&values[..n])
+      .into_iter()
+      .map(|rank| (rank - 1.0) / (n - 1) as f32)
+      .collect()
+  };
+  percentile(scores)
+    .into_iter()
+    .zip(percentile(ratings))
+    .map(|(score, rating)| (score - rating).abs() <= AGREEMENT_RANK_GAP)
+    .collect()
+}
+
+/// Score one row, returning the time spent in the engine and the recording's length in ms
+fn score_row(
+  scorer: &mut dyn Scorer,
+  row: &ManifestRow,
+) -> Result<(PronunciationScore, Duration, f32)> {
+  let target = Transcription::parse(&row.ipa);
+  if let Some(heard) = &row.heard {
+    ensure!(
+      heard.len() == target.phonemes.len(),
+      "'heard' has {} symbols for the {} phonemes of /{}/",
+      heard.len(),
+      target.phonemes.len(),
+      row.ipa
+    );
+  }
+
+  let audio = AudioProcessor::load_audio_file(&row.recording)
+    .with_context(|| format!("Failed to load {}", row.recording.display()))?;
+  let reference = match &row.reference {
+    Some(path) => AudioProcessor::load_audio_file(path)
+      .with_context(|| format!("Failed to load {}", path.display()))?,
+    None if scorer.uses_reference_audio() => {
+      FormantSynthesizer::for_dialect(&row.dialect)
+        .synthesize(&target)?
+        .audio
+    }
+    None => Vec::new(),
+  };
+  let references: Vec<Reference> = if reference.is_empty() {
+    Vec::new()
+  } else {
+    vec![Reference::new(&reference)]
+  };
+
+  let started = Instant::now();
+  let score = scorer.score(&ScoringInput {
+    audio: &audio,
+    quality: &QualityGate::default().check(&audio, None),
+    target: &target,
+    dialect: &row.dialect,
+    references: &references,
+    competitors: &[],
+  })?;
+  let elapsed = started.elapsed();
+
+  Ok((
+    score,
+    elapsed,
+    audio.len() as f32 * 1000.0 / SAMPLE_RATE as f32,
+  ))
+}
+
+/// The sound the engine reports for a target phoneme, in the manifest's notation
+fn engine_heard(phoneme: &PhonemeScore) -> String {
+  match &phoneme.diagnosis {
+    Diagnosis::Correct => phoneme.phoneme.clone(),
+    Diagnosis::Substituted { produced } => produced.clone().unwrap_or_else(|| UNKNOWN.into()),
+    Diagnosis::Deleted => DELETED.into(),
+  }
+}
+
+/// Pearson correlation; `None` for fewer than two pairs or a constant series
+pub fn pearson(xs: &[f32], ys: &[f32]) -> Option<f32> {
+  let n = xs.len().min(ys.len());
+  if n < 2 {
+    return None;
+  }
+  let mean = |values: &[f32]| values[..n].iter().map(|&v| v as f64).sum::<f64>() / n as f64;
+  let (mean_x, mean_y) = (mean(xs), mean(ys));
+
+  let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+  for (&x, &y) in xs.iter().zip(ys) {
+    let (dx, dy) = (x as f64 - mean_x, y as f64 - mean_y);
+    covariance += dx * dy;
+    var_x += dx * dx;
+    var_y += dy * dy;
+  }
+  if var_x <= f64::EPSILON || var_y <= f64::EPSILON {
+    return None;
+  }
+  Some((covariance / (var_x * var_y).sqrt()) as f32)
+}
+
+/// Spearman rank correlation, with tied values sharing their mean rank
+pub fn spearman(xs: &[f32], ys: &[f32]) -> Option<f32> {
+  pearson(&ranks(xs), &ranks(ys))
+}
+
+fn ranks(values: &[f32]) -> Vec<f32> {
+  let mut order: Vec<usize> = (0..values.len()).collect();
+  order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+  let mut ranks = vec![0.0; values.len()];
+  let mut start = 0;
+  while start < order.len() {
+    let mut end = start + 1;
+    while end < order.len() && values[order[end]] == values[order[start]] {
+      end += 1;
+    }
+    // Ranks are 1-based; a tie of positions start..end shares their mean
+    let rank = (start + end + 1) as f32 / 2.0;
+    for &i in &order[start..end] {
+      ranks[i] = rank;
+    }
+    start = end;
+  }
+  ranks
+}
+
+impl fmt::Display for EvaluationReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let number = |value: Option<f32>| value.map_or("n/a".to_string(), |v| format!("{:.3}", v));
+
+    writeln!(
+      f,
+      "Engine {} {}: {} of {} rows scored",
+      self.engine.name, self.engine.version, self.scored, self.rows
+    )?;
+    writeln!(
+      f,
+      "Overall score against tutor rating: Pearson {}, Spearman {}",
+      number(self.pearson),
+      number(self.spearman)
+    )?;
+
+    let phonemes = &self.phonemes;
+    if phonemes.labelled > 0 {
+      writeln!(
+        f,
+        "\nPhonemes: {} labelled, agreement {}, kappa {}",
+        phonemes.labelled,
+        number(phonemes.agreement),
+        number(phonemes.kappa)
+      )?;
+      let [[both_right, engine_wrong], [tutor_wrong, both_wrong]] = phonemes.verdicts;
+      writeln!(
+        f,
+        "{:>14} {:>12} {:>12}",
+        "", "engine right", "engine wrong"
+      )?;
+      writeln!(
+        f,
+        "{:>14} {:>12} {:>12}",
+        "tutor right", both_right, engine_wrong
+      )?;
+      writeln!(
+        f,
+        "{:>14} {:>12} {:>12}",
+        "tutor wrong", tutor_wrong, both_wrong
+      )?;
+
+      // Every sound either side reported, rows by tutor and columns by engine
+      let symbols: BTreeSet<&str> = phonemes
+        .confusion
+        .iter()
+        .flat_map(|(tutor, row)| {
+          std::iter::once(tutor.as_str()).chain(row.keys().map(String::as_str))
+        })
+        .collect();
+      writeln!(
+        f,
+        "\nConfusions (rows: tutor heard, columns: engine reported)"
+      )?;
+      write!(f, "{:>6}", "")?;
+      for symbol in &symbols {
+        write!(f, "{:>6}", symbol)?;
+      }
+      writeln!(f)?;
+      for tutor in &symbols {
+        write!(f, "{:>6}", tutor)?;
+        for engine in &symbols {
+          let count = phonemes
+            .confusion
+            .get(*tutor)
+            .and_then(|row| row.get(*engine))
+            .copied()
+            .unwrap_or(0);
+          write!(f, "{:>6}", count)?;
+        }
+        writeln!(f)?;
+      }
+    }
+
+    let runtime = &self.runtime;
+    writeln!(
+      f,
+      "\nRuntime: {:.1} ms total, {:.1} ms mean, {:.1} ms p95, {:.3}x real time",
+      runtime.total_ms, runtime.mean_ms, runtime.p95_ms, runtime.real_time_factor
+    )?;
+
+    if !self.failures.is_empty() {
+      writeln!(f, "\nFailures:")?;
+      for failure in &self.failures {
+        writeln!(
+          f,
+          "  row {} ({}): {}",
+          failure.row,
+          failure.recording.display(),
+          failure.error
+        )?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::PronunciationScorer;
+
+  #[test]
+  fn test_correlations() {
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let linear = [3.0, 5.0, 7.0, 9.0, 11.0];
+    let monotonic = [1.0, 4.0, 9.0, 16.0, 100.0];
+    assert!((pearson(&x, &linear).unwrap() - 1.0).abs() < 1e-6);
+    assert!(pearson(&x, &monotonic).unwrap() < 0.95);
+    assert!((spearman(&x, &monotonic).unwrap() - 1.0).abs() < 1e-6);
+
+    let reversed = [5.0, 4.0, 3.0, 2.0, 1.0];
+    assert!((spearman(&x, &reversed).unwrap() + 1.0).abs() < 1e-6);
+
+    assert_eq!(ranks(&[10.0, 20.0, 20.0, 5.0]), vec![2.0, 3.5, 3.5, 1.0]);
+    assert_eq!(pearson(&x, &[2.0; 5]), None);
+    assert_eq!(pearson(&[1.0], &[1.0]), None);
+  }
+
+  #[test]
+  fn test_listener_agreement_compares_ranks() {
+    // The tutor rated the third attempt the worst and the first well, against the engine
+    let scores = [40.0, 55.0, 90.0, 70.0, 80.0];
+    let ratings = [4.0, 3.0, 1.0, 2.0, 5.0];
+    assert_eq!(
+      listener_agreement(&scores, &ratings),
+      vec![false, true, false, true, true]
+    );
+    assert!(listener_agreement(&[50.0], &[3.0]).is_empty());
+  }
+
+  #[test]
+  fn test_phoneme_agreement() {
+    let mut agreement = PhonemeAgreement::default();
+    // Both right, both hear the same substitution, and the engine misses a deletion
+    agreement.add("k", "k", "k");
+    agreement.add("æ", "ɛ", "ɛ");
+    agreement.add("t", "-", "t");
+    agreement.add("s", "s", "s");
+    agreement.finish();
+
+    assert_eq!(agreement.labelled, 4);
+    assert_eq!(agreement.verdicts, [[2, 0], [1, 1]]);
+    assert_eq!(agreement.agreement, Some(0.75));
+    // Chance agreement is 0.5 * 0.75 + 0.5 * 0.25
+    assert!((agreement.kappa.unwrap() - 0.5).abs() < 1e-6);
+    assert_eq!(agreement.confusion["-"]["t"], 1);
+    assert_eq!(agreement.confusion["ɛ"]["ɛ"], 1);
+  }
+
+  #[test]
+  fn test_evaluate_manifest() {
+    let dir = std::env::temp_dir().join(format!("ipa-eval-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join("clips")).unwrap();
+    let say = |name: &str, ipa: &str| {
+      let audio = FormantSynthesizer::new()
+        .with_pitch(100.0)
+        .synthesize(&Transcription::parse(ipa))
+        .unwrap()
+        .audio;
+      AudioProcessor::save_wav_file(dir.join("clips").join(name), &audio).unwrap();
+    };
+    say("right.wav", "ˈnɑlmi");
+    say("vowel.wav", "ˈnilmi");
+    say("wrong.wav", "ˈbutu");
+
+    let manifest = dir.join("manifest.jsonl");
+    std::fs::write(
+      &manifest,
+      [
+        "# recordings of one word, rated 1-5",
+        r#"{"recording": "clips/right.wav", "ipa": "ˈnɑlmi", "rating": 5, "heard": ["n", "ɑ", "l", "m", "i"]}"#,
+        r#"{"recording": "clips/vowel.wav", "ipa": "ˈnɑlmi", "rating": 3, "heard": ["n", "i", "l", "m", "i"]}"#,
+        "",
+        r#"{"recording": "clips/wrong.wav", "ipa": "ˈnɑlmi", "rating": 1}"#,
+        r#"{"recording": "clips/missing.wav", "ipa": "ˈnɑlmi", "rating": 2}"#,
+      ]
+      .join("\n"),
+    )
+    .unwrap();
+
+    let rows = read_manifest(&manifest).unwrap();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0].recording, dir.join("clips/right.wav"));
+
+    let report = evaluate(&mut PronunciationScorer::new(), &rows);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!((report.rows, report.scored), (4, 3));
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].row, 4);
+    assert_eq!(report.spearman, Some(1.0));
+    assert_eq!(report.phonemes.labelled, 10);
+    assert_eq!(report.phonemes.verdicts.iter().flatten().sum::<usize>(), 10);
+    assert_eq!(report.phonemes.confusion["n"]["n"], 2);
+    assert!(report.runtime.total_ms > 0.0 && report.runtime.real_time_factor > 0.0);
+    assert_eq!(report.confidence_samples.len(), 3);
+    assert!(report.confidence_samples.iter().all(|(_, agreed)| *agreed));
+
+    let text = report.to_string();
+    assert!(text.contains("3 of 4 rows scored"), "{}", text);
+    assert!(text.contains("missing.wav"), "{}", text);
+    serde_json::to_value(&report).unwrap();
+  }
+
+  #[test]
+  fn test_bad_manifest_row_names_its_line() {
+    let path = std::env::temp_dir().join(format!("ipa-eval-{}.jsonl", uuid::Uuid::new_v4()));
+    std::fs::write(
+      &path,
+      "{\"recording\": \"a.wav\", \"ipa\": \"kæt\", \"rating\": 1}\n{}\n",
+    )
+    .unwrap();
+    let error = read_manifest(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(format!("{:#}", error).contains(":2"), "{:#}", error);
+  }
+}
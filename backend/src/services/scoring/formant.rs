@@ -0,0 +1,339 @@
+use rustfft::num_complex::Complex64;
+use serde::{Deserialize, Serialize};
+
+use super::SAMPLE_RATE;
+
+/// Resonances wider than this are treated as spectral shaping rather than formants
+const MAX_BANDWIDTH_HZ: f64 = 500.0;
+
+/// Lowest frequency accepted as F1
+const MIN_FORMANT_HZ: f64 = 150.0;
+
+/// Root-finding iterations for the LPC polynomial
+const MAX_ITERATIONS: usize = 500;
+
+/// Formant analysis parameters
+#[derive(Debug, Clone)]
+pub struct FormantConfig {
+  pub sample_rate: u32,
+  /// LPC order; two poles per expected formant plus two for the glottal and radiation tilt
+  pub lpc_order: usize,
+  pub frame_ms: f32,
+  pub hop_ms: f32,
+  pub pre_emphasis: f32,
+  /// Fraction of the segment, centred on its midpoint, treated as the vowel's steady state
+  pub steady_fraction: f32,
+}
+
+impl Default for FormantConfig {
+  fn default() -> Self {
+    Self {
+      sample_rate: SAMPLE_RATE,
+      lpc_order: 2 + SAMPLE_RATE as usize / 1000,
+      frame_ms: 25.0,
+      hop_ms: 10.0,
+      pre_emphasis: 0.97,
+      steady_fraction: 0.5,
+    }
+  }
+}
+
+/// First three formant frequencies of a vowel, in Hz
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Formants {
+  pub f1: f32,
+  pub f2: f32,
+  pub f3: f32,
+}
+
+/// Formants of one target vowel in the learner's and the reference recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VowelFormants {
+  pub phoneme: String,
+  /// Position of the vowel in the learner's recording
+  pub start_ms: u32,
+  pub end_ms: u32,
+  pub learner: Option<Formants>,
+  pub reference: Option<Formants>,
+}
+
+/// LPC formant estimator for aligned vowel segments
+pub struct FormantTracker {
+  config: FormantConfig,
+}
+
+impl Default for FormantTracker {
+  fn default() -> Self {
+    Self::new(FormantConfig::default())
+  }
+}
+
+impl FormantTracker {
+  pub fn new(config: FormantConfig) -> Self {
+    Self { config }
+  }
+
+  /// Median F1-F3 over the steady middle of a vowel segment, or `None` if fewer than three
+  /// formants could be resolved in most frames
+  pub fn measure(&self, segment: &[f32]) -> Option<Formants> {
+    let frame_len = self.samples(self.config.frame_ms);
+    let hop = self.samples(self.config.hop_ms).max(1);
+    if segment.len() <= self.config.lpc_order * 2 {
+      return None;
+    }
+
+    // Keep the steady state, but never less than one frame
+    let steady = ((segment.len() as f32 * self.config.steady_fraction) as usize)
+      .max(frame_len)
+      .min(segment.len());
+    let start = (segment.len() - steady) / 2;
+    let steady = &segment[start..start + steady];
+
+    let frame_len = frame_len.min(steady.len());
+    let per_frame: Vec<[f64; 3]> = (0..=steady.len() - frame_len)
+      .step_by(hop)
+      .filter_map(|offset| self.frame_formants(&steady[offset..offset + frame_len]))
+      .collect();
+    if per_frame.is_empty() {
+      return None;
+    }
+
+    let median = |k: usize| {
+      let mut values: Vec<f64> = per_frame.iter().map(|f| f[k]).collect();
+      values.sort_by(f64::total_cmp);
+      values[values.len() / 2] as f32
+    };
+    Some(Formants {
+      f1: median(0),
+      f2: median(1),
+      f3: median(2),
+    })
+  }
+
+  /// Measure the part of `audio` between two times
+  pub fn measure_between(&self, audio: &[f32], start_ms: u32, end_ms: u32) -> Option<Formants> {
+    let to_sample =
+      |ms: u32| ((ms as u64 * self.config.sample_rate as u64 / 1000) as usize).min(audio.len());
+    let (start, end) = (to_sample(start_ms), to_sample(end_ms));
+    self.measure(audio.get(start..end.max(start))?)
+  }
+
+  fn frame_formants(&self, frame: &[f32]) -> Option<[f64; 3]> {
+    let len = frame.len();
+    let alpha = self.config.pre_emphasis as f64;
+    let windowed: Vec<f64> = (0..len)
+      .map(|n| {
+        let previous = if n > 0 { frame[n - 1] as f64 } else { 0.0 };
+        let emphasized = frame[n] as f64 - alpha * previous;
+        let hamming =
+          0.54 - 0.46 * (2.0 * std::f64::consts::PI * n as f64 / (len - 1).max(1) as f64).cos();
+        emphasized * hamming
+      })
+      .collect();
+
+    let coefficients = lpc(&windowed, self.config.lpc_order)?;
+    let sample_rate = self.config.sample_rate as f64;
+    let nyquist = sample_rate / 2.0;
+
+    let mut candidates: Vec<f64> = polynomial_roots(&coefficients)
+      .into_iter()
+      .filter(|root| root.im > 0.0)
+      .filter_map(|root| {
+        let frequency = root.arg() * sample_rate / (2.0 * std::f64::consts::PI);
+        let bandwidth = -root.norm().ln() * sample_rate / std::f64::consts::PI;
+        (frequency > MIN_FORMANT_HZ && frequency < nyquist - 50.0 && bandwidth < MAX_BANDWIDTH_HZ)
+          .then_some(frequency)
+      })
+      .collect();
+    candidates.sort_by(f64::total_cmp);
+
+    match candidates[..] {
+      [f1, f2, f3, ..] => Some([f1, f2, f3]),
+      _ => None,
+    }
+  }
+
+  fn samples(&self, ms: f32) -> usize {
+    (ms * self.config.sample_rate as f32 / 1000.0).round() as usize
+  }
+}
+
+/// Prediction polynomial `[1, a1, ..., ap]` by the autocorrelation method (Levinson-Durbin)
+fn lpc(signal: &[f64], order: usize) -> Option<Vec<f64>> {
+  let autocorrelation: Vec<f64> = (0..=order)
+    .map(|lag| {
+      signal
+        .iter()
+        .zip(&signal[lag.min(signal.len())..])
+        .map(|(a, b)| a * b)
+        .sum()
+    })
+    .collect();
+  if autocorrelation[0] <= f64::EPSILON {
+    return None;
+  }
+
+  let mut a = vec![0.0; order + 1];
+  a[0] = 1.0;
+  // A touch of white noise keeps the recursion stable on very clean signals
+  let mut error = autocorrelation[0] * (1.0 + 1e-9);
+
+  for i in 1..=order {
+    let acc: f64 = (1..i).map(|j| a[j] * autocorrelation[i - j]).sum();
+    let reflection = -(autocorrelation[i] + acc) / error;
+    let previous = a.clone();
+    for j in 1..i {
+      a[j] = previous[j] + reflection * previous[i - j];
+    }
+    a[i] = reflection;
+    error *= 1.0 - reflection * reflection;
+    if error <= 0.0 {
+      return None;
+    }
+  }
+
+  Some(a)
+}
+
+/// Roots of `z^p + c1 z^(p-1) + ... + cp` for monic coefficients `[1, c1, ..., cp]`
+/// (Durand-Kerner iteration)
+fn polynomial_roots(coefficients: &[f64]) -> Vec<Complex64> {
+  let degree = coefficients.len() - 1;
+  let evaluate = |z: Complex64| {
+    coefficients
+      .iter()
+      .fold(Complex64::new(0.0, 0.0), |acc, &c| acc * z + c)
+  };
+
+  let seed = Complex64::new(0.4, 0.9);
+  let mut roots: Vec<Complex64> = (0..degree).map(|k| seed.powu(k as u32)).collect();
+
+  for _ in 0..MAX_ITERATIONS {
+    let mut largest_step: f64 = 0.0;
+    for i in 0..degree {
+      let denominator = (0..degree)
+        .filter(|&j| j != i)
+        .fold(Complex64::new(1.0, 0.0), |acc, j| {
+          acc * (roots[i] - roots[j])
+        });
+      if denominator.norm() < f64::EPSILON {
+        continue;
+      }
+      let step = evaluate(roots[i]) / denominator;
+      roots[i] -= step;
+      largest_step = largest_step.max(step.norm());
+    }
+    if largest_step < 1e-12 {
+      break;
+    }
+  }
+
+  roots
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Pulse train at `f0` with a -6 dB/octave glottal and radiation tilt, through a cascade of
+  /// two-pole resonators
+  fn synthetic_vowel(f0: f32, formants: &[(f32, f32)], len: usize) -> Vec<f32> {
+    let sample_rate = SAMPLE_RATE as f64;
+    let period = (sample_rate / f0 as f64) as usize;
+    let mut tilt = 0.0;
+    let mut signal: Vec<f64> = (0..len)
+      .map(|n| {
+        tilt = 0.97 * tilt + if n % period == 0 { 1.0 } else { 0.0 };
+        tilt
+      })
+      .collect();
+
+    for &(frequency, bandwidth) in formants {
+      let r = (-std::f64::consts::PI * bandwidth as f64 / sample_rate).exp();
+      let theta = 2.0 * std::f64::consts::PI * frequency as f64 / sample_rate;
+      let (b1, b2) = (2.0 * r * theta.cos(), -r * r);
+      let (mut y1, mut y2) = (0.0, 0.0);
+      for sample in signal.iter_mut() {
+        let y = *sample + b1 * y1 + b2 * y2;
+        y2 = y1;
+        y1 = y;
+        *sample = y;
+      }
+    }
+
+    let peak = signal.iter().fold(0.0f64, |m, s| m.max(s.abs()));
+    signal.iter().map(|s| (0.5 * s / peak) as f32).collect()
+  }
+
+  fn assert_close(measured: f32, expected: f32, tolerance: f32) {
+    assert!(
+      (measured - expected).abs() <= expected * tolerance,
+      "measured {} expected {}",
+      measured,
+      expected
+    );
+  }
+
+  #[test]
+  fn test_recovers_formants_of_synthetic_vowels() {
+    let tracker = FormantTracker::default();
+    // Hillenbrand et al. (1995) adult male means for /i/, /æ/, /ɑ/, /u/
+    for (f0, [f1, f2, f3]) in [
+      (120.0, [342.0, 2322.0, 3000.0]),
+      (110.0, [588.0, 1952.0, 2601.0]),
+      (130.0, [768.0, 1333.0, 2522.0]),
+      (200.0, [378.0, 997.0, 2343.0]),
+    ] {
+      let vowel = synthetic_vowel(
+        f0,
+        &[(f1, 60.0), (f2, 90.0), (f3, 120.0), (3800.0, 200.0)],
+        3200,
+      );
+      let formants = tracker.measure(&vowel).unwrap();
+      assert_close(formants.f1, f1, 0.08);
+      assert_close(formants.f2, f2, 0.05);
+      assert_close(formants.f3, f3, 0.05);
+    }
+  }
+
+  #[test]
+  fn test_distinguishes_neighbouring_vowels() {
+    // /æ/ against /ɛ/: the learner's vowel should land nearer the one actually produced
+    let tracker = FormantTracker::default();
+    let ae = tracker
+      .measure(&synthetic_vowel(
+        120.0,
+        &[(660.0, 70.0), (1720.0, 90.0), (2410.0, 120.0)],
+        2400,
+      ))
+      .unwrap();
+    let eh = tracker
+      .measure(&synthetic_vowel(
+        120.0,
+        &[(530.0, 70.0), (1840.0, 90.0), (2480.0, 120.0)],
+        2400,
+      ))
+      .unwrap();
+    assert!(ae.f1 > eh.f1 + 80.0);
+    assert!(ae.f2 < eh.f2 - 60.0);
+  }
+
+  #[test]
+  fn test_silence_and_short_segments_have_no_formants() {
+    let tracker = FormantTracker::default();
+    assert!(tracker.measure(&[0.0; 1600]).is_none());
+    assert!(tracker.measure(&[0.1; 20]).is_none());
+  }
+
+  #[test]
+  fn test_lpc_recovers_known_resonator() {
+    // Tilt plus one resonator is an exact three-pole model
+    let vowel = synthetic_vowel(100.0, &[(1000.0, 80.0)], 1600);
+    let windowed: Vec<f64> = vowel.iter().map(|&s| s as f64).collect();
+    let a = lpc(&windowed, 3).unwrap();
+    let roots = polynomial_roots(&a);
+    let upper = roots.iter().find(|r| r.im > 0.0).unwrap();
+    let frequency = upper.arg() * SAMPLE_RATE as f64 / (2.0 * std::f64::consts::PI);
+    assert!((frequency - 1000.0).abs() < 30.0, "{}", frequency);
+  }
+}
@@ -0,0 +1,672 @@
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::Deserialize;
+use tract_onnx::prelude::*;
+
+use super::confidence::{ConfidenceFeatures, ConfidenceModel};
+use super::diagnosis::{self, Diagnosis, Edit, Insertion, SUBSTITUTED_BELOW};
+use super::duration::DurationAnalyzer;
+use super::engine::{EngineId, Scorer, ScoringInput};
+use super::formant::{FormantTracker, VowelFormants};
+use super::mfcc::{MFCCExtractor, MfccConfig};
+use super::stress::StressAnalyzer;
+use super::synth::FormantSynthesizer;
+use super::vad::VoiceActivityDetector;
+use super::{Cmvn, PhonemeScore, PronunciationScore, SAMPLE_RATE};
+use crate::services::{articulation, ipa};
+
+/// Goodness of Pronunciation from an acoustic model's phone posteriors.
+///
+/// - 1.1: substituted, deleted and inserted sounds diagnosed per phoneme
+/// - 1.2: affricates and diphthongs scored as single phonemes
+/// - 1.3: substitutions earn partial credit by articulatory distance
+/// - 1.4: confidence judged on the recording before noise reduction
+/// - 1.5: SNR and clipping taken from the quality check, which measures SNR without DC offset
+/// - 1.6: phonemes outside the model's inventory scored as its nearest phone
+pub const GOP: EngineId = EngineId {
+  name: "gop",
+  version: "1.6",
+};
+
+/// Log-probability cost of changing phone when decoding without the target, which keeps brief
+/// posterior flickers from being reported as sounds
+const SWITCH_PENALTY: f32 = 5.0;
+
+/// Shortest run of frames the free decoding reports as a sound
+const MIN_SEGMENT_FRAMES: usize = 3;
+
+/// Marks dropped when a target phoneme is not in the model's inventory as written
+const LENGTH_MARKS: [char; 2] = ['ː', 'ˑ'];
+
+/// How the model was trained, read from `model.json` next to `model.onnx`
+#[derive(Debug, Clone, Deserialize)]
+pub struct GopModelInfo {
+  /// Phone for each output column, in order
+  pub phones: Vec<String>,
+  /// Whether the model expects delta and delta-delta coefficients after the 13 MFCCs
+  #[serde(default = "default_deltas")]
+  pub deltas: bool,
+  #[serde(default = "default_cmvn")]
+  pub cmvn: Cmvn,
+}
+
+fn default_deltas() -> bool {
+  true
+}
+
+fn default_cmvn() -> Cmvn {
+  Cmvn::Utterance
+}
+
+/// Scores each phoneme by how strongly an acoustic model prefers it over every other phone in
+/// its force-aligned segment (Witt & Young, 2000).
+///
+/// The model maps MFCC frames `[1, T, D]` to per-frame logits or log posteriors `[1, T', P]`
+/// over the phones listed in `model.json`; `T'` may be a subsampling of `T`. Unlike template
+/// matching, no reference recording is needed, so a learner is not marked down for sounding
+/// unlike one particular speaker.
+pub struct GopScorer {
+  model: TypedRunnableModel<TypedModel>,
+  info: GopModelInfo,
+  mfcc_extractor: MFCCExtractor,
+  hop_ms: f32,
+  vad: VoiceActivityDetector,
+  stress: StressAnalyzer,
+  duration: DurationAnalyzer,
+  formants: FormantTracker,
+  confidence: ConfidenceModel,
+}
+
+impl GopScorer {
+  /// Load `model.onnx` and `model.json` from a directory
+  pub fn load(dir: &Path) -> Result<Self> {
+    let info: GopModelInfo = serde_json::from_slice(
+      &std::fs::read(dir.join("model.json"))
+        .with_context(|| format!("Reading {}", dir.join("model.json").display()))?,
+    )
+    .context("Parsing model.json")?;
+    ensure!(!info.phones.is_empty(), "model.json lists no phones");
+
+    let model = tract_onnx::onnx()
+      .model_for_path(dir.join("model.onnx"))
+      .with_context(|| format!("Loading {}", dir.join("model.onnx").display()))?
+      .into_optimized()?
+      .into_runnable()?;
+
+    let config = if info.deltas {
+      MfccConfig::with_deltas()
+    } else {
+      MfccConfig::default()
+    };
+    let hop_ms = config.hop_size as f32 * 1000.0 / config.sample_rate as f32;
+
+    Ok(Self {
+      model,
+      info,
+      mfcc_extractor: MFCCExtractor::with_config(config)?,
+      hop_ms,
+      vad: VoiceActivityDetector::default(),
+      stress: StressAnalyzer::new(SAMPLE_RATE),
+      duration: DurationAnalyzer::default(),
+      formants: FormantTracker::default(),
+      confidence: ConfidenceModel::default(),
+    })
+  }
+
+  /// Use confidence coefficients fitted on listener-labelled scores from this engine
+  pub fn with_confidence_model(mut self, model: ConfidenceModel) -> Self {
+    self.confidence = model;
+    self
+  }
+
+  /// Per-frame log posteriors over the model's phones
+  fn log_posteriors(&self, features: &[Vec<f32>]) -> Result<Vec<Vec<f32>>> {
+    let dim = features.first().map_or(0, Vec::len);
+    let flat: Vec<f32> = features.iter().flatten().copied().collect();
+    let input = tract_ndarray::Array3::from_shape_vec((1, features.len(), dim), flat)?;
+
+    let outputs = self.model.run(tvec!(Tensor::from(input).into()))?;
+    let output = outputs[0].to_array_view::<f32>()?;
+    let shape = output.shape();
+    ensure!(
+      shape.len() == 3 && shape[2] == self.info.phones.len(),
+      "Model output shape {:?} does not match {} phones",
+      shape,
+      self.info.phones.len()
+    );
+
+    // Normalising makes logits and log posteriors interchangeable
+    Ok(
+      (0..shape[1])
+        .map(|t| log_softmax((0..shape[2]).map(|p| output[[0, t, p]]).collect()))
+        .collect(),
+    )
+  }
+
+  /// Column of each target phoneme in the model's output. Phonemes the model was not trained on
+  /// are scored as its nearest phone by articulatory features.
+  fn phone_indices(&self, phonemes: &[String]) -> Result<Vec<usize>> {
+    phonemes
+      .iter()
+      .map(|phoneme| {
+        let bare: String = phoneme
+          .chars()
+          .filter(|c| !LENGTH_MARKS.contains(c))
+          .collect();
+        self
+          .info
+          .phones
+          .iter()
+          .position(|p| p == phoneme)
+          .or_else(|| self.info.phones.iter().position(|p| *p == bare))
+          .or_else(|| self.nearest_phone(phoneme))
+          .with_context(|| {
+            format!(
+              "Phoneme '{}' is not in the model's inventory, nor close to anything in it",
+              phoneme
+            )
+          })
+      })
+      .collect()
+  }
+
+  /// The model's phone closest to `phoneme`; none when nothing shares its class, as for a vowel
+  /// against an inventory of consonants or a symbol outside the IPA charts
+  fn nearest_phone(&self, phoneme: &str) -> Option<usize> {
+    self
+      .info
+      .phones
+      .iter()
+      .map(|phone| articulation::phoneme_distance(phoneme, phone))
+      .enumerate()
+      .filter(|&(_, distance)| distance < 1.0)
+      .min_by(|a, b| a.1.total_cmp(&b.1))
+      .map(|(i, _)| i)
+  }
+}
+
+impl Scorer for GopScorer {
+  fn engine(&self) -> EngineId {
+    GOP
+  }
+
+  fn uses_reference_audio(&self) -> bool {
+    false
+  }
+
+  fn score(&mut self, input: &ScoringInput) -> Result<PronunciationScore> {
+    ensure!(
+      !input.target.phonemes.is_empty(),
+      "The word has no transcription to score against"
+    );
+    let phones = self.phone_indices(&input.target.phonemes)?;
+
+    let (speech_audio, speech) = self.vad.trim(input.audio);
+    let mut features = self.mfcc_extractor.extract_features(speech_audio)?;
+    self.info.cmvn.apply(&mut features);
+    let log_posteriors = self.log_posteriors(&features)?;
+    let Some(spans) = force_align(&log_posteriors, &phones) else {
+      bail!(
+        "{} frames of speech are too few for {} phonemes",
+        log_posteriors.len(),
+        phones.len()
+      );
+    };
+
+    // Output frames may be subsampled relative to the MFCC hop
+    let frame_ms = self.hop_ms * features.len() as f32 / log_posteriors.len().max(1) as f32;
+    let offset_ms = speech.map_or(0, |s| s.start_ms);
+    let to_ms = |frame: usize| offset_ms + (frame as f32 * frame_ms) as u32;
+
+    // What the model hears without the target, lined up against what was expected
+    let recognised = recognise(&log_posteriors);
+    let recognised_phones: Vec<usize> = recognised.iter().map(|(phone, _)| *phone).collect();
+    let edits = diagnosis::edit_script(&phones, &recognised_phones);
+
+    // Frames of added sounds are reported as insertions, not held against their neighbours
+    let mut inserted = vec![false; log_posteriors.len()];
+    for edit in &edits {
+      if let Edit::Insert { recognised: r } = edit {
+        inserted[recognised[*r].1.clone()].fill(true);
+      }
+    }
+
+    let mut per_phoneme: Vec<PhonemeScore> = input
+      .target
+      .phonemes
+      .iter()
+      .zip(&phones)
+      .zip(&spans)
+      .map(|((phoneme, &phone), span)| {
+        let kept: Vec<usize> = span.clone().filter(|&t| !inserted[t]).collect();
+        let kept = if kept.is_empty() {
+          span.clone().collect()
+        } else {
+          kept
+        };
+        let frames: Vec<Vec<f32>> = kept.iter().map(|&t| log_posteriors[t].clone()).collect();
+        let score = 100.0 * goodness(&frames, phone).exp();
+        // A poor phoneme was most likely the phone the model prefers over its segment
+        let diagnosis = if score < SUBSTITUTED_BELOW {
+          let preferred = argmax(&mean_frame(&frames));
+          Diagnosis::Substituted {
+            produced: (preferred != phone).then(|| self.info.phones[preferred].clone()),
+          }
+        } else {
+          Diagnosis::Correct
+        };
+        PhonemeScore {
+          phoneme: phoneme.clone(),
+          start_ms: to_ms(kept[0]),
+          end_ms: to_ms(kept[kept.len() - 1] + 1),
+          score,
+          diagnosis,
+          ..Default::default()
+        }
+      })
+      .collect();
+
+    let mut next_target = 0;
+    for edit in edits {
+      match edit {
+        Edit::Match { target, .. } => next_target = target + 1,
+        Edit::Substitute { target, recognised } => {
+          next_target = target + 1;
+          let phoneme = &mut per_phoneme[target];
+          if phoneme.score < SUBSTITUTED_BELOW {
+            phoneme.diagnosis = Diagnosis::Substituted {
+              produced: Some(self.info.phones[recognised_phones[recognised]].clone()),
+            };
+          }
+        }
+        Edit::Delete { target } => {
+          next_target = target + 1;
+          if per_phoneme[target].score < SUBSTITUTED_BELOW {
+            per_phoneme[target].diagnosis = Diagnosis::Deleted;
+          }
+        }
+        Edit::Insert { recognised: r } => {
+          let (phone, span) = &recognised[r];
+          diagnosis::attach_insertion(
+            &mut per_phoneme,
+            next_target,
+            Insertion {
+              sound: Some(self.info.phones[*phone].clone()),
+              start_ms: to_ms(span.start),
+              end_ms: to_ms(span.end),
+            },
+          );
+        }
+      }
+    }
+
+    diagnosis::give_partial_credit(&mut per_phoneme);
+    let overall_pct = per_phoneme.iter().map(|p| p.score).sum::<f32>() / per_phoneme.len() as f32;
+    let mean_gop = phones
+      .iter()
+      .zip(&spans)
+      .map(|(&phone, span)| goodness(&log_posteriors[span.clone()], phone) * span.len() as f32)
+      .sum::<f32>()
+      / log_posteriors.len() as f32;
+
+    // How often the model's own best guess agrees with the forced alignment
+    let agreement = phones
+      .iter()
+      .zip(&spans)
+      .map(|(&phone, span)| {
+        log_posteriors[span.clone()]
+          .iter()
+          .filter(|frame| argmax(frame) == phone)
+          .count()
+      })
+      .sum::<usize>() as f32
+      / log_posteriors.len() as f32;
+    let confidence_features = ConfidenceFeatures::measure(
+      input.quality,
+      agreement,
+      speech.map_or(0, |s| s.end_ms - s.start_ms),
+      None,
+    );
+
+    let stress = self.stress.analyze(input.audio, &per_phoneme, input.target);
+    // Without reference audio, segment lengths are judged against the synthesizer's timing
+    let duration = FormantSynthesizer::for_dialect(input.dialect)
+      .spans(input.target)
+      .ok()
+      .and_then(|spans| self.duration.analyze(&per_phoneme, &spans, input.target));
+    let vowels = per_phoneme
+      .iter()
+      .filter(|phoneme| ipa::is_vowel(&phoneme.phoneme))
+      .map(|phoneme| VowelFormants {
+        phoneme: phoneme.phoneme.clone(),
+        start_ms: phoneme.start_ms,
+        end_ms: phoneme.end_ms,
+        learner: self
+          .formants
+          .measure_between(input.audio, phoneme.start_ms, phoneme.end_ms),
+        reference: None,
+      })
+      .collect();
+
+    Ok(PronunciationScore {
+      overall_pct: overall_pct.clamp(0.0, 100.0),
+      per_phoneme,
+      alignment_cost: -mean_gop,
+      confidence: self.confidence.predict(&confidence_features),
+      confidence_features,
+      speech,
+      stress,
+      duration,
+      vowels,
+      reference_index: None,
+      engine: GOP,
+    })
+  }
+}
+
+/// Mean log ratio of the target phone's posterior to the best phone's over a segment; 0 when the
+/// model prefers the target in every frame
+fn goodness(frames: &[Vec<f32>], phone: usize) -> f32 {
+  frames
+    .iter()
+    .map(|frame| frame[phone] - frame[argmax(frame)])
+    .sum::<f32>()
+    / frames.len().max(1) as f32
+}
+
+/// Viterbi alignment of `phones`, in order and at least one frame each, maximising the summed log
+/// posterior. `None` when there are fewer frames than phones.
+fn force_align(log_posteriors: &[Vec<f32>], phones: &[usize]) -> Option<Vec<Range<usize>>> {
+  let (frames, n) = (log_posteriors.len(), phones.len());
+  if n == 0 || frames < n {
+    return None;
+  }
+
+  // Best score of frames 0..=t ending in phone k, and whether phone k began at frame t
+  let mut score = vec![vec![f32::NEG_INFINITY; n]; frames];
+  let mut entered = vec![vec![false; n]; frames];
+  score[0][0] = log_posteriors[0][phones[0]];
+  for t in 1..frames {
+    for k in 0..n {
+      let stay = score[t - 1][k];
+      let advance = if k > 0 {
+        score[t - 1][k - 1]
+      } else {
+        f32::NEG_INFINITY
+      };
+      entered[t][k] = advance > stay;
+      score[t][k] = stay.max(advance) + log_posteriors[t][phones[k]];
+    }
+  }
+
+  let mut starts = vec![0; n];
+  let mut k = n - 1;
+  for t in (1..frames).rev() {
+    if k == 0 {
+      break;
+    }
+    if entered[t][k] {
+      starts[k] = t;
+      k -= 1;
+    }
+  }
+
+  Some(
+    (0..n)
+      .map(|k| starts[k]..starts.get(k + 1).copied().unwrap_or(frames))
+      .collect(),
+  )
+}
+
+/// Most likely phone sequence with no target imposed: Viterbi over a loop of every phone, paying
+/// [`SWITCH_PENALTY`] per change. Runs shorter than [`MIN_SEGMENT_FRAMES`] join the previous
+/// sound.
+fn recognise(log_posteriors: &[Vec<f32>]) -> Vec<(usize, Range<usize>)> {
+  let Some(first) = log_posteriors.first() else {
+    return Vec::new();
+  };
+  let phones = first.len();
+
+  let mut score = first.clone();
+  let mut came_from = vec![vec![0; phones]; log_posteriors.len()];
+  for (t, frame) in log_posteriors.iter().enumerate().skip(1) {
+    let best = argmax(&score);
+    let switched = score[best] - SWITCH_PENALTY;
+    let mut next = vec![0.0; phones];
+    for p in 0..phones {
+      let (from, previous) = if score[p] >= switched {
+        (p, score[p])
+      } else {
+        (best, switched)
+      };
+      came_from[t][p] = from;
+      next[p] = previous + frame[p];
+    }
+    score = next;
+  }
+
+  let mut path = vec![argmax(&score); log_posteriors.len()];
+  for t in (1..path.len()).rev() {
+    path[t - 1] = came_from[t][path[t]];
+  }
+
+  let mut segments: Vec<(usize, Range<usize>)> = Vec::new();
+  let mut start = 0;
+  for t in 1..=path.len() {
+    if t < path.len() && path[t] == path[start] {
+      continue;
+    }
+    match segments.last_mut() {
+      Some((phone, span)) if *phone == path[start] || t - start < MIN_SEGMENT_FRAMES => {
+        span.end = t
+      }
+      _ => segments.push((path[start], start..t)),
+    }
+    start = t;
+  }
+  if segments.len() > 1 && segments[0].1.len() < MIN_SEGMENT_FRAMES {
+    let (_, span) = segments.remove(0);
+    segments[0].1.start = span.start;
+  }
+  segments
+}
+
+/// Per-phone mean over frames
+fn mean_frame(frames: &[Vec<f32>]) -> Vec<f32> {
+  let mut mean = vec![0.0; frames.first().map_or(0, Vec::len)];
+  for frame in frames {
+    for (m, x) in mean.iter_mut().zip(frame) {
+      *m += x / frames.len() as f32;
+    }
+  }
+  mean
+}
+
+fn log_softmax(mut logits: Vec<f32>) -> Vec<f32> {
+  let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+  let log_sum = logits.iter().map(|l| (l - max).exp()).sum::<f32>().ln() + max;
+  for l in &mut logits {
+    *l -= log_sum;
+  }
+  logits
+}
+
+fn argmax(values: &[f32]) -> usize {
+  values
+    .iter()
+    .enumerate()
+    .max_by(|a, b| a.1.total_cmp(b.1))
+    .map_or(0, |(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::Dialect;
+  use crate::services::ipa::Transcription;
+  use crate::services::scoring::{signals, QualityGate};
+
+  /// Nearest-prototype model over /u/, /s/ and /ə/ built by `scripts/make_gop_test_model.py`
+  fn test_scorer() -> GopScorer {
+    GopScorer::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/gop")).unwrap()
+  }
+
+  /// Harmonic vowel-like sound whose spectral envelope peaks at `formants`
+  fn vowel(formants: &[f32], len: usize) -> Vec<f32> {
+    let f0 = 130.0;
+    (0..len)
+      .map(|n| {
+        let t = n as f32 / SAMPLE_RATE as f32;
+        (1..(7000.0 / f0) as usize)
+          .map(|k| {
+            let freq = k as f32 * f0;
+            let envelope: f32 = formants
+              .iter()
+              .map(|f| (-((freq - f) / 150.0).powi(2)).exp())
+              .sum();
+            0.05 * (0.05 + envelope) * (2.0 * std::f32::consts::PI * freq * t).sin()
+          })
+          .sum::<f32>()
+      })
+      .collect()
+  }
+
+  /// High-passed white noise, a stand-in for a sibilant
+  fn hiss(len: usize) -> Vec<f32> {
+    let white = signals::noise(len, 0.2, 3);
+    let mut previous = 0.0;
+    white
+      .iter()
+      .map(|&white| {
+        let sample = white - previous;
+        previous = white;
+        sample
+      })
+      .collect()
+  }
+
+  fn score(scorer: &mut GopScorer, audio: &[f32], ipa: &str) -> Result<PronunciationScore> {
+    scorer.score(&ScoringInput {
+      audio,
+      quality: &QualityGate::default().check(audio, None),
+      target: &Transcription::parse(ipa),
+      dialect: &Dialect::GA,
+      references: &[],
+      competitors: &[],
+    })
+  }
+
+  #[test]
+  fn test_correct_phonemes_score_high() {
+    let mut scorer = test_scorer();
+    assert!(!scorer.uses_reference_audio());
+
+    let audio = [vowel(&[300.0, 870.0], 4800), hiss(4800)].concat();
+    // Length marks fall back to the bare phone
+    let score = score(&mut scorer, &audio, "uːs").unwrap();
+    assert_eq!(score.engine, GOP);
+    assert_eq!(score.reference_index, None);
+    assert_eq!(score.per_phoneme.len(), 2);
+    assert!(score.overall_pct > 90.0, "{:?}", score.per_phoneme);
+
+    // The boundary lands near the switch from vowel to hiss at 300 ms
+    let boundary = score.per_phoneme[1].start_ms as i32;
+    assert!((boundary - 300).abs() <= 40, "{}", boundary);
+    assert!(score.confidence_features.path_stability > 0.9);
+  }
+
+  #[test]
+  fn test_substituted_phoneme_scores_low() {
+    let mut scorer = test_scorer();
+    // Schwa where /u/ was expected
+    let audio = [vowel(&[500.0, 1500.0], 4800), hiss(4800)].concat();
+    let score = score(&mut scorer, &audio, "us").unwrap();
+
+    assert!(score.per_phoneme[0].score < 10.0, "{:?}", score.per_phoneme);
+    assert!(score.per_phoneme[1].score > 90.0, "{:?}", score.per_phoneme);
+    assert!(score.overall_pct < 60.0);
+    assert!(score.alignment_cost > 0.0);
+    assert_eq!(
+      score.per_phoneme[0].diagnosis,
+      Diagnosis::Substituted {
+        produced: Some("ə".into())
+      }
+    );
+    assert_eq!(score.per_phoneme[1].diagnosis, Diagnosis::Correct);
+  }
+
+  #[test]
+  fn test_deleted_and_inserted_sounds_are_diagnosed() {
+    let mut scorer = test_scorer();
+    let u = vowel(&[300.0, 870.0], 4800);
+    let schwa = vowel(&[500.0, 1500.0], 2400);
+
+    // Just the hiss
+    let hissed = score(&mut scorer, &hiss(4800), "us").unwrap();
+    assert_eq!(hissed.per_phoneme[0].diagnosis, Diagnosis::Deleted);
+    assert_eq!(hissed.per_phoneme[1].diagnosis, Diagnosis::Correct);
+
+    // A schwa slipped in between, and another after the word
+    let audio = [u.clone(), schwa.clone(), hiss(4800), schwa].concat();
+    let padded = score(&mut scorer, &audio, "us").unwrap();
+    let [u, s] = &padded.per_phoneme[..] else {
+      panic!("{:?}", padded.per_phoneme);
+    };
+    assert_eq!(u.diagnosis, Diagnosis::Correct);
+    assert!(u.inserted_before.is_empty() && u.inserted_after.is_empty());
+    assert_eq!(s.inserted_before.len(), 1, "{:?}", s);
+    assert_eq!(s.inserted_before[0].sound.as_deref(), Some("ə"));
+    assert!((s.inserted_before[0].start_ms as i32 - 300).abs() <= 40);
+    assert!((s.inserted_before[0].end_ms as i32 - 450).abs() <= 40);
+    assert_eq!(s.inserted_after.len(), 1, "{:?}", s);
+  }
+
+  #[test]
+  fn test_recognise_ignores_flickers() {
+    let (a, b) = ((0.9f32).ln(), (0.1f32).ln());
+    let mut frames = vec![vec![a, b]; 5];
+    frames.extend(vec![vec![b, a]; 2]);
+    frames.extend(vec![vec![a, b]; 3]);
+    frames.extend(vec![vec![b, a]; 6]);
+    assert_eq!(recognise(&frames), vec![(0, 0..10), (1, 10..16)]);
+  }
+
+  #[test]
+  fn test_phoneme_outside_inventory_is_scored_as_the_nearest() {
+    let mut scorer = test_scorer();
+    let audio = [vowel(&[300.0, 870.0], 4800), hiss(4800)].concat();
+
+    // The model has no /k/; its nearest phone is the other consonant, /s/
+    let stand_in = score(&mut scorer, &audio, "uk").unwrap();
+    let listed = score(&mut scorer, &audio, "us").unwrap();
+    assert_eq!(stand_in.per_phoneme[1].phoneme, "k");
+    assert_eq!(stand_in.per_phoneme[1].score, listed.per_phoneme[1].score);
+
+    let error = score(&mut scorer, &audio, "").unwrap_err();
+    assert!(error.to_string().contains("no transcription"), "{}", error);
+    assert!(GopScorer::load(Path::new("/nonexistent/gop")).is_err());
+  }
+
+  #[test]
+  fn test_force_align_follows_posteriors() {
+    let (a, b) = ((0.9f32).ln(), (0.1f32).ln());
+    let frames = vec![
+      vec![a, b],
+      vec![a, b],
+      vec![b, a],
+      vec![b, a],
+      vec![b, a],
+      vec![a, b],
+    ];
+    assert_eq!(
+      force_align(&frames, &[0, 1, 0]),
+      Some(vec![0..2, 2..5, 5..6])
+    );
+
+    // Every phone gets at least one frame, even against the evidence
+    assert_eq!(force_align(&frames[..2], &[0, 1]), Some(vec![0..1, 1..2]));
+    assert_eq!(force_align(&frames[..2], &[0, 1, 0]), None);
+  }
+}
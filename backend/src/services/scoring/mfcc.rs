@@ -0,0 +1,631 @@
+use anyhow::{bail, Result};
+use realfft::{RealFftPlanner, RealToComplex};
+use rustfft::num_complex::Complex32;
+use std::sync::Arc;
+
+use super::simd;
+use super::{FRAME_SIZE, HOP_SIZE, MFCC_COEFFS, SAMPLE_RATE};
+
+/// Floor applied to filterbank and frame energies before taking the log
+const LOG_FLOOR: f32 = 1e-10;
+
+/// How the zeroth cepstral coefficient is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyMode {
+  /// Keep C0 as produced by the DCT
+  C0,
+  /// Replace C0 with the log energy of the windowed frame
+  LogEnergy,
+  /// Drop C0 and return coefficients 1..=num_coeffs instead
+  Omit,
+}
+
+/// MFCC extraction parameters
+#[derive(Debug, Clone)]
+pub struct MfccConfig {
+  pub sample_rate: u32,
+  pub frame_size: usize,
+  pub hop_size: usize,
+  /// FFT length; frames shorter than this are zero-padded
+  pub fft_size: usize,
+  pub num_filters: usize,
+  pub num_coeffs: usize,
+  pub min_freq: f32,
+  pub max_freq: f32,
+  pub pre_emphasis: f32,
+  pub energy: EnergyMode,
+  /// Append delta and delta-delta coefficients to every frame
+  pub deltas: bool,
+  /// Number of neighbouring frames on each side used by the delta regression
+  pub delta_window: usize,
+}
+
+impl Default for MfccConfig {
+  fn default() -> Self {
+    Self {
+      sample_rate: SAMPLE_RATE,
+      frame_size: FRAME_SIZE,
+      hop_size: HOP_SIZE,
+      fft_size: FRAME_SIZE,
+      num_filters: 26,
+      num_coeffs: MFCC_COEFFS,
+      min_freq: 0.0,
+      max_freq: SAMPLE_RATE as f32 / 2.0,
+      pre_emphasis: 0.97,
+      energy: EnergyMode::C0,
+      deltas: false,
+      delta_window: 2,
+    }
+  }
+}
+
+impl MfccConfig {
+  /// Configuration used for pronunciation scoring: static, delta and delta-delta coefficients
+  pub fn with_deltas() -> Self {
+    Self {
+      deltas: true,
+      ..Self::default()
+    }
+  }
+
+  /// Number of values in each output feature vector
+  pub fn feature_dim(&self) -> usize {
+    if self.deltas {
+      self.num_coeffs * 3
+    } else {
+      self.num_coeffs
+    }
+  }
+
+  fn validate(&self) -> Result<()> {
+    if self.sample_rate == 0 || self.frame_size == 0 || self.hop_size == 0 {
+      bail!("sample rate, frame size and hop size must be non-zero");
+    }
+    if self.fft_size < self.frame_size {
+      bail!(
+        "FFT size {} is smaller than frame size {}",
+        self.fft_size,
+        self.frame_size
+      );
+    }
+    let nyquist = self.sample_rate as f32 / 2.0;
+    if self.min_freq < 0.0 || self.max_freq > nyquist || self.min_freq >= self.max_freq {
+      bail!(
+        "invalid filterbank range {}..{} Hz for sample rate {}",
+        self.min_freq,
+        self.max_freq,
+        self.sample_rate
+      );
+    }
+    let available = match self.energy {
+      EnergyMode::Omit => self.num_filters.saturating_sub(1),
+      _ => self.num_filters,
+    };
+    if self.num_coeffs == 0 || self.num_coeffs > available {
+      bail!(
+        "cannot take {} coefficients from {} mel filters",
+        self.num_coeffs,
+        self.num_filters
+      );
+    }
+    Ok(())
+  }
+}
+
+/// Convert a frequency in Hz to the mel scale (HTK formula)
+pub fn hz_to_mel(hz: f32) -> f32 {
+  2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+/// Convert a mel value back to Hz (HTK formula)
+pub fn mel_to_hz(mel: f32) -> f32 {
+  700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// A mel filter, stored as its weights over the run of FFT bins it covers
+struct MelFilter {
+  first_bin: usize,
+  weights: Vec<f32>,
+}
+
+impl MelFilter {
+  fn trimmed(dense: Vec<f32>) -> Self {
+    let first_bin = dense.iter().position(|&w| w > 0.0).unwrap_or(0);
+    let end = dense
+      .iter()
+      .rposition(|&w| w > 0.0)
+      .map_or(first_bin, |last| last + 1);
+    Self {
+      first_bin,
+      weights: dense[first_bin..end].to_vec(),
+    }
+  }
+}
+
+/// MFCC feature extraction
+pub struct MFCCExtractor {
+  config: MfccConfig,
+  /// Planned once for the configured FFT size and reused for every frame
+  fft: Arc<dyn RealToComplex<f32>>,
+  window: Vec<f32>,
+  mel_filters: Vec<MelFilter>,
+  /// Orthonormal DCT-II basis, one row per output coefficient
+  dct: Vec<Vec<f32>>,
+  fft_input: Vec<f32>,
+  spectrum: Vec<Complex32>,
+  scratch: Vec<Complex32>,
+}
+
+impl Default for MFCCExtractor {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl MFCCExtractor {
+  pub fn new() -> Self {
+    Self::with_config(MfccConfig::default()).expect("default MFCC config is valid")
+  }
+
+  pub fn with_config(config: MfccConfig) -> Result<Self> {
+    config.validate()?;
+    let fft = RealFftPlanner::<f32>::new().plan_fft_forward(config.fft_size);
+    let mel_filters = Self::create_mel_filters(&config)
+      .into_iter()
+      .map(MelFilter::trimmed)
+      .collect();
+
+    Ok(Self {
+      window: hamming_window(config.frame_size),
+      dct: Self::create_dct(&config),
+      fft_input: fft.make_input_vec(),
+      spectrum: fft.make_output_vec(),
+      scratch: fft.make_scratch_vec(),
+      fft,
+      mel_filters,
+      config,
+    })
+  }
+
+  pub fn config(&self) -> &MfccConfig {
+    &self.config
+  }
+
+  /// Extract MFCC features from audio data
+  pub fn extract_features(&mut self, audio_data: &[f32]) -> Result<Vec<Vec<f32>>> {
+    let mut features = Vec::new();
+
+    // Pre-emphasis filter
+    let pre_emphasized = self.pre_emphasis(audio_data);
+
+    // Frame the audio
+    let frames = self.frame_audio(&pre_emphasized);
+
+    for frame in frames {
+      // Apply Hamming window
+      let windowed = self.apply_hamming_window(&frame);
+
+      // Compute FFT
+      self.compute_fft(&windowed)?;
+
+      // Compute power spectrum
+      let power_spectrum = self.compute_power_spectrum();
+
+      // Apply mel filters
+      let mel_energies = self.apply_mel_filters(&power_spectrum);
+
+      // Compute MFCC
+      let mut mfcc = self.compute_mfcc(&mel_energies);
+
+      if self.config.energy == EnergyMode::LogEnergy {
+        let energy: f32 = windowed.iter().map(|s| s * s).sum();
+        mfcc[0] = energy.max(LOG_FLOOR).ln();
+      }
+
+      features.push(mfcc);
+    }
+
+    if self.config.deltas {
+      features = Self::append_deltas(features, self.config.delta_window);
+    }
+
+    Ok(features)
+  }
+
+  /// Power spectrum of each frame, `fft_size / 2 + 1` bins from 0 Hz to Nyquist, framed and
+  /// windowed exactly as for feature extraction
+  pub fn power_spectrogram(&mut self, audio_data: &[f32]) -> Result<Vec<Vec<f32>>> {
+    let pre_emphasized = self.pre_emphasis(audio_data);
+    self
+      .frame_audio(&pre_emphasized)
+      .into_iter()
+      .map(|frame| {
+        self.compute_fft(&self.apply_hamming_window(&frame))?;
+        Ok(self.compute_power_spectrum())
+      })
+      .collect()
+  }
+
+  fn pre_emphasis(&self, audio: &[f32]) -> Vec<f32> {
+    let alpha = self.config.pre_emphasis;
+    let mut result = Vec::with_capacity(audio.len());
+
+    if audio.is_empty() {
+      return result;
+    }
+
+    result.push(audio[0]);
+    for i in 1..audio.len() {
+      result.push(audio[i] - alpha * audio[i - 1]);
+    }
+
+    result
+  }
+
+  fn frame_audio(&self, audio: &[f32]) -> Vec<Vec<f32>> {
+    let frame_size = self.config.frame_size;
+    let mut frames = Vec::new();
+
+    if audio.is_empty() {
+      return frames;
+    }
+
+    // Clips shorter than one frame still yield a single zero-padded frame
+    if audio.len() < frame_size {
+      let mut frame = audio.to_vec();
+      frame.resize(frame_size, 0.0);
+      frames.push(frame);
+      return frames;
+    }
+
+    for i in (0..=audio.len() - frame_size).step_by(self.config.hop_size) {
+      frames.push(audio[i..i + frame_size].to_vec());
+    }
+
+    frames
+  }
+
+  fn apply_hamming_window(&self, frame: &[f32]) -> Vec<f32> {
+    frame.iter().zip(&self.window).map(|(s, w)| s * w).collect()
+  }
+
+  /// Transform a windowed frame, zero-padded to the FFT size, into `self.spectrum`
+  fn compute_fft(&mut self, windowed: &[f32]) -> Result<()> {
+    let (frame, padding) = self.fft_input.split_at_mut(windowed.len());
+    frame.copy_from_slice(windowed);
+    padding.fill(0.0);
+
+    self
+      .fft
+      .process_with_scratch(&mut self.fft_input, &mut self.spectrum, &mut self.scratch)?;
+
+    Ok(())
+  }
+
+  fn compute_power_spectrum(&self) -> Vec<f32> {
+    self.spectrum.iter().map(|c| c.norm_sqr()).collect()
+  }
+
+  fn apply_mel_filters(&self, power_spectrum: &[f32]) -> Vec<f32> {
+    self
+      .mel_filters
+      .iter()
+      .map(|filter| simd::dot(&filter.weights, &power_spectrum[filter.first_bin..]))
+      .collect()
+  }
+
+  fn compute_mfcc(&self, mel_energies: &[f32]) -> Vec<f32> {
+    // Log mel energies
+    let log_mel: Vec<f32> = mel_energies
+      .iter()
+      .map(|&energy| energy.max(LOG_FLOOR).ln())
+      .collect();
+
+    self
+      .dct
+      .iter()
+      .map(|basis| simd::dot(basis, &log_mel))
+      .collect()
+  }
+
+  /// Orthonormal DCT-II rows for the output coefficients; C0 is skipped when the energy mode
+  /// omits it
+  fn create_dct(config: &MfccConfig) -> Vec<Vec<f32>> {
+    let first = match config.energy {
+      EnergyMode::Omit => 1,
+      _ => 0,
+    };
+    let n = config.num_filters as f32;
+
+    (first..first + config.num_coeffs)
+      .map(|i| {
+        let scale = if i == 0 {
+          (1.0 / n).sqrt()
+        } else {
+          (2.0 / n).sqrt()
+        };
+        (0..config.num_filters)
+          .map(|j| scale * (std::f32::consts::PI * i as f32 * (2 * j + 1) as f32 / (2.0 * n)).cos())
+          .collect()
+      })
+      .collect()
+  }
+
+  /// Triangular filters spaced evenly on the mel scale between `min_freq` and `max_freq`
+  fn create_mel_filters(config: &MfccConfig) -> Vec<Vec<f32>> {
+    let num_bins = config.fft_size / 2 + 1;
+    let bin_hz = config.sample_rate as f32 / config.fft_size as f32;
+    let mel_min = hz_to_mel(config.min_freq);
+    let mel_max = hz_to_mel(config.max_freq);
+
+    let edges: Vec<f32> = (0..config.num_filters + 2)
+      .map(|i| {
+        mel_to_hz(mel_min + (mel_max - mel_min) * i as f32 / (config.num_filters + 1) as f32)
+      })
+      .collect();
+
+    edges
+      .windows(3)
+      .map(|edge| {
+        let (lower, center, upper) = (edge[0], edge[1], edge[2]);
+        (0..num_bins)
+          .map(|bin| {
+            let freq = bin as f32 * bin_hz;
+            if freq > lower && freq <= center {
+              (freq - lower) / (center - lower)
+            } else if freq > center && freq < upper {
+              (upper - freq) / (upper - center)
+            } else {
+              0.0
+            }
+          })
+          .collect()
+      })
+      .collect()
+  }
+
+  /// Append delta and delta-delta coefficients to each frame
+  fn append_deltas(features: Vec<Vec<f32>>, window: usize) -> Vec<Vec<f32>> {
+    let deltas = compute_deltas(&features, window);
+    let delta_deltas = compute_deltas(&deltas, window);
+
+    features
+      .into_iter()
+      .zip(deltas)
+      .zip(delta_deltas)
+      .map(|((mut frame, delta), delta_delta)| {
+        frame.extend(delta);
+        frame.extend(delta_delta);
+        frame
+      })
+      .collect()
+  }
+}
+
+/// Symmetric Hamming window
+fn hamming_window(len: usize) -> Vec<f32> {
+  let denom = (len.max(2) - 1) as f32;
+  (0..len)
+    .map(|i| 0.54 - 0.46 * (2.0 * std::f32::consts::PI * i as f32 / denom).cos())
+    .collect()
+}
+
+/// Regression deltas over `window` frames on each side, replicating the edge frames
+pub fn compute_deltas(features: &[Vec<f32>], window: usize) -> Vec<Vec<f32>> {
+  if features.is_empty() || window == 0 {
+    return features.iter().map(|f| vec![0.0; f.len()]).collect();
+  }
+
+  let last = features.len() - 1;
+  let denom = 2.0 * (1..=window).map(|n| (n * n) as f32).sum::<f32>();
+
+  (0..features.len())
+    .map(|t| {
+      let mut delta = vec![0.0; features[t].len()];
+      for n in 1..=window {
+        let next = &features[(t + n).min(last)];
+        let prev = &features[t.saturating_sub(n)];
+        for (d, (a, b)) in delta.iter_mut().zip(next.iter().zip(prev)) {
+          *d += n as f32 * (a - b);
+        }
+      }
+      delta.iter_mut().for_each(|d| *d /= denom);
+      delta
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Three-tone test signal shared with the reference implementation
+  fn three_tone(len: usize) -> Vec<f32> {
+    (0..len)
+      .map(|n| {
+        let t = n as f64 / SAMPLE_RATE as f64;
+        let tau = 2.0 * std::f64::consts::PI;
+        (0.5 * (tau * 440.0 * t).sin()
+          + 0.25 * (tau * 1500.0 * t).sin()
+          + 0.1 * (tau * 3200.0 * t).sin()) as f32
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_mel_scale_reference_points() {
+    assert!((hz_to_mel(1000.0) - 999.9855).abs() < 1e-2);
+    assert!((hz_to_mel(8000.0) - 2840.023).abs() < 1e-2);
+    assert!((mel_to_hz(1000.0) - 1000.0218).abs() < 1e-2);
+    assert!((mel_to_hz(hz_to_mel(3150.0)) - 3150.0).abs() < 1e-1);
+  }
+
+  #[test]
+  fn test_filterbank_is_mel_spaced() {
+    let config = MfccConfig::default();
+    let filters = MFCCExtractor::create_mel_filters(&config);
+    assert_eq!(filters.len(), 26);
+
+    let bin_hz = config.sample_rate as f32 / config.fft_size as f32;
+    let peaks: Vec<f32> = filters
+      .iter()
+      .map(|f| {
+        let (bin, _) = f.iter().enumerate().fold(
+          (0, 0.0),
+          |best, (i, &w)| if w > best.1 { (i, w) } else { best },
+        );
+        bin as f32 * bin_hz
+      })
+      .collect();
+
+    // Peaks increase monotonically and widen with frequency
+    assert!(peaks.windows(2).all(|p| p[1] > p[0]));
+    assert!(peaks[25] - peaks[24] > 4.0 * (peaks[1] - peaks[0]));
+    // Nothing above Nyquist is covered and the top filter ends near it
+    assert!(peaks[25] < 8000.0 && peaks[25] > 6500.0);
+  }
+
+  #[test]
+  fn test_filterbank_follows_sample_rate() {
+    let config = MfccConfig {
+      sample_rate: 8000,
+      max_freq: 4000.0,
+      ..MfccConfig::default()
+    };
+    let filters = MFCCExtractor::create_mel_filters(&config);
+    let bin_hz = 8000.0 / config.fft_size as f32;
+    let top = filters[25].iter().rposition(|&w| w > 0.0).unwrap();
+    assert!(top as f32 * bin_hz <= 4000.0);
+    assert!(top as f32 * bin_hz > 3500.0);
+  }
+
+  #[test]
+  fn test_mfcc_matches_reference_vectors() {
+    // Computed with an independent double precision implementation of the same pipeline
+    // (pre-emphasis 0.97, symmetric Hamming, 512-point FFT, 26 HTK mel filters, ortho DCT-II)
+    let expected = [
+      [
+        -15.5122, -1.8766, -3.4863, 2.3078, -5.6006, -4.3355, -6.9303, -14.5034, 2.2437, 10.6059,
+        -2.1624, -0.1612, 6.096,
+      ],
+      [
+        -18.1902, -0.4227, -3.1881, 7.986, -2.7494, -4.9684, -8.4973, -15.2422, 2.5928, 11.5726,
+        -1.2971, 0.0061, 5.4445,
+      ],
+    ];
+
+    let mut extractor = MFCCExtractor::new();
+    let features = extractor.extract_features(&three_tone(2048)).unwrap();
+    assert_eq!(features.len(), 7);
+
+    for (frame, reference) in features.iter().zip(expected.iter()) {
+      for (got, want) in frame.iter().zip(reference.iter()) {
+        assert!(
+          (got - want).abs() < 2e-2,
+          "coefficient {} differs from reference {}",
+          got,
+          want
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_deltas_of_linear_ramp() {
+    let features: Vec<Vec<f32>> = (0..10).map(|t| vec![t as f32, 2.0 * t as f32]).collect();
+    let deltas = compute_deltas(&features, 2);
+
+    // Interior frames see the exact slope; edges are damped by replication
+    for frame in &deltas[2..8] {
+      assert!((frame[0] - 1.0).abs() < 1e-6);
+      assert!((frame[1] - 2.0).abs() < 1e-6);
+    }
+    assert!(deltas[0][0] < 1.0 && deltas[9][0] < 1.0);
+
+    let delta_deltas = compute_deltas(&deltas, 2);
+    assert!(delta_deltas[4..6].iter().all(|f| f[0].abs() < 1e-6));
+  }
+
+  #[test]
+  fn test_feature_layout_with_deltas_and_energy() {
+    let config = MfccConfig {
+      energy: EnergyMode::LogEnergy,
+      ..MfccConfig::with_deltas()
+    };
+    let mut extractor = MFCCExtractor::with_config(config).unwrap();
+    let audio = three_tone(4096);
+    let features = extractor.extract_features(&audio).unwrap();
+    assert!(features.iter().all(|f| f.len() == 39));
+
+    // C0 now tracks loudness: halving the amplitude lowers log energy by ln(4)
+    let quiet: Vec<f32> = audio.iter().map(|s| s * 0.5).collect();
+    let quiet_features = extractor.extract_features(&quiet).unwrap();
+    let drop = features[3][0] - quiet_features[3][0];
+    assert!((drop - 4f32.ln()).abs() < 1e-3);
+  }
+
+  #[test]
+  fn test_omitting_c0_is_gain_invariant() {
+    let config = MfccConfig {
+      energy: EnergyMode::Omit,
+      ..MfccConfig::default()
+    };
+    let mut extractor = MFCCExtractor::with_config(config).unwrap();
+    let audio = three_tone(2048);
+    let loud: Vec<f32> = audio.iter().map(|s| s * 4.0).collect();
+
+    let a = extractor.extract_features(&audio).unwrap();
+    let b = extractor.extract_features(&loud).unwrap();
+    for (x, y) in a[2].iter().zip(&b[2]) {
+      assert!((x - y).abs() < 1e-3);
+    }
+  }
+
+  #[test]
+  fn test_power_spectrogram_peaks_at_tone() {
+    let config = MfccConfig {
+      pre_emphasis: 0.0,
+      ..MfccConfig::default()
+    };
+    let mut extractor = MFCCExtractor::with_config(config).unwrap();
+    let tone: Vec<f32> = (0..4096)
+      .map(|n| (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / SAMPLE_RATE as f32).sin())
+      .collect();
+    let spectrogram = extractor.power_spectrogram(&tone).unwrap();
+    assert_eq!(
+      spectrogram.len(),
+      extractor.extract_features(&tone).unwrap().len()
+    );
+    assert!(spectrogram.iter().all(|frame| frame.len() == 257));
+
+    // 1 kHz falls in bin 1000 / (16000 / 512) = 32
+    let peak = spectrogram[3]
+      .iter()
+      .enumerate()
+      .max_by(|a, b| a.1.total_cmp(b.1))
+      .unwrap()
+      .0;
+    assert_eq!(peak, 32);
+  }
+
+  #[test]
+  fn test_invalid_configs_are_rejected() {
+    let too_many_coeffs = MfccConfig {
+      num_coeffs: 40,
+      ..MfccConfig::default()
+    };
+    assert!(MFCCExtractor::with_config(too_many_coeffs).is_err());
+
+    let above_nyquist = MfccConfig {
+      max_freq: 9000.0,
+      ..MfccConfig::default()
+    };
+    assert!(MFCCExtractor::with_config(above_nyquist).is_err());
+
+    let short_fft = MfccConfig {
+      fft_size: 256,
+      ..MfccConfig::default()
+    };
+    assert!(MFCCExtractor::with_config(short_fft).is_err());
+  }
+}
@@ -0,0 +1,958 @@
+#![allow(dead_code)]
+
+use anyhow::{ensure, Result};
+use std::ops::Range;
+
+use crate::services::ipa::{self, Transcription};
+
+pub mod align;
+pub mod audio;
+pub mod batch;
+pub mod cmvn;
+pub mod confidence;
+pub mod decode;
+pub mod denoise;
+pub mod diagnosis;
+pub mod dtw;
+pub mod duration;
+pub mod engine;
+pub mod evaluation;
+pub mod formant;
+pub mod gop;
+pub mod mfcc;
+pub mod opus;
+pub mod pitch;
+pub mod quality;
+pub mod reference;
+pub mod render;
+#[cfg(test)]
+pub(crate) mod signals;
+pub mod simd;
+pub mod stress;
+pub mod synth;
+pub mod vad;
+pub mod vowel_chart;
+
+pub use align::{check_boundaries, ForcedAligner, PhonemeBoundary, PhonemeScore};
+pub use audio::AudioProcessor;
+pub use batch::ScorerPool;
+pub use cmvn::Cmvn;
+pub use confidence::{ConfidenceFeatures, ConfidenceModel};
+pub use denoise::{Denoiser, NoiseReduction};
+pub use diagnosis::{Diagnosis, Insertion};
+pub use dtw::{Alignment, DTWAligner, Distance, DtwConfig, Window};
+pub use duration::{DurationAnalyzer, DurationScore};
+pub use engine::{EngineId, Scorer, ScoringInput, MFCC_DTW};
+pub use formant::{FormantTracker, VowelFormants};
+pub use mfcc::{MFCCExtractor, MfccConfig};
+pub use pitch::PitchTracker;
+pub use quality::{QualityGate, QualityReport};
+pub use reference::{Reference, ReferenceCombination};
+pub use render::{ImageFormat, Renderer, TimeWarp};
+pub use stress::{StressAnalyzer, StressScore};
+pub use synth::FormantSynthesizer;
+pub use vad::{SpeechSegment, VoiceActivityDetector};
+
+/// Audio processing parameters
+const SAMPLE_RATE: u32 = 16000;
+const FRAME_SIZE: usize = 512;
+const HOP_SIZE: usize = 256;
+const MFCC_COEFFS: usize = 13;
+
+/// Pronunciation scoring service
+pub struct PronunciationScorer {
+  mfcc_extractor: MFCCExtractor,
+  cmvn: Cmvn,
+  dtw: DTWAligner,
+  aligner: ForcedAligner,
+  vad: VoiceActivityDetector,
+  stress: StressAnalyzer,
+  duration: DurationAnalyzer,
+  formants: FormantTracker,
+  pitch: PitchTracker,
+  confidence: ConfidenceModel,
+  combination: ReferenceCombination,
+}
+
+impl Default for PronunciationScorer {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl PronunciationScorer {
+  pub fn new() -> Self {
+    Self::with_mfcc_config(MfccConfig::with_deltas()).expect("default MFCC config is valid")
+  }
+
+  pub fn with_mfcc_config(config: MfccConfig) -> Result<Self> {
+    let aligner = ForcedAligner::new(config.hop_size, config.sample_rate);
+    let stress = StressAnalyzer::new(config.sample_rate);
+    let formants = FormantTracker::new(formant::FormantConfig {
+      sample_rate: config.sample_rate,
+      ..Default::default()
+    });
+    let pitch = PitchTracker::new(pitch::PitchConfig {
+      sample_rate: config.sample_rate,
+      ..Default::default()
+    });
+    let vad = VoiceActivityDetector::new(vad::VadConfig {
+      sample_rate: config.sample_rate,
+      ..Default::default()
+    });
+
+    // After trimming both recordings hold just the word, so tempo rarely differs by more than 2x;
+    // pairs that do are aligned without the window
+    let dtw = DTWAligner::new(DtwConfig {
+      window: Window::Itakura { max_slope: 2.0 },
+      ..Default::default()
+    });
+
+    Ok(Self {
+      mfcc_extractor: MFCCExtractor::with_config(config)?,
+      cmvn: Cmvn::Utterance,
+      dtw,
+      aligner,
+      vad,
+      stress,
+      duration: DurationAnalyzer::default(),
+      formants,
+      pitch,
+      confidence: ConfidenceModel::default(),
+      combination: ReferenceCombination::default(),
+    })
+  }
+
+  /// Choose how features are normalised before alignment
+  pub fn with_cmvn(mut self, cmvn: Cmvn) -> Self {
+    self.cmvn = cmvn;
+    self
+  }
+
+  /// Use confidence coefficients fitted on listener-labelled scores
+  pub fn with_confidence_model(mut self, model: ConfidenceModel) -> Self {
+    self.confidence = model;
+    self
+  }
+
+  /// Choose how scores against several reference speakers are merged
+  pub fn with_reference_combination(mut self, combination: ReferenceCombination) -> Self {
+    self.combination = combination;
+    self
+  }
+
+  /// Score pronunciation by comparing user audio with reference.
+  ///
+  /// `target` is the word's transcription; per-phoneme scores are reported in the order of its
+  /// phonemes, and stress placement is scored when it marks primary stress.
+  pub fn score_pronunciation(
+    &mut self,
+    user_audio: &[f32],
+    reference_audio: &[f32],
+    target: &Transcription,
+  ) -> Result<PronunciationScore> {
+    self.score_with_competitors(user_audio, reference_audio, target, &[])
+  }
+
+  /// Score as [`Self::score_pronunciation`], also aligning against reference recordings of the
+  /// word's minimal pairs so confidence reflects whether the attempt sounds like the target
+  /// rather than a neighbour.
+  pub fn score_with_competitors(
+    &mut self,
+    user_audio: &[f32],
+    reference_audio: &[f32],
+    target: &Transcription,
+    competitors: &[&[f32]],
+  ) -> Result<PronunciationScore> {
+    self.score_against_references(
+      user_audio,
+      &[Reference::new(reference_audio)],
+      target,
+      competitors,
+    )
+  }
+
+  /// Score against several reference speakers of the same dialect variant, merged according to
+  /// the scorer's [`ReferenceCombination`]. Phoneme times, stress and vowel formants come from
+  /// the reference with the largest share.
+  pub fn score_against_references(
+    &mut self,
+    user_audio: &[f32],
+    references: &[Reference],
+    target: &Transcription,
+    competitors: &[&[f32]],
+  ) -> Result<PronunciationScore> {
+    let quality = QualityGate::default().check(user_audio, None);
+    self.score_recording(user_audio, &quality, references, target, competitors)
+  }
+
+  /// Score `user_audio`, judging recording conditions from `quality`, the quality check of the
+  /// recording as made before any noise reduction
+  fn score_recording(
+    &mut self,
+    user_audio: &[f32],
+    quality: &QualityReport,
+    references: &[Reference],
+    target: &Transcription,
+    competitors: &[&[f32]],
+  ) -> Result<PronunciationScore> {
+    ensure!(
+      !references.is_empty(),
+      "no reference recordings to score against"
+    );
+
+    // Trim leading and trailing silence so pauses are not matched against each other
+    let (user_speech, speech) = self.vad.trim(user_audio);
+    let user_features = self.features(user_speech)?;
+
+    let mut candidates = Vec::with_capacity(references.len());
+    for reference in references {
+      let (reference_speech, reference_segment) = self.vad.trim(reference.audio);
+      let reference_features = self.features(reference_speech)?;
+
+      // Align sequences using DTW; the normalised cost is the mean frame distance along the path
+      let alignment = self.dtw.align(&user_features, &reference_features);
+
+      // Phoneme spans from the reference's stored boundaries, or by force-aligning the target
+      let stored = reference
+        .boundaries
+        .filter(|boundaries| check_boundaries(boundaries, &target.phonemes).is_ok());
+      let spans = match stored {
+        Some(boundaries) => self.aligner.spans_from_boundaries(
+          boundaries,
+          reference_segment.map_or(0, |s| s.start_ms),
+          reference_features.len(),
+        ),
+        None => self.aligner.segment(&reference_features, &target.phonemes),
+      };
+      let per_phoneme = self.aligner.score_spans(
+        &user_features,
+        &reference_features,
+        &alignment.path,
+        &spans,
+        &target.phonemes,
+      );
+      candidates.push(ReferenceAlignment {
+        speech: reference_speech,
+        features: reference_features,
+        alignment,
+        spans,
+        per_phoneme,
+      });
+    }
+
+    // Merge speakers, so one idiosyncratic reference cannot sink an acceptable attempt
+    let scores: Vec<f32> = candidates
+      .iter()
+      .map(|c| align::distance_to_pct(c.alignment.normalized_cost))
+      .collect();
+    let speaker_weights: Vec<f32> = references.iter().map(|r| r.weight).collect();
+    let weights = self.combination.weights(&scores, &speaker_weights);
+    let reference_index = weights
+      .iter()
+      .enumerate()
+      .max_by(|a, b| a.1.total_cmp(b.1))
+      .map_or(0, |(i, _)| i);
+    let percentage_score: f32 = scores.iter().zip(&weights).map(|(s, w)| s * w).sum();
+
+    let mut per_phoneme = candidates[reference_index].per_phoneme.clone();
+    for (p, phoneme) in per_phoneme.iter_mut().enumerate() {
+      phoneme.score = candidates
+        .iter()
+        .zip(&weights)
+        .map(|(c, w)| w * c.per_phoneme.get(p).map_or(phoneme.score, |s| s.score))
+        .sum();
+    }
+    let ReferenceAlignment {
+      speech: reference_speech,
+      features: reference_features,
+      alignment,
+      spans: reference_spans,
+      ..
+    } = candidates.swap_remove(reference_index);
+
+    // Deleted, substituted and inserted sounds, from how the path crosses each phoneme
+    self.aligner.diagnose(
+      &mut per_phoneme,
+      &user_features,
+      &reference_features,
+      &alignment.path,
+      &reference_spans,
+    );
+
+    // Report phoneme times relative to the original recording
+    if let Some(speech) = speech {
+      for phoneme in &mut per_phoneme {
+        phoneme.start_ms += speech.start_ms;
+        phoneme.end_ms += speech.start_ms;
+        let insertions = phoneme
+          .inserted_before
+          .iter_mut()
+          .chain(phoneme.inserted_after.iter_mut());
+        for insertion in insertions {
+          insertion.start_ms += speech.start_ms;
+          insertion.end_ms += speech.start_ms;
+        }
+      }
+    }
+
+    // Compare the syllable the learner stressed with the one the transcription marks
+    let stress = self.stress.analyze(user_audio, &per_phoneme, target);
+
+    // Segment lengths against the reference, for vowel length contrasts and speaking rate
+    let frame_ms = self.aligner.frame_ms();
+    let reference_ms: Vec<Range<u32>> = reference_spans
+      .iter()
+      .map(|span| (span.start as f32 * frame_ms) as u32..(span.end as f32 * frame_ms) as u32)
+      .collect();
+    let duration = self.duration.analyze(&per_phoneme, &reference_ms, target);
+
+    // Formants of each vowel, for plotting the learner against the reference speaker
+    let vowels = per_phoneme
+      .iter()
+      .zip(&reference_ms)
+      .filter(|(phoneme, _)| ipa::is_vowel(&phoneme.phoneme))
+      .map(|(phoneme, span)| VowelFormants {
+        phoneme: phoneme.phoneme.clone(),
+        start_ms: phoneme.start_ms,
+        end_ms: phoneme.end_ms,
+        learner: self
+          .formants
+          .measure_between(user_audio, phoneme.start_ms, phoneme.end_ms),
+        reference: self
+          .formants
+          .measure_between(reference_speech, span.start, span.end),
+      })
+      .collect::<Vec<_>>();
+
+    // Name wrong and added vowels; only voiced insertions can be vowels
+    let has_insertions = per_phoneme
+      .iter()
+      .any(|p| !p.inserted_before.is_empty() || !p.inserted_after.is_empty());
+    let contour = has_insertions.then(|| self.pitch.track(user_audio));
+    diagnosis::name_vowels(&mut per_phoneme, &vowels, |insertion| {
+      contour
+        .as_ref()?
+        .median_between(insertion.start_ms, insertion.end_ms)?;
+      self
+        .formants
+        .measure_between(user_audio, insertion.start_ms, insertion.end_ms)
+    });
+    diagnosis::give_partial_credit(&mut per_phoneme);
+
+    // How much the score can be trusted, from evidence other than the score itself
+    let competitor_cost = competitors
+      .iter()
+      .map(|audio| self.normalized_cost_against(&user_features, audio))
+      .collect::<Result<Vec<f32>>>()?
+      .into_iter()
+      .reduce(f32::min);
+    let competitor_margin = competitor_cost.map(|competitor| {
+      let total = competitor + alignment.normalized_cost;
+      if total > f32::EPSILON {
+        (competitor - alignment.normalized_cost) / total
+      } else {
+        0.0
+      }
+    });
+    let confidence_features = ConfidenceFeatures::measure(
+      quality,
+      confidence::path_stability(&alignment.path),
+      speech.map_or(0, |s| s.end_ms - s.start_ms),
+      competitor_margin,
+    );
+
+    Ok(PronunciationScore {
+      overall_pct: percentage_score.clamp(0.0, 100.0),
+      per_phoneme,
+      alignment_cost: alignment.cost,
+      confidence: self.confidence.predict(&confidence_features),
+      confidence_features,
+      speech,
+      stress,
+      duration,
+      vowels,
+      reference_index: Some(reference_index),
+      engine: MFCC_DTW,
+    })
+  }
+
+  /// Phoneme boundaries in a reference recording, found by the same trimming and segmentation
+  /// scoring falls back on, so they can be stored with the recording
+  pub fn segment_reference(
+    &mut self,
+    reference_audio: &[f32],
+    target: &Transcription,
+  ) -> Result<Vec<PhonemeBoundary>> {
+    let (speech, segment) = self.vad.trim(reference_audio);
+    let features = self.features(speech)?;
+    let spans = self.aligner.segment(&features, &target.phonemes);
+    Ok(self.aligner.boundaries_from_spans(
+      &spans,
+      segment.map_or(0, |s| s.start_ms),
+      &target.phonemes,
+    ))
+  }
+
+  /// Map the learner's timeline onto a reference recording's through the same trimmed DTW
+  /// alignment scoring uses
+  pub fn time_warp(&mut self, user_audio: &[f32], reference_audio: &[f32]) -> Result<TimeWarp> {
+    let (user_speech, user_segment) = self.vad.trim(user_audio);
+    let (reference_speech, reference_segment) = self.vad.trim(reference_audio);
+    let user_features = self.features(user_speech)?;
+    let reference_features = self.features(reference_speech)?;
+    let alignment = self.dtw.align(&user_features, &reference_features);
+    let offset = |segment: Option<SpeechSegment>| segment.map_or(0.0, |s| s.start_ms as f32);
+    Ok(TimeWarp::from_path(
+      &alignment.path,
+      self.aligner.frame_ms(),
+      offset(user_segment),
+      offset(reference_segment),
+    ))
+  }
+
+  /// MFCCs normalised so the microphone and level do not dominate the distance
+  fn features(&mut self, speech: &[f32]) -> Result<Vec<Vec<f32>>> {
+    let mut features = self.mfcc_extractor.extract_features(speech)?;
+    self.cmvn.apply(&mut features);
+    Ok(features)
+  }
+
+  /// Mean frame distance between the learner's features and another recording
+  fn normalized_cost_against(&mut self, user_features: &[Vec<f32>], audio: &[f32]) -> Result<f32> {
+    let (speech, _) = self.vad.trim(audio);
+    let features = self.features(speech)?;
+    Ok(self.dtw.align(user_features, &features).normalized_cost)
+  }
+}
+
+impl Scorer for PronunciationScorer {
+  fn engine(&self) -> EngineId {
+    MFCC_DTW
+  }
+
+  fn score(&mut self, input: &ScoringInput) -> Result<PronunciationScore> {
+    self.score_recording(
+      input.audio,
+      input.quality,
+      input.references,
+      input.target,
+      input.competitors,
+    )
+  }
+}
+
+/// The learner's recording aligned against one reference speaker
+struct ReferenceAlignment<'a> {
+  speech: &'a [f32],
+  features: Vec<Vec<f32>>,
+  alignment: Alignment,
+  spans: Vec<Range<usize>>,
+  per_phoneme: Vec<PhonemeScore>,
+}
+
+/// Pronunciation scoring result
+#[derive(Debug, Clone)]
+pub struct PronunciationScore {
+  pub overall_pct: f32,
+  pub per_phoneme: Vec<PhonemeScore>,
+  pub alignment_cost: f32,
+  /// Calibrated probability, 0-1, that the score matches a listener's judgement
+  pub confidence: f32,
+  pub confidence_features: ConfidenceFeatures,
+  /// Where speech was detected in the learner's recording
+  pub speech: Option<SpeechSegment>,
+  /// Lexical stress placement, for polysyllables with marked stress
+  pub stress: Option<StressScore>,
+  /// Phoneme and syllable lengths and speaking rate against the reference, or the synthesizer's
+  /// timing for engines without reference audio
+  pub duration: Option<DurationScore>,
+  /// Measured formants of each target vowel
+  pub vowels: Vec<VowelFormants>,
+  /// Reference with the largest share of the score; phoneme times and reference formants come
+  /// from it. `None` for engines that score without reference audio
+  pub reference_index: Option<usize>,
+  pub engine: EngineId,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::Dialect;
+  use confidence::LOW_CONFIDENCE;
+
+  #[test]
+  fn test_mfcc_extraction() {
+    let mut extractor = MFCCExtractor::new();
+    let audio_data = vec![0.0; 1000]; // Silent audio
+    let features = extractor.extract_features(&audio_data).unwrap();
+    assert!(!features.is_empty());
+    assert_eq!(features[0].len(), MFCC_COEFFS);
+  }
+
+  #[test]
+  fn test_dtw_alignment() {
+    let seq1 = vec![vec![1.0, 2.0], vec![3.0, 4.0]];
+    let seq2 = vec![vec![1.1, 2.1], vec![3.1, 4.1]];
+    let alignment = DTWAligner::default().align(&seq1, &seq2);
+    assert!(alignment.normalized_cost < 1.0); // Should be small for similar sequences
+  }
+
+  #[test]
+  fn test_dtw_path_covers_both_sequences() {
+    let seq1 = vec![vec![0.0], vec![1.0], vec![1.0], vec![2.0]];
+    let seq2 = vec![vec![0.0], vec![1.0], vec![2.0]];
+    let alignment = DTWAligner::default().align(&seq1, &seq2);
+    assert_eq!(alignment.cost, 0.0);
+    assert_eq!(alignment.path, vec![(0, 0), (1, 1), (2, 1), (3, 2)]);
+  }
+
+  #[test]
+  fn test_pronunciation_scoring() {
+    let mut scorer = PronunciationScorer::new();
+    let user_audio = vec![0.1; 1000];
+    let reference_audio = vec![0.1; 1000];
+
+    let score = scorer
+      .score_pronunciation(&user_audio, &reference_audio, &Transcription::default())
+      .unwrap();
+    assert!(score.overall_pct >= 0.0 && score.overall_pct <= 100.0);
+    assert!(score.confidence >= 0.0 && score.confidence <= 1.0);
+  }
+
+  #[test]
+  fn test_per_phoneme_scores_use_target_symbols() {
+    let mut scorer = PronunciationScorer::new();
+    let audio: Vec<f32> = [tone(300.0, 3200), tone(1200.0, 4800), tone(700.0, 3200)].concat();
+    let target = Transcription::parse("ˈmiːn");
+
+    let score = scorer.score_pronunciation(&audio, &audio, &target).unwrap();
+    let keys: Vec<&str> = score
+      .per_phoneme
+      .iter()
+      .map(|p| p.phoneme.as_str())
+      .collect();
+    assert_eq!(keys, vec!["m", "iː", "n"]);
+    assert!(score
+      .per_phoneme
+      .windows(2)
+      .all(|w| w[0].end_ms <= w[1].start_ms + 16));
+    assert!(score.per_phoneme.iter().all(|p| p.score > 95.0));
+  }
+
+  #[test]
+  fn test_leading_silence_is_trimmed_before_scoring() {
+    let mut scorer = PronunciationScorer::new();
+    let word: Vec<f32> = [tone(300.0, 3200), tone(1200.0, 4800), tone(700.0, 3200)].concat();
+    let reference = [hiss(1600), word.clone(), hiss(1600)].concat();
+    let hesitant = [hiss(12000), word, hiss(1600)].concat();
+    let target = Transcription::parse("miːn");
+
+    let score = scorer
+      .score_pronunciation(&hesitant, &reference, &target)
+      .unwrap();
+    let speech = score.speech.unwrap();
+    assert!((speech.start_ms as i32 - 750).abs() <= 40, "{:?}", speech);
+    assert!(score.per_phoneme[0].start_ms >= speech.start_ms);
+    assert!(score.overall_pct > 95.0);
+  }
+
+  #[test]
+  fn test_three_times_slower_or_faster_is_still_scored() {
+    let mut scorer = PronunciationScorer::new();
+    let word = |len: usize| -> Vec<f32> {
+      [
+        vowel(120.0, &[700.0, 1100.0], len),
+        vowel(120.0, &[300.0, 2300.0], len),
+        vowel(120.0, &[300.0, 800.0], len),
+      ]
+      .concat()
+    };
+    let target = Transcription::parse("ˈɑiu");
+    let reference = word(3200);
+
+    // Well beyond the window's 2x, which once left vowels deleted and the word scored near 0
+
+    for learner in [word(9600), word(1100)] {
+      let score = scorer
+        .score_pronunciation(&learner, &reference, &target)
+        .unwrap();
+      assert!(
+        score
+          .per_phoneme
+          .iter()
+          .all(|p| p.diagnosis == Diagnosis::Correct),
+        "{:?}",
+        score.per_phoneme
+      );
+      assert!(score.overall_pct > 50.0, "{}", score.overall_pct);
+    }
+  }
+
+  /// Pure tone at `freq`, standing in for one sound
+  fn tone(freq: f32, len: usize) -> Vec<f32> {
+    (0..len)
+      .map(|n| 0.3 * (2.0 * std::f32::consts::PI * freq * n as f32 / SAMPLE_RATE as f32).sin())
+      .collect()
+  }
+
+  /// Faint broadband noise, as between words
+  fn hiss(len: usize) -> Vec<f32> {
+    (0..len)
+      .map(|n| 0.001 * ((n * 7919 % 101) as f32 / 50.0 - 1.0))
+      .collect()
+  }
+
+  /// Harmonic vowel-like sound whose spectral envelope peaks at `formants`
+  fn vowel(f0: f32, formants: &[f32], len: usize) -> Vec<f32> {
+    (0..len)
+      .map(|n| {
+        let t = n as f32 / SAMPLE_RATE as f32;
+        (1..(7000.0 / f0) as usize)
+          .map(|k| {
+            let freq = k as f32 * f0;
+            let envelope: f32 = formants
+              .iter()
+              .map(|f| (-((freq - f) / 150.0).powi(2)).exp())
+              .sum();
+            0.05 * (0.05 + envelope) * (2.0 * std::f32::consts::PI * freq * t).sin()
+          })
+          .sum::<f32>()
+      })
+      .collect()
+  }
+
+  /// Simulated microphones: level changes and first-order EQ
+  fn channels(clip: &[f32]) -> Vec<Vec<f32>> {
+    let gain = |g: f32| clip.iter().map(|s| s * g).collect::<Vec<f32>>();
+    let bright: Vec<f32> = (0..clip.len())
+      .map(|n| clip[n] - 0.8 * if n > 0 { clip[n - 1] } else { 0.0 })
+      .collect();
+    let mut muffled = Vec::with_capacity(clip.len());
+    let mut state = 0.0;
+    for &s in clip {
+      state = 0.6 * state + 0.4 * s;
+      muffled.push(state);
+    }
+    vec![clip.to_vec(), gain(0.25), gain(3.0), bright, muffled]
+  }
+
+  fn score_variance(scorer: &mut PronunciationScorer, reference: &[f32]) -> (f32, f32) {
+    let scores: Vec<f32> = channels(reference)
+      .iter()
+      .map(|user| {
+        scorer
+          .score_pronunciation(user, reference, &Transcription::default())
+          .unwrap()
+          .overall_pct
+      })
+      .collect();
+    let mean = scores.iter().sum::<f32>() / scores.len() as f32;
+    let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / scores.len() as f32;
+    (mean, variance)
+  }
+
+  #[test]
+  fn test_cmvn_reduces_score_variance_across_channels() {
+    let clip: Vec<f32> = [
+      vowel(140.0, &[700.0, 1200.0], 4800),
+      vowel(130.0, &[300.0, 2300.0], 4800),
+      vowel(120.0, &[500.0, 900.0], 4800),
+    ]
+    .concat();
+
+    let mut raw = PronunciationScorer::new().with_cmvn(Cmvn::Off);
+    let mut normalized = PronunciationScorer::new();
+    let mut sliding = PronunciationScorer::new().with_cmvn(Cmvn::Sliding { frames: 100 });
+
+    let (raw_mean, raw_variance) = score_variance(&mut raw, &clip);
+    let (mean, variance) = score_variance(&mut normalized, &clip);
+    let (_, sliding_variance) = score_variance(&mut sliding, &clip);
+
+    assert!(
+      variance < raw_variance / 10.0,
+      "{} vs {}",
+      variance,
+      raw_variance
+    );
+    assert!(sliding_variance < raw_variance / 10.0);
+    assert!(mean > raw_mean && mean > 80.0, "{} vs {}", mean, raw_mean);
+  }
+
+  #[test]
+  fn test_vowel_formants_are_reported_for_learner_and_reference() {
+    let mut scorer = PronunciationScorer::new();
+    let audio: Vec<f32> = [
+      vowel(120.0, &[250.0], 2400),
+      vowel(120.0, &[700.0, 1200.0, 2600.0], 4800),
+    ]
+    .concat();
+
+    let score = scorer
+      .score_pronunciation(&audio, &audio, &Transcription::parse("ˈmɑ"))
+      .unwrap();
+    assert_eq!(score.vowels.len(), 1);
+    let vowel = &score.vowels[0];
+    assert_eq!(vowel.phoneme, "ɑ");
+
+    for formants in [vowel.learner.unwrap(), vowel.reference.unwrap()] {
+      assert!((formants.f1 - 700.0).abs() < 100.0, "{:?}", formants);
+      assert!((formants.f2 - 1200.0).abs() < 120.0, "{:?}", formants);
+    }
+  }
+
+  #[test]
+  fn test_confidence_follows_competitor_margin() {
+    let mut scorer = PronunciationScorer::new();
+    let word = |vowel_formants: &[f32]| -> Vec<f32> {
+      [
+        vec![0.0; 3200],
+        vowel(120.0, &[250.0], 2400),
+        vowel(120.0, vowel_formants, 4800),
+        vec![0.0; 3200],
+      ]
+      .concat()
+    };
+    // "bit" against its minimal pair "bat"
+    let (bit, bat) = (word(&[430.0, 2000.0]), word(&[660.0, 1720.0]));
+
+    let clear = scorer
+      .score_with_competitors(&bit, &bit, &Transcription::parse("bɪt"), &[bat.as_slice()])
+      .unwrap();
+    let confused = scorer
+      .score_with_competitors(&bat, &bit, &Transcription::parse("bɪt"), &[bat.as_slice()])
+      .unwrap();
+    assert!(clear.confidence_features.competitor_margin.unwrap() > 0.5);
+    assert!(confused.confidence_features.competitor_margin.unwrap() < 0.0);
+    assert!(
+      clear.confidence > LOW_CONFIDENCE,
+      "{:?}",
+      clear.confidence_features
+    );
+    assert!(confused.confidence < clear.confidence);
+
+    // A fragment too short to judge
+    let blip = [
+      vec![0.0; 3200],
+      vowel(120.0, &[430.0, 2000.0], 800),
+      vec![0.0; 3200],
+    ]
+    .concat();
+    let short = scorer
+      .score_pronunciation(&blip, &bit, &Transcription::parse("bɪt"))
+      .unwrap();
+    assert!(short.confidence < clear.confidence);
+  }
+
+  #[test]
+  fn test_confidence_judges_the_recording_before_noise_reduction() {
+    let mut scorer = PronunciationScorer::new();
+    let clean = [
+      vec![0.0; 3200],
+      vowel(120.0, &[430.0, 2000.0], 4800),
+      vec![0.0; 3200],
+    ]
+    .concat();
+    // Hiss that noise reduction would have removed
+    let noisy: Vec<f32> = clean
+      .iter()
+      .zip(signals::noise(clean.len(), 0.05, 1))
+      .map(|(s, n)| s + n)
+      .collect();
+
+    let target = Transcription::parse("ɪ");
+    let score = |scorer: &mut PronunciationScorer, recorded: &[f32]| {
+      Scorer::score(
+        scorer,
+        &ScoringInput {
+          audio: &clean,
+          quality: &QualityGate::default().check(recorded, None),
+          target: &target,
+          dialect: &Dialect::GA,
+          references: &[Reference::new(&clean)],
+          competitors: &[],
+        },
+      )
+      .unwrap()
+    };
+    let as_recorded = score(&mut scorer, &noisy);
+    assert_eq!(
+      as_recorded.confidence_features.snr_db,
+      QualityGate::default().check(&noisy, None).snr_db
+    );
+    assert!(as_recorded.confidence < score(&mut scorer, &clean).confidence);
+  }
+
+  #[test]
+  fn test_idiosyncratic_reference_speaker_is_outvoted() {
+    let word = |f0: f32, vowel_formants: &[f32]| -> Vec<f32> {
+      [vowel(f0, &[250.0], 2400), vowel(f0, vowel_formants, 4800)].concat()
+    };
+    let learner = word(120.0, &[700.0, 1200.0]);
+    let typical = [word(110.0, &[710.0, 1190.0]), word(200.0, &[690.0, 1230.0])];
+    // A speaker whose vowel is far fronted
+    let unusual = word(130.0, &[600.0, 1900.0]);
+    let target = Transcription::parse("ˈmɑ");
+
+    let mut scorer = PronunciationScorer::new();
+    let alone = scorer
+      .score_pronunciation(&learner, &unusual, &target)
+      .unwrap();
+    let references = [
+      Reference::new(&unusual),
+      Reference::new(&typical[0]),
+      Reference::new(&typical[1]),
+    ];
+
+    for combination in [ReferenceCombination::Best, ReferenceCombination::default()] {
+      let mut scorer = PronunciationScorer::new().with_reference_combination(combination);
+      let combined = scorer
+        .score_against_references(&learner, &references, &target, &[])
+        .unwrap();
+      assert_ne!(combined.reference_index, Some(0), "{:?}", combination);
+      assert!(
+        combined.overall_pct > alone.overall_pct + 10.0,
+        "{:?}: {} vs {}",
+        combination,
+        combined.overall_pct,
+        alone.overall_pct
+      );
+      assert_eq!(combined.per_phoneme.len(), 2);
+    }
+
+    assert!(scorer
+      .score_against_references(&learner, &[], &target, &[])
+      .is_err());
+  }
+
+  #[test]
+  fn test_phonemes_are_diagnosed() {
+    let mut scorer = PronunciationScorer::new();
+    let diagnose = |scorer: &mut PronunciationScorer, target: &str, said: &str| {
+      let target = Transcription::parse(target);
+      let reference = FormantSynthesizer::new().synthesize(&target).unwrap().audio;
+      let learner = FormantSynthesizer::new()
+        .with_pitch(100.0)
+        .synthesize(&Transcription::parse(said))
+        .unwrap()
+        .audio;
+      scorer
+        .score_pronunciation(&learner, &reference, &target)
+        .unwrap()
+        .per_phoneme
+    };
+
+    let correct = diagnose(&mut scorer, "ˈglæd", "ˈglæd");
+    assert!(
+      correct
+        .iter()
+        .all(|p| p.diagnosis == Diagnosis::Correct && p.inserted_before.is_empty()),
+      "{:?}",
+      correct
+    );
+
+    // The wrong vowel is named from its formants
+    let substituted = diagnose(&mut scorer, "ˈnɑlmi", "ˈnilmi");
+    assert_eq!(
+      substituted[1].diagnosis,
+      Diagnosis::Substituted {
+        produced: Some("i".into())
+      }
+    );
+
+    // An epenthetic vowel breaking up the cluster
+    let epenthesis = diagnose(&mut scorer, "ˈglæd", "gəˈlæd");
+    let [insertion] = &epenthesis[1].inserted_before[..] else {
+      panic!("{:?}", epenthesis);
+    };
+    assert!(ipa::is_vowel(insertion.sound.as_deref().unwrap()));
+    // The synthesized schwa runs from 160 to 250 ms
+    assert!(
+      (insertion.start_ms as i32 - 160).abs() <= 40,
+      "{:?}",
+      insertion
+    );
+    assert!(
+      (insertion.end_ms as i32 - 250).abs() <= 40,
+      "{:?}",
+      insertion
+    );
+  }
+
+  #[test]
+  fn test_vowel_length_is_compared_with_the_reference() {
+    let mut scorer = PronunciationScorer::new();
+    let target = Transcription::parse("ˈnɑːm");
+    let reference = FormantSynthesizer::new().synthesize(&target).unwrap().audio;
+    let mut duration = |said: &str| {
+      let learner = FormantSynthesizer::new()
+        .with_pitch(100.0)
+        .synthesize(&Transcription::parse(said))
+        .unwrap()
+        .audio;
+      scorer
+        .score_pronunciation(&learner, &reference, &target)
+        .unwrap()
+        .duration
+        .unwrap()
+    };
+
+    let held = duration("ˈnɑːm");
+    assert!((held.rate.tempo - 1.0).abs() < 0.1, "{:?}", held.rate);
+    assert!(held.missing_contrasts.is_empty(), "{:?}", held);
+
+    // Said with a short vowel, the word loses its length contrast
+    let short = duration("ˈnɑm");
+    let [contrast] = &short.missing_contrasts[..] else {
+      panic!("{:?}", short);
+    };
+    assert_eq!(
+      (contrast.phoneme_index, contrast.heard_as.as_str()),
+      (1, "ʌ")
+    );
+  }
+
+  #[test]
+  fn test_stored_reference_boundaries_replace_segmentation() {
+    let mut scorer = PronunciationScorer::new();
+    let target = Transcription::parse("ˈlɑmə");
+    let reference = FormantSynthesizer::new().synthesize(&target).unwrap().audio;
+    let learner = FormantSynthesizer::new()
+      .with_pitch(100.0)
+      .synthesize(&target)
+      .unwrap()
+      .audio;
+
+    // Boundaries found once and stored give the same score as segmenting on every job
+    let boundaries = scorer.segment_reference(&reference, &target).unwrap();
+    let other = scorer
+      .segment_reference(&reference, &Transcription::parse("ˈlɑm"))
+      .unwrap();
+    assert!(check_boundaries(&boundaries, &target.phonemes).is_ok());
+    let segmented = scorer
+      .score_pronunciation(&learner, &reference, &target)
+      .unwrap();
+    let mut score_with = |boundaries: &[PhonemeBoundary]| {
+      scorer
+        .score_against_references(
+          &learner,
+          &[Reference::new(&reference).with_boundaries(boundaries)],
+          &target,
+          &[],
+        )
+        .unwrap()
+    };
+    let stored = score_with(&boundaries);
+    assert_eq!(stored.per_phoneme, segmented.per_phoneme);
+
+    // An editor moving the /m/-/ə/ boundary moves it in the learner's recording too
+    let mut edited = boundaries.clone();
+    edited[2].end_ms += 48;
+    edited[3].start_ms += 48;
+    let moved = score_with(&edited);
+    assert!(moved.per_phoneme[3].start_ms > stored.per_phoneme[3].start_ms);
+
+    // Boundaries for another transcription are ignored
+    assert_eq!(score_with(&other).per_phoneme, segmented.per_phoneme);
+  }
+}
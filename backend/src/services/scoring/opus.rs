@@ -0,0 +1,143 @@
+use symphonia::core::audio::{
+  AsAudioBufferRef, AudioBuffer, AudioBufferRef, Channels, Signal, SignalSpec,
+};
+use symphonia::core::codecs::{
+  CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult, CODEC_TYPE_OPUS,
+};
+use symphonia::core::errors::{decode_error, unsupported_error, Result};
+use symphonia::core::formats::Packet;
+use symphonia::core::support_codec;
+
+/// Opus always decodes at 48 kHz, whatever rate the encoder was fed
+const SAMPLE_RATE: u32 = 48000;
+
+/// Longest Opus packet: 120 ms at 48 kHz
+const MAX_FRAMES: usize = 5760;
+
+/// Opus decoder for the Matroska and Ogg demuxers, built on a pure-Rust port of libopus.
+///
+/// Only mono and stereo streams (channel mapping family 0) are decoded, which is all that
+/// `MediaRecorder` produces. The encoder's pre-skip, read from the `OpusHead` the demuxers pass as
+/// extra data, is dropped from the start of the stream.
+pub struct OpusDecoder {
+  state: *mut unsafe_libopus::OpusDecoder,
+  params: CodecParameters,
+  channels: usize,
+  pre_skip: usize,
+  remaining_skip: usize,
+  interleaved: Vec<f32>,
+  buffer: AudioBuffer<f32>,
+}
+
+// SAFETY: the decoder state is owned exclusively by this value and only touched through `&mut self`
+unsafe impl Send for OpusDecoder {}
+unsafe impl Sync for OpusDecoder {}
+
+impl Drop for OpusDecoder {
+  fn drop(&mut self) {
+    // SAFETY: `state` came from `opus_decoder_create` and is destroyed exactly once
+    unsafe { unsafe_libopus::opus_decoder_destroy(self.state) }
+  }
+}
+
+/// Channel count and pre-skip from an `OpusHead` identification header
+fn parse_head(head: &[u8]) -> Result<(usize, usize)> {
+  if head.len() < 19 || &head[..8] != b"OpusHead" {
+    return decode_error("opus: malformed identification header");
+  }
+  if head[18] != 0 {
+    return unsupported_error("opus: multichannel mapping families are not supported");
+  }
+  let pre_skip = u16::from_le_bytes([head[10], head[11]]) as usize;
+  Ok((head[9] as usize, pre_skip))
+}
+
+impl Decoder for OpusDecoder {
+  fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+    let (channels, pre_skip) = match &params.extra_data {
+      Some(head) => parse_head(head)?,
+      None => (params.channels.map_or(1, |channels| channels.count()), 0),
+    };
+    let layout = match channels {
+      1 => Channels::FRONT_LEFT,
+      2 => Channels::FRONT_LEFT | Channels::FRONT_RIGHT,
+      _ => return unsupported_error("opus: only mono and stereo streams are supported"),
+    };
+
+    let mut error = 0;
+    // SAFETY: the rate and channel count are ones libopus accepts, and `error` outlives the call
+    let state = unsafe {
+      unsafe_libopus::opus_decoder_create(SAMPLE_RATE as i32, channels as i32, &mut error)
+    };
+    if state.is_null() || error != unsafe_libopus::OPUS_OK {
+      return decode_error("opus: failed to create decoder");
+    }
+
+    Ok(Self {
+      state,
+      params: params.clone(),
+      channels,
+      pre_skip,
+      remaining_skip: pre_skip,
+      interleaved: vec![0.0; MAX_FRAMES * channels],
+      buffer: AudioBuffer::new(MAX_FRAMES as u64, SignalSpec::new(SAMPLE_RATE, layout)),
+    })
+  }
+
+  fn supported_codecs() -> &'static [CodecDescriptor] {
+    &[support_codec!(CODEC_TYPE_OPUS, "opus", "Opus")]
+  }
+
+  fn reset(&mut self) {
+    // SAFETY: re-initialising a live decoder with the parameters it was created with
+    unsafe {
+      unsafe_libopus::opus_decoder_init(self.state, SAMPLE_RATE as i32, self.channels as i32);
+    }
+    self.remaining_skip = self.pre_skip;
+  }
+
+  fn codec_params(&self) -> &CodecParameters {
+    &self.params
+  }
+
+  fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+    // SAFETY: `interleaved` holds `MAX_FRAMES` frames for every channel, as the call is told
+    let frames = unsafe {
+      unsafe_libopus::opus_decode_float(
+        self.state,
+        packet.buf().as_ptr(),
+        packet.buf().len() as i32,
+        self.interleaved.as_mut_ptr(),
+        MAX_FRAMES as i32,
+        0,
+      )
+    };
+    if frames < 0 {
+      return decode_error("opus: invalid packet");
+    }
+
+    let skip = self.remaining_skip.min(frames as usize);
+    self.remaining_skip -= skip;
+    let kept = &self.interleaved[skip * self.channels..frames as usize * self.channels];
+
+    self.buffer.clear();
+    self
+      .buffer
+      .render_reserved(Some(kept.len() / self.channels));
+    for channel in 0..self.channels {
+      let plane = self.buffer.chan_mut(channel);
+      for (sample, frame) in plane.iter_mut().zip(kept.chunks_exact(self.channels)) {
+        *sample = frame[channel];
+      }
+    }
+    Ok(self.buffer.as_audio_buffer_ref())
+  }
+
+  fn finalize(&mut self) -> FinalizeResult {
+    FinalizeResult::default()
+  }
+
+  fn last_decoded(&self) -> AudioBufferRef<'_> {
+    self.buffer.as_audio_buffer_ref()
+  }
+}
@@ -0,0 +1,198 @@
+use super::{HOP_SIZE, SAMPLE_RATE};
+
+/// F0 tracking parameters
+#[derive(Debug, Clone)]
+pub struct PitchConfig {
+  pub sample_rate: u32,
+  pub hop_size: usize,
+  pub min_f0: f32,
+  pub max_f0: f32,
+  /// YIN aperiodicity threshold; frames whose best dip stays above it are unvoiced
+  pub threshold: f32,
+  /// Frames quieter than this (dBFS) are treated as unvoiced without searching
+  pub silence_db: f32,
+}
+
+impl Default for PitchConfig {
+  fn default() -> Self {
+    Self {
+      sample_rate: SAMPLE_RATE,
+      hop_size: HOP_SIZE,
+      min_f0: 60.0,
+      max_f0: 500.0,
+      threshold: 0.15,
+      silence_db: -50.0,
+    }
+  }
+}
+
+/// Fundamental frequency every `frame_ms`, starting at 0 ms; `None` for unvoiced frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct PitchContour {
+  pub frame_ms: f32,
+  pub f0: Vec<Option<f32>>,
+}
+
+impl PitchContour {
+  /// Median F0 of the voiced frames within `start_ms..end_ms`
+  pub fn median_between(&self, start_ms: u32, end_ms: u32) -> Option<f32> {
+    let mut voiced: Vec<f32> = self
+      .f0
+      .iter()
+      .enumerate()
+      .filter(|(i, _)| {
+        let time = *i as f32 * self.frame_ms;
+        time >= start_ms as f32 && time < end_ms as f32
+      })
+      .filter_map(|(_, f0)| *f0)
+      .collect();
+    if voiced.is_empty() {
+      return None;
+    }
+    voiced.sort_by(f32::total_cmp);
+    Some(voiced[voiced.len() / 2])
+  }
+}
+
+/// YIN fundamental frequency estimator (de Cheveigné & Kawahara, 2002)
+pub struct PitchTracker {
+  config: PitchConfig,
+}
+
+impl Default for PitchTracker {
+  fn default() -> Self {
+    Self::new(PitchConfig::default())
+  }
+}
+
+impl PitchTracker {
+  pub fn new(config: PitchConfig) -> Self {
+    Self { config }
+  }
+
+  pub fn track(&self, audio: &[f32]) -> PitchContour {
+    let sample_rate = self.config.sample_rate as f32;
+    let min_lag = (sample_rate / self.config.max_f0).floor().max(2.0) as usize;
+    let max_lag = (sample_rate / self.config.min_f0).ceil() as usize;
+    // The integration window must span the longest period searched
+    let window = max_lag;
+    let hop = self.config.hop_size.max(1);
+
+    // Each analysis span is centred on its frame time; frames too close to the edges are unvoiced
+    let span = window + max_lag;
+    let frames = audio.len().div_ceil(hop);
+    let f0 = (0..frames)
+      .map(|frame| {
+        let start = (frame * hop).checked_sub(span / 2)?;
+        let analysis = audio.get(start..start + span)?;
+        self.estimate(analysis, window, min_lag, max_lag)
+      })
+      .collect();
+
+    PitchContour {
+      frame_ms: hop as f32 * 1000.0 / sample_rate,
+      f0,
+    }
+  }
+
+  fn estimate(&self, frame: &[f32], window: usize, min_lag: usize, max_lag: usize) -> Option<f32> {
+    let power = frame[..window].iter().map(|s| s * s).sum::<f32>() / window as f32;
+    if 10.0 * power.max(1e-12).log10() < self.config.silence_db {
+      return None;
+    }
+
+    // Difference function and its cumulative mean normalisation
+    let difference: Vec<f32> = (0..=max_lag)
+      .map(|lag| {
+        frame[..window]
+          .iter()
+          .zip(&frame[lag..lag + window])
+          .map(|(a, b)| (a - b).powi(2))
+          .sum()
+      })
+      .collect();
+    let mut normalized = vec![1.0; max_lag + 1];
+    let mut running = 0.0;
+    for lag in 1..=max_lag {
+      running += difference[lag];
+      normalized[lag] = if running > 0.0 {
+        difference[lag] * lag as f32 / running
+      } else {
+        1.0
+      };
+    }
+
+    // First dip below the threshold, followed down to its local minimum
+    let mut lag = (min_lag..max_lag).find(|&lag| normalized[lag] < self.config.threshold)?;
+    while lag + 1 < max_lag && normalized[lag + 1] < normalized[lag] {
+      lag += 1;
+    }
+
+    // Parabolic interpolation around the minimum
+    let (prev, here, next) = (normalized[lag - 1], normalized[lag], normalized[lag + 1]);
+    let curvature = prev - 2.0 * here + next;
+    let offset = if curvature.abs() > f32::EPSILON {
+      (0.5 * (prev - next) / curvature).clamp(-0.5, 0.5)
+    } else {
+      0.0
+    };
+
+    Some(self.config.sample_rate as f32 / (lag as f32 + offset))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::signals;
+
+  fn harmonic(f0: f32, len: usize) -> Vec<f32> {
+    (0..len)
+      .map(|n| {
+        let t = n as f32 / SAMPLE_RATE as f32;
+        (1..=5)
+          .map(|k| 0.3 / k as f32 * (2.0 * std::f32::consts::PI * f0 * k as f32 * t).sin())
+          .sum()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_tracks_harmonic_f0() {
+    let tracker = PitchTracker::default();
+    for f0 in [90.0, 150.0, 220.0, 380.0] {
+      let contour = tracker.track(&harmonic(f0, 8000));
+      let estimate = contour.median_between(0, 500).unwrap();
+      assert!(
+        (estimate - f0).abs() < 1.0,
+        "{} Hz tracked as {}",
+        f0,
+        estimate
+      );
+    }
+  }
+
+  #[test]
+  fn test_noise_and_silence_are_unvoiced() {
+    let noise = signals::noise(8000, 0.3, 9);
+    let tracker = PitchTracker::default();
+
+    let voiced = tracker
+      .track(&noise)
+      .f0
+      .iter()
+      .filter(|f| f.is_some())
+      .count();
+    assert!(voiced < 3, "{} noise frames voiced", voiced);
+    assert!(tracker.track(&[0.0; 8000]).f0.iter().all(Option::is_none));
+  }
+
+  #[test]
+  fn test_contour_follows_a_rise() {
+    let audio = [harmonic(120.0, 4000), harmonic(200.0, 4000)].concat();
+    let contour = PitchTracker::default().track(&audio);
+    let low = contour.median_between(0, 200).unwrap();
+    let high = contour.median_between(300, 450).unwrap();
+    assert!((low - 120.0).abs() < 2.0 && (high - 200.0).abs() < 2.0);
+  }
+}
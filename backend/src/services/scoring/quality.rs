@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+
+use super::vad::VoiceActivityDetector;
+use super::SAMPLE_RATE;
+
+/// Samples at or above this magnitude are counted as clipped
+const CLIP_LEVEL: f32 = 0.99;
+
+/// Frame length for the signal-to-noise estimate
+const SNR_FRAME_MS: u32 = 20;
+
+/// SNR estimates are clamped to this range; digital silence would otherwise dominate
+pub(crate) const MAX_SNR_DB: f32 = 60.0;
+
+/// Limits a recording must meet before it is scored
+#[derive(Debug, Clone)]
+pub struct QualityConfig {
+  /// Largest tolerated fraction of samples at full scale
+  pub max_clipped_fraction: f32,
+  /// Quietest tolerated speech level, in dB relative to full scale
+  pub min_level_db: f32,
+  pub min_snr_db: f32,
+  /// Largest tolerated mean sample value
+  pub max_dc_offset: f32,
+  /// Speech shorter than this is rejected whatever the word
+  pub min_speech_ms: u32,
+  /// Tolerated speech length as a multiple of the word's expected duration
+  pub min_duration_ratio: f32,
+  pub max_duration_ratio: f32,
+}
+
+impl Default for QualityConfig {
+  fn default() -> Self {
+    Self {
+      max_clipped_fraction: 0.005,
+      min_level_db: -40.0,
+      min_snr_db: 15.0,
+      max_dc_offset: 0.02,
+      min_speech_ms: 150,
+      min_duration_ratio: 0.4,
+      max_duration_ratio: 5.0,
+    }
+  }
+}
+
+/// Something about a recording that would make its score meaningless
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum QualityIssue {
+  NoSpeech,
+  Clipping {
+    fraction: f32,
+  },
+  TooQuiet {
+    level_db: f32,
+  },
+  Noisy {
+    snr_db: f32,
+  },
+  DcOffset {
+    offset: f32,
+  },
+  TooShort {
+    speech_ms: u32,
+    expected_ms: Option<u32>,
+  },
+  TooLong {
+    speech_ms: u32,
+    expected_ms: u32,
+  },
+}
+
+impl QualityIssue {
+  /// What the learner can do about it
+  pub fn advice(&self) -> &'static str {
+    match self {
+      Self::NoSpeech => "We couldn't hear any speech. Check that the right microphone is selected.",
+      Self::Clipping { .. } => {
+        "The recording is distorted. Move back from the microphone a little."
+      }
+      Self::TooQuiet { .. } => "The recording is too quiet. Move closer or speak up.",
+      Self::Noisy { .. } => "There is too much background noise. Try somewhere quieter.",
+      Self::DcOffset { .. } => {
+        "The microphone signal looks faulty. Try reconnecting it or using another one."
+      }
+      Self::TooShort { .. } => "The recording cut off too soon. Say the whole word.",
+      Self::TooLong { .. } => "The recording is much longer than the word. Say just the word once.",
+    }
+  }
+}
+
+/// An issue with its advice, as shown to the learner
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryReason {
+  #[serde(flatten)]
+  pub issue: QualityIssue,
+  pub message: &'static str,
+}
+
+/// Measurements of one recording and the issues they reveal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityReport {
+  pub dc_offset: f32,
+  pub clipped_fraction: f32,
+  /// RMS level of the detected speech in dBFS
+  pub level_db: Option<f32>,
+  pub snr_db: f32,
+  pub speech_ms: Option<u32>,
+  pub expected_ms: Option<u32>,
+  pub issues: Vec<QualityIssue>,
+}
+
+impl QualityReport {
+  pub fn passed(&self) -> bool {
+    self.issues.is_empty()
+  }
+
+  pub fn retry_reasons(&self) -> Vec<RetryReason> {
+    self
+      .issues
+      .iter()
+      .map(|&issue| RetryReason {
+        issue,
+        message: issue.advice(),
+      })
+      .collect()
+  }
+}
+
+/// Screens recordings for problems that would make a pronunciation score misleading
+#[derive(Default)]
+pub struct QualityGate {
+  config: QualityConfig,
+  vad: VoiceActivityDetector,
+}
+
+impl QualityGate {
+  pub fn new(config: QualityConfig) -> Self {
+    Self {
+      config,
+      vad: VoiceActivityDetector::default(),
+    }
+  }
+
+  /// Check a 16 kHz mono recording; `expected_ms` is how long the target word usually takes
+  pub fn check(&self, audio: &[f32], expected_ms: Option<u32>) -> QualityReport {
+    let config = &self.config;
+    let mut issues = Vec::new();
+
+    let dc_offset = audio.iter().sum::<f32>() / audio.len().max(1) as f32;
+    if dc_offset.abs() > config.max_dc_offset {
+      issues.push(QualityIssue::DcOffset { offset: dc_offset });
+    }
+    // The remaining checks look past the offset, so a faulty microphone is reported once
+    let centred: Vec<f32> = audio.iter().map(|s| s - dc_offset).collect();
+
+    let clipped_fraction = clipped_fraction(audio);
+    if clipped_fraction > config.max_clipped_fraction {
+      issues.push(QualityIssue::Clipping {
+        fraction: clipped_fraction,
+      });
+    }
+
+    let snr_db = snr_db(&centred);
+    let speech = self.vad.detect(&centred);
+    let level_db = speech.map(|s| rms_db(&centred[s.start_sample..s.end_sample]));
+
+    match (speech, level_db) {
+      (Some(speech), Some(level_db)) => {
+        if level_db < config.min_level_db {
+          issues.push(QualityIssue::TooQuiet { level_db });
+        }
+        if snr_db < config.min_snr_db {
+          issues.push(QualityIssue::Noisy { snr_db });
+        }
+
+        let speech_ms = speech.end_ms - speech.start_ms;
+        let expected = expected_ms.map(|ms| ms as f32);
+        if speech_ms < config.min_speech_ms
+          || expected.is_some_and(|e| (speech_ms as f32) < e * config.min_duration_ratio)
+        {
+          issues.push(QualityIssue::TooShort {
+            speech_ms,
+            expected_ms,
+          });
+        }
+        if let Some(expected_ms) = expected_ms {
+          if speech_ms as f32 > expected_ms as f32 * config.max_duration_ratio {
+            issues.push(QualityIssue::TooLong {
+              speech_ms,
+              expected_ms,
+            });
+          }
+        }
+      }
+      // Speech buried in loud noise is not detected at all; say which problem to fix
+      _ if rms_db(&centred) >= config.min_level_db && snr_db < config.min_snr_db => {
+        issues.push(QualityIssue::Noisy { snr_db })
+      }
+      _ => issues.push(QualityIssue::NoSpeech),
+    }
+
+    QualityReport {
+      dc_offset,
+      clipped_fraction,
+      level_db,
+      snr_db,
+      speech_ms: speech.map(|s| s.end_ms - s.start_ms),
+      expected_ms,
+      issues,
+    }
+  }
+}
+
+fn rms_db(audio: &[f32]) -> f32 {
+  let power = audio.iter().map(|x| x * x).sum::<f32>() / audio.len().max(1) as f32;
+  10.0 * power.max(1e-12).log10()
+}
+
+/// Difference between the loud (95th percentile) and quiet (10th percentile) frame energies
+fn snr_db(audio: &[f32]) -> f32 {
+  let frame_len = (SAMPLE_RATE * SNR_FRAME_MS / 1000) as usize;
+  let mut energies: Vec<f32> = audio
+    .chunks(frame_len)
+    .filter(|frame| frame.len() == frame_len)
+    .map(|frame| {
+      let power = frame.iter().map(|s| s * s).sum::<f32>() / frame_len as f32;
+      10.0 * power.max(1e-12).log10()
+    })
+    .collect();
+  if energies.is_empty() {
+    return 0.0;
+  }
+  energies.sort_by(f32::total_cmp);
+  let percentile = |p: f32| energies[((energies.len() - 1) as f32 * p) as usize];
+  (percentile(0.95) - percentile(0.1)).clamp(0.0, MAX_SNR_DB)
+}
+
+fn clipped_fraction(audio: &[f32]) -> f32 {
+  if audio.is_empty() {
+    return 0.0;
+  }
+  audio.iter().filter(|s| s.abs() >= CLIP_LEVEL).count() as f32 / audio.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::ipa::Transcription;
+  use crate::services::scoring::signals::noise;
+  use crate::services::scoring::FormantSynthesizer;
+
+  /// A clean recording of "banana" with silence either side
+  fn word() -> (Vec<f32>, u32) {
+    let target = Transcription::parse("bəˈnænə");
+    let synth = FormantSynthesizer::new();
+    let audio = synth.synthesize(&target).unwrap().audio;
+    (audio, synth.duration_ms(&target).unwrap())
+  }
+
+  fn reasons(report: &QualityReport) -> Vec<&'static str> {
+    report
+      .issues
+      .iter()
+      .map(|issue| match issue {
+        QualityIssue::NoSpeech => "no_speech",
+        QualityIssue::Clipping { .. } => "clipping",
+        QualityIssue::TooQuiet { .. } => "too_quiet",
+        QualityIssue::Noisy { .. } => "noisy",
+        QualityIssue::DcOffset { .. } => "dc_offset",
+        QualityIssue::TooShort { .. } => "too_short",
+        QualityIssue::TooLong { .. } => "too_long",
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_clean_recording_passes() {
+    let gate = QualityGate::default();
+    let (audio, expected_ms) = word();
+    let report = gate.check(&audio, Some(expected_ms));
+    assert!(report.passed(), "{:?}", report);
+    assert!(report.speech_ms.unwrap() >= expected_ms);
+  }
+
+  #[test]
+  fn test_each_problem_is_reported() {
+    let gate = QualityGate::default();
+    let (audio, expected_ms) = word();
+    let check = |audio: &[f32]| reasons(&gate.check(audio, Some(expected_ms)));
+
+    assert_eq!(check(&vec![0.0; 16000]), vec!["no_speech"]);
+    assert_eq!(check(&noise(16000, 0.1, 7)), vec!["noisy"]);
+
+    let clipped: Vec<f32> = audio.iter().map(|s| (s * 4.0).clamp(-1.0, 1.0)).collect();
+    assert_eq!(check(&clipped), vec!["clipping"]);
+
+    let quiet: Vec<f32> = audio.iter().map(|s| s * 0.002).collect();
+    assert_eq!(check(&quiet), vec!["too_quiet"]);
+
+    let noisy: Vec<f32> = audio
+      .iter()
+      .zip(noise(audio.len(), 0.1, 7))
+      .map(|(s, n)| s + n)
+      .collect();
+    assert_eq!(check(&noisy), vec!["noisy"]);
+
+    let offset: Vec<f32> = audio.iter().map(|s| s + 0.1).collect();
+    assert_eq!(check(&offset), vec!["dc_offset"]);
+
+    // The first syllable alone, and the word said six times over
+    let cut = &audio[..audio.len() / 3];
+    assert_eq!(check(cut), vec!["too_short"]);
+    let repeated = audio.repeat(6);
+    assert_eq!(check(&repeated), vec!["too_long"]);
+  }
+
+  #[test]
+  fn test_retry_reasons_carry_advice() {
+    let report = QualityGate::default().check(&[0.0; 8000], None);
+    let json = serde_json::to_value(report.retry_reasons()).unwrap();
+    assert_eq!(json[0]["reason"], "no_speech");
+    assert!(json[0]["message"].as_str().unwrap().contains("microphone"));
+
+    let too_quiet = serde_json::to_value(QualityIssue::TooQuiet { level_db: -50.0 }).unwrap();
+    assert_eq!(
+      too_quiet,
+      serde_json::json!({"reason": "too_quiet", "level_db": -50.0})
+    );
+  }
+
+  #[test]
+  fn test_audio_quality_measures() {
+    let tone: Vec<f32> = (0..8000)
+      .map(|n| 0.3 * (2.0 * std::f32::consts::PI * 220.0 * n as f32 / SAMPLE_RATE as f32).sin())
+      .collect();
+    let quiet: Vec<f32> = [vec![0.0; 4000], tone.clone(), vec![0.0; 4000]]
+      .concat()
+      .iter()
+      .zip(noise(16000, 0.001, 5))
+      .map(|(s, n)| s + n)
+      .collect();
+    let noisy: Vec<f32> = [vec![0.0; 4000], tone.clone(), vec![0.0; 4000]]
+      .concat()
+      .iter()
+      .zip(noise(16000, 0.2, 6))
+      .map(|(s, n)| s + n)
+      .collect();
+
+    let (clean_snr, noisy_snr) = (snr_db(&quiet), snr_db(&noisy));
+    assert!(
+      clean_snr > 40.0 && noisy_snr < 10.0,
+      "{} {}",
+      clean_snr,
+      noisy_snr
+    );
+    assert_eq!(clipped_fraction(&quiet), 0.0);
+
+    let clipped: Vec<f32> = tone.iter().map(|s| (s * 5.0).clamp(-1.0, 1.0)).collect();
+    assert!(clipped_fraction(&clipped) > 0.5);
+  }
+}
@@ -0,0 +1,105 @@
+use super::align::PhonemeBoundary;
+
+/// One speaker's recording of the target word
+#[derive(Debug, Clone, Copy)]
+pub struct Reference<'a> {
+  pub audio: &'a [f32],
+  /// Relative trust in this speaker; 1.0 unless a recording is known to be atypical
+  pub weight: f32,
+  /// Stored phoneme boundaries, used instead of segmenting the recording when they follow the
+  /// target's phonemes
+  pub boundaries: Option<&'a [PhonemeBoundary]>,
+}
+
+impl<'a> Reference<'a> {
+  pub fn new(audio: &'a [f32]) -> Self {
+    Self {
+      audio,
+      weight: 1.0,
+      boundaries: None,
+    }
+  }
+
+  pub fn with_boundaries(mut self, boundaries: &'a [PhonemeBoundary]) -> Self {
+    self.boundaries = Some(boundaries);
+    self
+  }
+}
+
+/// How scores against several reference speakers are merged into one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReferenceCombination {
+  /// Score against whichever speaker the learner sounds most like
+  Best,
+  /// Average over speakers, each weighted by its speaker weight and by
+  /// `exp((score - best score) / temperature)`, so speakers far from the learner fade out
+  Weighted { temperature: f32 },
+}
+
+impl Default for ReferenceCombination {
+  fn default() -> Self {
+    // A speaker 5 points further from the learner counts e^-1 as much as the closest one
+    Self::Weighted { temperature: 5.0 }
+  }
+}
+
+impl ReferenceCombination {
+  /// Share of each reference in the combined score, summing to 1. `scores` are 0-100 percentages
+  /// against each reference, `speaker_weights` the references' own weights.
+  pub fn weights(self, scores: &[f32], speaker_weights: &[f32]) -> Vec<f32> {
+    let best = scores
+      .iter()
+      .enumerate()
+      .max_by(|a, b| a.1.total_cmp(b.1))
+      .map(|(i, _)| i);
+    let only_best = |i: usize| if Some(i) == best { 1.0 } else { 0.0 };
+
+    match self {
+      Self::Best => (0..scores.len()).map(only_best).collect(),
+      Self::Weighted { temperature } => {
+        let top = best.map_or(0.0, |i| scores[i]);
+        let raw: Vec<f32> = scores
+          .iter()
+          .zip(speaker_weights)
+          .map(|(score, weight)| weight.max(0.0) * ((score - top) / temperature.max(1e-3)).exp())
+          .collect();
+        let total: f32 = raw.iter().sum();
+        if total > f32::EPSILON {
+          raw.iter().map(|w| w / total).collect()
+        } else {
+          (0..scores.len()).map(only_best).collect()
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_best_takes_the_closest_speaker() {
+    let weights = ReferenceCombination::Best.weights(&[70.0, 92.0, 40.0], &[1.0, 1.0, 1.0]);
+    assert_eq!(weights, vec![0.0, 1.0, 0.0]);
+  }
+
+  #[test]
+  fn test_weighted_discounts_an_outlying_speaker() {
+    let combination = ReferenceCombination::default();
+    let weights = combination.weights(&[90.0, 88.0, 55.0], &[1.0, 1.0, 1.0]);
+    assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+    assert!(weights[0] > weights[1] && weights[1] > 0.3);
+    assert!(weights[2] < 1e-3, "{:?}", weights);
+
+    // Speaker weights scale each reference's share
+    let trusted = combination.weights(&[90.0, 90.0], &[3.0, 1.0]);
+    assert!((trusted[0] - 0.75).abs() < 1e-5);
+  }
+
+  #[test]
+  fn test_weighted_without_speaker_weight_falls_back_to_best() {
+    let weights = ReferenceCombination::default().weights(&[60.0, 80.0], &[0.0, 0.0]);
+    assert_eq!(weights, vec![0.0, 1.0]);
+  }
+}
@@ -0,0 +1,642 @@
+use anyhow::Result;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use super::diagnosis::SUBSTITUTED_BELOW;
+use super::mfcc::{MFCCExtractor, MfccConfig};
+use super::{Insertion, PhonemeScore, SAMPLE_RATE};
+
+/// Analysis frame and hop for display: 25 ms windows every 10 ms, finer than scoring uses
+const DISPLAY_FRAME: usize = 400;
+const DISPLAY_HOP: usize = 160;
+const DISPLAY_FFT: usize = 512;
+
+/// Height of the strip above the plots that names and colours each phoneme
+const LABEL_HEIGHT: u32 = 24;
+
+/// Floor on spectral power before converting to decibels
+const POWER_FLOOR: f32 = 1e-10;
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const WAVEFORM: [u8; 3] = [55, 65, 81];
+const BOUNDARY: [u8; 3] = [37, 99, 235];
+const SEPARATOR: [u8; 3] = [209, 213, 219];
+const GOOD: [u8; 3] = [22, 163, 74];
+const FAIR: [u8; 3] = [217, 119, 6];
+const POOR: [u8; 3] = [220, 38, 38];
+const INSERTED: [u8; 3] = [124, 58, 237];
+
+/// Score at or above which a phoneme is drawn as well said
+const GOOD_FROM: f32 = 80.0;
+
+/// Encoding of a rendered recording
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+  #[default]
+  Png,
+  Svg,
+}
+
+impl ImageFormat {
+  pub fn content_type(self) -> &'static str {
+    match self {
+      ImageFormat::Png => "image/png",
+      ImageFormat::Svg => "image/svg+xml",
+    }
+  }
+}
+
+/// Image size and spectrogram range
+#[derive(Debug, Clone)]
+pub struct RenderConfig {
+  pub width: u32,
+  pub waveform_height: u32,
+  /// Height of each spectrogram, the learner's and the reference's
+  pub spectrogram_height: u32,
+  /// Highest frequency shown; formants of interest lie below 5 kHz
+  pub max_freq: f32,
+  /// Levels this far below a spectrogram's loudest point are drawn blank
+  pub dynamic_range_db: f32,
+}
+
+impl Default for RenderConfig {
+  fn default() -> Self {
+    Self {
+      width: 800,
+      waveform_height: 100,
+      spectrogram_height: 240,
+      max_freq: 5000.0,
+      dynamic_range_db: 70.0,
+    }
+  }
+}
+
+/// Piecewise linear map from the learner's timeline to a reference recording's, built from a
+/// DTW path. Times outside the path keep the offset at its nearer end.
+#[derive(Debug, Clone, Default)]
+pub struct TimeWarp {
+  /// `(learner_ms, reference_ms)`, strictly increasing in learner time
+  points: Vec<(f32, f32)>,
+}
+
+impl TimeWarp {
+  /// `path` pairs learner and reference frames `frame_ms` apart, counted from the given offsets
+  pub fn from_path(
+    path: &[(usize, usize)],
+    frame_ms: f32,
+    learner_offset_ms: f32,
+    reference_offset_ms: f32,
+  ) -> Self {
+    // A learner frame matched to several reference frames maps to their middle
+    let mut points: Vec<(f32, f32)> = Vec::new();
+    let mut matched = 0;
+    for &(learner, reference) in path {
+      let learner_ms = learner_offset_ms + learner as f32 * frame_ms;
+      let reference_ms = reference_offset_ms + reference as f32 * frame_ms;
+      match points.last_mut() {
+        Some(last) if last.0 == learner_ms => {
+          matched += 1;
+          last.1 += (reference_ms - last.1) / matched as f32;
+        }
+        _ => {
+          points.push((learner_ms, reference_ms));
+          matched = 1;
+        }
+      }
+    }
+    Self { points }
+  }
+
+  /// Time in the reference recording said at `learner_ms` in the learner's
+  pub fn reference_ms(&self, learner_ms: f32) -> f32 {
+    let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+      return learner_ms;
+    };
+    if learner_ms <= first.0 {
+      return learner_ms + first.1 - first.0;
+    }
+    if learner_ms >= last.0 {
+      return learner_ms + last.1 - last.0;
+    }
+    let next = self.points.partition_point(|p| p.0 <= learner_ms);
+    let (a, b) = (self.points[next - 1], self.points[next]);
+    a.1 + (b.1 - a.1) * (learner_ms - a.0) / (b.0 - a.0)
+  }
+}
+
+/// A recording laid out for drawing: one waveform extent and spectrogram column per pixel
+#[derive(Debug, Clone)]
+pub struct Plot {
+  pub width: u32,
+  pub duration_ms: f32,
+  /// Lowest and highest sample under each pixel column, scaled to the recording's peak
+  pub waveform: Vec<(f32, f32)>,
+  /// Spectrogram intensities, 0 (blank) to 1 (loudest), row-major from the top row
+  pub spectrogram: Vec<f32>,
+  /// The reference's spectrogram, warped onto the learner's timeline
+  pub reference: Option<Vec<f32>>,
+  pub phonemes: Vec<PhonemeScore>,
+  config: RenderConfig,
+}
+
+/// Draws recordings as a waveform over a spectrogram, with phoneme boundaries from alignment
+pub struct Renderer {
+  extractor: MFCCExtractor,
+  config: RenderConfig,
+}
+
+impl Default for Renderer {
+  fn default() -> Self {
+    Self::new(RenderConfig::default())
+  }
+}
+
+impl Renderer {
+  pub fn new(config: RenderConfig) -> Self {
+    let extractor = MFCCExtractor::with_config(MfccConfig {
+      frame_size: DISPLAY_FRAME,
+      hop_size: DISPLAY_HOP,
+      fft_size: DISPLAY_FFT,
+      ..MfccConfig::default()
+    })
+    .expect("display spectrogram config is valid");
+    Self { extractor, config }
+  }
+
+  /// Lay out the learner's recording, marking `per_phoneme` boundaries, optionally over a
+  /// reference recording mapped onto the learner's timeline by `warp`
+  pub fn plot(
+    &mut self,
+    audio: &[f32],
+    per_phoneme: &[PhonemeScore],
+    reference: Option<(&[f32], &TimeWarp)>,
+  ) -> Result<Plot> {
+    let width = self.config.width.max(1);
+    let duration_ms = audio.len() as f32 * 1000.0 / SAMPLE_RATE as f32;
+    let column_ms = |x: u32| (x as f32 + 0.5) * duration_ms / width as f32;
+
+    let peak = audio.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    let scale = if peak > 0.0 { 1.0 / peak } else { 0.0 };
+    let waveform = (0..width)
+      .map(|x| {
+        let start = audio.len() * x as usize / width as usize;
+        let end = (audio.len() * (x as usize + 1) / width as usize).max(start + 1);
+        audio
+          .get(start..end.min(audio.len()))
+          .unwrap_or_default()
+          .iter()
+          .fold((0.0f32, 0.0f32), |(lo, hi), &s| {
+            (lo.min(s * scale), hi.max(s * scale))
+          })
+      })
+      .collect();
+
+    let learner_frames = self.decibels(audio)?;
+    let spectrogram = self.columns(&learner_frames, (0..width).map(column_ms));
+    let reference = match reference {
+      Some((reference_audio, warp)) => {
+        let frames = self.decibels(reference_audio)?;
+        Some(self.columns(&frames, (0..width).map(|x| warp.reference_ms(column_ms(x)))))
+      }
+      None => None,
+    };
+
+    Ok(Plot {
+      width,
+      duration_ms,
+      waveform,
+      spectrogram,
+      reference,
+      phonemes: per_phoneme.to_vec(),
+      config: self.config.clone(),
+    })
+  }
+
+  /// Power spectrogram in decibels
+  fn decibels(&mut self, audio: &[f32]) -> Result<Vec<Vec<f32>>> {
+    let mut frames = self.extractor.power_spectrogram(audio)?;
+    for power in frames.iter_mut().flatten() {
+      *power = 10.0 * power.max(POWER_FLOOR).log10();
+    }
+    Ok(frames)
+  }
+
+  /// Sample the spectrogram at each column's time, blank past either end of the recording, and
+  /// scale levels to the dynamic range below its loudest point
+  fn columns(&self, frames: &[Vec<f32>], times_ms: impl Iterator<Item = f32>) -> Vec<f32> {
+    let width = self.config.width.max(1) as usize;
+    let height = self.config.spectrogram_height as usize;
+    let hop_ms = DISPLAY_HOP as f32 * 1000.0 / SAMPLE_RATE as f32;
+    let frame_ms = DISPLAY_FRAME as f32 * 1000.0 / SAMPLE_RATE as f32;
+    let bin_hz = SAMPLE_RATE as f32 / DISPLAY_FFT as f32;
+    let max_freq = self.config.max_freq.min(SAMPLE_RATE as f32 / 2.0);
+    let range = self.config.dynamic_range_db.max(1.0);
+    let loudest = frames
+      .iter()
+      .flatten()
+      .fold(f32::NEG_INFINITY, |m, &db| m.max(db));
+    let total_ms = frames.len() as f32 * hop_ms + frame_ms - hop_ms;
+
+    let mut pixels = vec![0.0; width * height];
+    for (x, t) in times_ms.enumerate().take(width) {
+      if frames.is_empty() || t < 0.0 || t > total_ms {
+        continue;
+      }
+      let frame =
+        &frames[(((t - frame_ms / 2.0) / hop_ms).round().max(0.0) as usize).min(frames.len() - 1)];
+      for y in 0..height {
+        let freq = (height - y) as f32 - 0.5;
+        let bin = freq / height as f32 * max_freq / bin_hz;
+        let lower = (bin.floor() as usize).min(frame.len() - 1);
+        let upper = (lower + 1).min(frame.len() - 1);
+        let db = frame[lower] + (frame[upper] - frame[lower]) * bin.fract();
+        pixels[y * width + x] = ((db - (loudest - range)) / range).clamp(0.0, 1.0);
+      }
+    }
+    pixels
+  }
+}
+
+impl Plot {
+  fn height(&self) -> u32 {
+    let spectrograms = if self.reference.is_some() { 2 } else { 1 };
+    LABEL_HEIGHT + self.config.waveform_height + spectrograms * self.config.spectrogram_height
+  }
+
+  /// Top of the waveform, learner spectrogram and reference spectrogram panels
+  fn panels(&self) -> (u32, u32, u32) {
+    let waveform = LABEL_HEIGHT;
+    let spectrogram = waveform + self.config.waveform_height;
+    (
+      waveform,
+      spectrogram,
+      spectrogram + self.config.spectrogram_height,
+    )
+  }
+
+  fn x(&self, ms: u32) -> f32 {
+    if self.duration_ms > 0.0 {
+      (ms as f32 / self.duration_ms * self.width as f32).clamp(0.0, self.width as f32)
+    } else {
+      0.0
+    }
+  }
+
+  /// Coloured bands for the phonemes and the sounds inserted between them
+  fn bands(&self) -> Vec<(f32, f32, [u8; 3], Option<&str>)> {
+    let mut bands = Vec::new();
+    let insertion_band = |insertion: &Insertion| {
+      (
+        self.x(insertion.start_ms),
+        self.x(insertion.end_ms),
+        INSERTED,
+        None,
+      )
+    };
+    for phoneme in &self.phonemes {
+      bands.extend(phoneme.inserted_before.iter().map(insertion_band));
+      let colour = if phoneme.score >= GOOD_FROM {
+        GOOD
+      } else if phoneme.score >= SUBSTITUTED_BELOW {
+        FAIR
+      } else {
+        POOR
+      };
+      bands.push((
+        self.x(phoneme.start_ms),
+        self.x(phoneme.end_ms),
+        colour,
+        Some(phoneme.phoneme.as_str()),
+      ));
+      bands.extend(phoneme.inserted_after.iter().map(insertion_band));
+    }
+    bands
+  }
+
+  /// Pixel columns of every phoneme boundary
+  fn boundaries(&self) -> Vec<f32> {
+    let mut boundaries: Vec<f32> = self
+      .phonemes
+      .iter()
+      .flat_map(|p| [self.x(p.start_ms), self.x(p.end_ms)])
+      .collect();
+    boundaries.sort_by(f32::total_cmp);
+    boundaries.dedup_by(|a, b| (*a - *b).abs() < 1.0);
+    boundaries
+  }
+
+  /// Render as a PNG image; phoneme names are only drawn in SVG
+  pub fn to_png(&self) -> Result<Vec<u8>> {
+    let mut canvas = Canvas::new(self.width, self.height());
+    let (waveform_top, spectrogram_top, reference_top) = self.panels();
+
+    for (start, end, colour, _) in self.bands() {
+      canvas.fill(start as u32, end.ceil() as u32, 4, LABEL_HEIGHT - 4, colour);
+    }
+    let mid = waveform_top as f32 + self.config.waveform_height as f32 / 2.0;
+    let half = self.config.waveform_height as f32 / 2.0 - 2.0;
+    for (x, &(lo, hi)) in self.waveform.iter().enumerate() {
+      let (top, bottom) = ((mid - hi * half) as u32, (mid - lo * half) as u32);
+      canvas.fill(x as u32, x as u32 + 1, top, bottom + 1, WAVEFORM);
+    }
+    canvas.blit(&self.spectrogram, spectrogram_top);
+    if let Some(reference) = &self.reference {
+      canvas.blit(reference, reference_top);
+    }
+    for top in [spectrogram_top, reference_top] {
+      canvas.fill(0, self.width, top, top + 1, SEPARATOR);
+    }
+    for x in self.boundaries() {
+      let x = (x as u32).min(self.width - 1);
+      canvas.fill(x, x + 1, 0, self.height(), BOUNDARY);
+    }
+    canvas.encode(png::ColorType::Rgb)
+  }
+
+  /// Render as an SVG document, with the spectrograms embedded as PNG
+  pub fn to_svg(&self) -> Result<String> {
+    let (width, height) = (self.width, self.height());
+    let (waveform_top, spectrogram_top, reference_top) = self.panels();
+    let mut svg = String::new();
+    writeln!(
+      svg,
+      r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="13">"#
+    )?;
+    writeln!(
+      svg,
+      r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+      hex(BACKGROUND)
+    )?;
+
+    for (start, end, colour, label) in self.bands() {
+      writeln!(
+        svg,
+        r#"<rect x="{start:.1}" y="2" width="{:.1}" height="{}" fill="{}" fill-opacity="0.25"/>"#,
+        (end - start).max(1.0),
+        LABEL_HEIGHT - 4,
+        hex(colour)
+      )?;
+      if let Some(label) = label {
+        writeln!(
+          svg,
+          r#"<text x="{:.1}" y="{}" text-anchor="middle" fill="{}">{}</text>"#,
+          (start + end) / 2.0,
+          LABEL_HEIGHT - 7,
+          hex(colour),
+          escape(label)
+        )?;
+      }
+    }
+
+    let mid = waveform_top as f32 + self.config.waveform_height as f32 / 2.0;
+    let half = self.config.waveform_height as f32 / 2.0 - 2.0;
+    let mut path = String::new();
+    for (x, &(lo, hi)) in self.waveform.iter().enumerate() {
+      write!(
+        path,
+        "M{}.5 {:.1}V{:.1}",
+        x,
+        mid - hi * half,
+        mid - lo * half + 0.5
+      )?;
+    }
+    writeln!(
+      svg,
+      r#"<path d="{path}" stroke="{}" stroke-width="1"/>"#,
+      hex(WAVEFORM)
+    )?;
+
+    let panels = [Some((&self.spectrogram, spectrogram_top))]
+      .into_iter()
+      .chain([self.reference.as_ref().map(|r| (r, reference_top))]);
+    for (pixels, top) in panels.flatten() {
+      let mut canvas = Canvas::new(width, self.config.spectrogram_height);
+      canvas.blit(pixels, 0);
+      let encoded = base64::engine::general_purpose::STANDARD.encode(canvas.encode_gray()?);
+      writeln!(
+        svg,
+        r#"<image x="0" y="{top}" width="{width}" height="{}" preserveAspectRatio="none" href="data:image/png;base64,{encoded}"/>"#,
+        self.config.spectrogram_height
+      )?;
+      writeln!(
+        svg,
+        r#"<line x1="0" y1="{top}" x2="{width}" y2="{top}" stroke="{}"/>"#,
+        hex(SEPARATOR)
+      )?;
+    }
+    if self.reference.is_some() {
+      writeln!(
+        svg,
+        r#"<text x="4" y="{}" fill="{}">reference</text>"#,
+        reference_top + 15,
+        hex(BOUNDARY)
+      )?;
+    }
+
+    for x in self.boundaries() {
+      writeln!(
+        svg,
+        r#"<line x1="{x:.1}" y1="0" x2="{x:.1}" y2="{height}" stroke="{}" stroke-opacity="0.8"/>"#,
+        hex(BOUNDARY)
+      )?;
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+  }
+
+  pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>> {
+    match format {
+      ImageFormat::Png => self.to_png(),
+      ImageFormat::Svg => Ok(self.to_svg()?.into_bytes()),
+    }
+  }
+}
+
+/// RGB raster the PNG output is drawn on
+struct Canvas {
+  width: u32,
+  height: u32,
+  pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+  fn new(width: u32, height: u32) -> Self {
+    Self {
+      width,
+      height,
+      pixels: vec![BACKGROUND; (width * height) as usize],
+    }
+  }
+
+  /// Fill columns `x0..x1` of rows `y0..y1`, clipped to the canvas
+  fn fill(&mut self, x0: u32, x1: u32, y0: u32, y1: u32, colour: [u8; 3]) {
+    for y in y0.min(self.height)..y1.min(self.height) {
+      for x in x0.min(self.width)..x1.min(self.width) {
+        self.pixels[(y * self.width + x) as usize] = colour;
+      }
+    }
+  }
+
+  /// Draw spectrogram intensities as greys, dark where loud, with the top row at `top`
+  fn blit(&mut self, intensities: &[f32], top: u32) {
+    for (i, &level) in intensities.iter().enumerate() {
+      let (x, y) = (i as u32 % self.width, top + i as u32 / self.width);
+      if y < self.height {
+        let grey = (255.0 * (1.0 - level)).round() as u8;
+        self.pixels[(y * self.width + x) as usize] = [grey; 3];
+      }
+    }
+  }
+
+  fn encode(&self, colour: png::ColorType) -> Result<Vec<u8>> {
+    let data: Vec<u8> = match colour {
+      png::ColorType::Grayscale => self.pixels.iter().map(|p| p[0]).collect(),
+      _ => self.pixels.iter().flatten().copied().collect(),
+    };
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+    encoder.set_color(colour);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(bytes)
+  }
+
+  fn encode_gray(&self) -> Result<Vec<u8>> {
+    self.encode(png::ColorType::Grayscale)
+  }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+  format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::Dialect;
+  use crate::services::ipa::Transcription;
+  use crate::services::scoring::{FormantSynthesizer, PronunciationScorer};
+
+  fn tone(freq: f32, len: usize) -> Vec<f32> {
+    (0..len)
+      .map(|n| 0.5 * (2.0 * std::f32::consts::PI * freq * n as f32 / SAMPLE_RATE as f32).sin())
+      .collect()
+  }
+
+  #[test]
+  fn test_time_warp() {
+    // The learner holds their second frame for three reference frames
+    let warp = TimeWarp::from_path(&[(0, 0), (1, 1), (1, 2), (1, 3), (2, 4)], 10.0, 100.0, 50.0);
+    assert_eq!(warp.reference_ms(100.0), 50.0);
+    assert_eq!(warp.reference_ms(110.0), 70.0);
+    assert_eq!(warp.reference_ms(115.0), 80.0);
+    assert_eq!(warp.reference_ms(20.0), -30.0);
+    assert_eq!(warp.reference_ms(300.0), 270.0);
+    assert_eq!(TimeWarp::default().reference_ms(42.0), 42.0);
+  }
+
+  #[test]
+  fn test_spectrogram_shows_the_tone() {
+    let config = RenderConfig {
+      width: 100,
+      ..RenderConfig::default()
+    };
+    let height = config.spectrogram_height as usize;
+    let mut renderer = Renderer::new(config);
+    // Half a second of silence, then half a second of 1 kHz
+    let audio = [vec![0.0; 8000], tone(1000.0, 8000)].concat();
+    let plot = renderer.plot(&audio, &[], None).unwrap();
+
+    assert_eq!(plot.waveform.len(), 100);
+    assert_eq!(plot.waveform[10], (0.0, 0.0));
+    assert!(plot.waveform[75].1 > 0.99 && plot.waveform[75].0 < -0.99);
+
+    // The loudest row in the tone half sits at 1 kHz of 5 kHz from the bottom
+    let spectrogram = &plot.spectrogram;
+    let column = |x: usize| (0..height).map(move |y| spectrogram[y * 100 + x]);
+    let loudest = column(75)
+      .enumerate()
+      .max_by(|a, b| a.1.total_cmp(&b.1))
+      .unwrap()
+      .0;
+    let expected = height - height / 5;
+    assert!(loudest.abs_diff(expected) <= 2, "peak at row {loudest}");
+    assert!(column(10).all(|level| level == 0.0));
+  }
+
+  #[test]
+  fn test_reference_is_warped_onto_learner_timeline() {
+    let config = RenderConfig {
+      width: 100,
+      ..RenderConfig::default()
+    };
+    let height = config.spectrogram_height as usize;
+    let mut renderer = Renderer::new(config);
+    let learner = [tone(500.0, 8000), tone(2000.0, 8000)].concat();
+    // The reference says the same thing three times as fast
+    let reference = [tone(500.0, 2667), tone(2000.0, 2667)].concat();
+    let warp = TimeWarp::from_path(&[(0, 0), (500, 167), (1000, 333)], 1.0, 0.0, 0.0);
+    let plot = renderer
+      .plot(&learner, &[], Some((&reference, &warp)))
+      .unwrap();
+
+    let reference_plot = plot.reference.as_ref().unwrap();
+    let loudest_row = |pixels: &[f32], x: usize| {
+      (0..height)
+        .max_by(|&a, &b| pixels[a * 100 + x].total_cmp(&pixels[b * 100 + x]))
+        .unwrap()
+    };
+    for x in [20, 80] {
+      assert!(loudest_row(reference_plot, x).abs_diff(loudest_row(&plot.spectrogram, x)) <= 2);
+    }
+  }
+
+  #[test]
+  fn test_encodings() {
+    let target = Transcription::parse("ˈnɑlmi");
+    let synthesis = FormantSynthesizer::for_dialect(&Dialect::GA)
+      .synthesize(&target)
+      .unwrap();
+    let mut scorer = PronunciationScorer::new();
+    let score = scorer
+      .score_pronunciation(&synthesis.audio, &synthesis.audio, &target)
+      .unwrap();
+    let warp = scorer
+      .time_warp(&synthesis.audio, &synthesis.audio)
+      .unwrap();
+    let plot = Renderer::default()
+      .plot(
+        &synthesis.audio,
+        &score.per_phoneme,
+        Some((&synthesis.audio, &warp)),
+      )
+      .unwrap();
+
+    let png = plot.encode(ImageFormat::Png).unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+    let decoder = png::Decoder::new(png.as_slice());
+    let info = decoder.read_info().unwrap().info().clone();
+    assert_eq!((info.width, info.height), (800, 24 + 100 + 240 * 2));
+
+    let svg = String::from_utf8(plot.encode(ImageFormat::Svg).unwrap()).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<image").count(), 2);
+    for phoneme in ["n", "ɑ", "l", "m", "i"] {
+      assert!(
+        svg.contains(&format!(">{phoneme}</text>")),
+        "{phoneme} is labelled"
+      );
+    }
+    // A boundary between each phoneme and at either end
+    assert_eq!(svg.matches(r#"stroke-opacity="0.8""#).count(), 6);
+  }
+}
@@ -0,0 +1,12 @@
+//! Deterministic test signals shared by the scoring tests
+
+/// Uniform white noise in [-amplitude, amplitude], the same for the same seed
+pub fn noise(len: usize, amplitude: f32, seed: u32) -> Vec<f32> {
+  let mut state = seed;
+  (0..len)
+    .map(|_| {
+      state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+      ((state >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0) * amplitude
+    })
+    .collect()
+}
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+
+use super::pitch::{PitchConfig, PitchTracker};
+use super::PhonemeScore;
+use crate::services::ipa::{Stress, Transcription};
+
+/// Relative weight of each cue in a syllable's prominence
+const ENERGY_WEIGHT: f32 = 0.4;
+const DURATION_WEIGHT: f32 = 0.3;
+const PITCH_WEIGHT: f32 = 0.3;
+
+/// Spreads below these are treated as no contrast rather than amplified into one
+const MIN_ENERGY_SPREAD_DB: f32 = 1.0;
+const MIN_DURATION_SPREAD: f32 = 0.05;
+const MIN_PITCH_SPREAD_SEMITONES: f32 = 0.5;
+
+/// How sharply the prominence margin over other syllables maps to the stress score
+const SCORE_SHARPNESS: f32 = 2.0;
+
+/// Acoustic measurements of one syllable nucleus in the learner's recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyllableProsody {
+  pub start_ms: u32,
+  pub end_ms: u32,
+  pub energy_db: f32,
+  pub f0_hz: Option<f32>,
+  /// Weighted z-score of energy, duration and pitch across the word's syllables
+  pub prominence: f32,
+  /// Stress the transcription marks on this syllable
+  pub expected: Stress,
+}
+
+/// How well the learner placed lexical stress
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StressScore {
+  /// Syllable the transcription marks with primary stress
+  pub expected_syllable: usize,
+  /// Most prominent syllable in the learner's recording
+  pub detected_syllable: usize,
+  /// 0-100; above 50 when the expected syllable stands out from the others
+  pub score: f32,
+  pub syllables: Vec<SyllableProsody>,
+}
+
+/// Measures syllable energy, duration and F0 to find which syllable the learner stressed
+pub struct StressAnalyzer {
+  sample_rate: u32,
+  pitch: PitchTracker,
+}
+
+impl StressAnalyzer {
+  pub fn new(sample_rate: u32) -> Self {
+    Self {
+      sample_rate,
+      pitch: PitchTracker::new(PitchConfig {
+        sample_rate,
+        ..Default::default()
+      }),
+    }
+  }
+
+  /// Compare the most prominent syllable nucleus with the transcription's primary stress.
+  ///
+  /// Nucleus times come from `per_phoneme`, which must be aligned to `audio` and follow the
+  /// transcription's phonemes. Returns `None` for monosyllables and unmarked transcriptions.
+  pub fn analyze(
+    &self,
+    audio: &[f32],
+    per_phoneme: &[PhonemeScore],
+    target: &Transcription,
+  ) -> Option<StressScore> {
+    let expected_syllable = target.primary_stress()?;
+    if target.syllables.len() < 2
+      || per_phoneme.len() != target.phonemes.len()
+      || target
+        .syllables
+        .iter()
+        .any(|s| s.nucleus.end > per_phoneme.len())
+    {
+      return None;
+    }
+
+    let contour = self.pitch.track(audio);
+    let spans: Vec<(u32, u32)> = target
+      .syllables
+      .iter()
+      .map(|s| {
+        let start = per_phoneme[s.nucleus.start].start_ms;
+        let end = per_phoneme[s.nucleus.end - 1].end_ms.max(start + 1);
+        (start, end)
+      })
+      .collect();
+
+    let energy: Vec<Option<f32>> = spans
+      .iter()
+      .map(|&(start, end)| Some(self.energy_db(audio, start, end)))
+      .collect();
+    let duration: Vec<Option<f32>> = spans
+      .iter()
+      .map(|&(start, end)| Some(((end - start) as f32).ln()))
+      .collect();
+    let f0: Vec<Option<f32>> = spans
+      .iter()
+      .map(|&(start, end)| contour.median_between(start, end))
+      .collect();
+    let semitones: Vec<Option<f32>> = f0
+      .iter()
+      .map(|f| f.map(|hz| 12.0 * (hz / 100.0).log2()))
+      .collect();
+
+    let prominence: Vec<f32> = z_scores(&energy, MIN_ENERGY_SPREAD_DB)
+      .iter()
+      .zip(z_scores(&duration, MIN_DURATION_SPREAD))
+      .zip(z_scores(&semitones, MIN_PITCH_SPREAD_SEMITONES))
+      .map(|((e, d), p)| ENERGY_WEIGHT * e + DURATION_WEIGHT * d + PITCH_WEIGHT * p)
+      .collect();
+
+    let detected_syllable = prominence
+      .iter()
+      .enumerate()
+      .max_by(|a, b| a.1.total_cmp(b.1))
+      .map(|(i, _)| i)?;
+
+    // Logistic in the margin over the strongest competing syllable: 50 for a tie
+    let competitor = prominence
+      .iter()
+      .enumerate()
+      .filter(|&(i, _)| i != expected_syllable)
+      .map(|(_, &p)| p)
+      .fold(f32::NEG_INFINITY, f32::max);
+    let margin = prominence[expected_syllable] - competitor;
+    let score = 100.0 / (1.0 + (-SCORE_SHARPNESS * margin).exp());
+
+    let syllables = target
+      .syllables
+      .iter()
+      .zip(&spans)
+      .zip(energy.iter().zip(&f0))
+      .zip(&prominence)
+      .map(
+        |(((syllable, &(start_ms, end_ms)), (energy, f0)), &prominence)| SyllableProsody {
+          start_ms,
+          end_ms,
+          energy_db: energy.unwrap_or_default(),
+          f0_hz: *f0,
+          prominence,
+          expected: syllable.stress,
+        },
+      )
+      .collect();
+
+    Some(StressScore {
+      expected_syllable,
+      detected_syllable,
+      score,
+      syllables,
+    })
+  }
+
+  fn energy_db(&self, audio: &[f32], start_ms: u32, end_ms: u32) -> f32 {
+    let to_sample =
+      |ms: u32| ((ms as u64 * self.sample_rate as u64 / 1000) as usize).min(audio.len());
+    let samples = &audio[to_sample(start_ms)..to_sample(end_ms)];
+    let power = samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32;
+    10.0 * power.max(1e-12).log10()
+  }
+}
+
+/// Standardise the present values, leaving missing ones (and cues with fewer than two values) at 0
+fn z_scores(values: &[Option<f32>], min_spread: f32) -> Vec<f32> {
+  let present: Vec<f32> = values.iter().flatten().copied().collect();
+  if present.len() < 2 {
+    return vec![0.0; values.len()];
+  }
+
+  let mean = present.iter().sum::<f32>() / present.len() as f32;
+  let variance = present.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / present.len() as f32;
+  let spread = variance.sqrt().max(min_spread);
+  values
+    .iter()
+    .map(|v| v.map_or(0.0, |v| (v - mean) / spread))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::SAMPLE_RATE;
+
+  /// A vowel-like harmonic tone of `ms` milliseconds
+  fn vowel(f0: f32, amplitude: f32, ms: u32) -> Vec<f32> {
+    let len = (ms * SAMPLE_RATE / 1000) as usize;
+    (0..len)
+      .map(|n| {
+        let t = n as f32 / SAMPLE_RATE as f32;
+        (1..=4)
+          .map(|k| amplitude / k as f32 * (2.0 * std::f32::consts::PI * f0 * k as f32 * t).sin())
+          .sum()
+      })
+      .collect()
+  }
+
+  fn consonant(ms: u32) -> Vec<f32> {
+    vec![0.0; (ms * SAMPLE_RATE / 1000) as usize]
+  }
+
+  /// "banana" with the given (f0, amplitude, duration) per vowel, and matching phoneme times
+  fn banana(vowels: [(f32, f32, u32); 3]) -> (Vec<f32>, Vec<PhonemeScore>) {
+    let target = Transcription::parse("bəˈnænə");
+    let mut audio = Vec::new();
+    let mut per_phoneme = Vec::new();
+    let mut vowel_index = 0;
+
+    for phoneme in &target.phonemes {
+      let start_ms = (audio.len() as u32 * 1000) / SAMPLE_RATE;
+      if crate::services::ipa::is_vowel(phoneme) {
+        let (f0, amplitude, ms) = vowels[vowel_index];
+        audio.extend(vowel(f0, amplitude, ms));
+        vowel_index += 1;
+      } else {
+        audio.extend(consonant(60));
+      }
+      per_phoneme.push(PhonemeScore {
+        phoneme: phoneme.clone(),
+        start_ms,
+        end_ms: (audio.len() as u32 * 1000) / SAMPLE_RATE,
+        score: 100.0,
+        ..Default::default()
+      });
+    }
+
+    (audio, per_phoneme)
+  }
+
+  #[test]
+  fn test_correct_stress_placement_scores_high() {
+    let target = Transcription::parse("bəˈnænə");
+    let (audio, per_phoneme) = banana([(110.0, 0.1, 80), (150.0, 0.4, 200), (100.0, 0.1, 90)]);
+
+    let result = StressAnalyzer::new(SAMPLE_RATE)
+      .analyze(&audio, &per_phoneme, &target)
+      .unwrap();
+    assert_eq!(result.expected_syllable, 1);
+    assert_eq!(result.detected_syllable, 1);
+    assert!(result.score > 80.0, "{:?}", result);
+    let f0 = result.syllables[1].f0_hz.unwrap();
+    assert!((f0 - 150.0).abs() < 3.0);
+  }
+
+  #[test]
+  fn test_misplaced_stress_scores_low() {
+    // The learner says BAnana
+    let target = Transcription::parse("bəˈnænə");
+    let (audio, per_phoneme) = banana([(150.0, 0.4, 200), (110.0, 0.1, 90), (100.0, 0.1, 90)]);
+
+    let result = StressAnalyzer::new(SAMPLE_RATE)
+      .analyze(&audio, &per_phoneme, &target)
+      .unwrap();
+    assert_eq!(result.detected_syllable, 0);
+    assert!(result.score < 20.0, "{:?}", result);
+  }
+
+  #[test]
+  fn test_no_stress_score_without_marked_polysyllable() {
+    let analyzer = StressAnalyzer::new(SAMPLE_RATE);
+    let (audio, per_phoneme) = banana([(110.0, 0.1, 80), (150.0, 0.4, 200), (100.0, 0.1, 90)]);
+
+    let unmarked = Transcription::parse("bənænə");
+    assert!(analyzer.analyze(&audio, &per_phoneme, &unmarked).is_none());
+    let mismatched = Transcription::parse("ˈkæt");
+    assert!(analyzer
+      .analyze(&audio, &per_phoneme, &mismatched)
+      .is_none());
+  }
+}
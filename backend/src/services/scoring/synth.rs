@@ -0,0 +1,838 @@
+use std::f32::consts::PI;
+use std::ops::Range;
+
+use anyhow::{anyhow, ensure, Result};
+
+use super::vowel_chart::{ipa_vowel_formants, vowel_chart, VowelTarget};
+use super::SAMPLE_RATE;
+use crate::models::Dialect;
+use crate::services::articulation::{self, Place};
+use crate::services::ipa::{self, Stress, Transcription};
+
+/// Longest transcription, in phonemes, synthesized on request; synthesis time grows with it
+pub const MAX_SYNTHESIS_PHONEMES: usize = 64;
+
+/// Synthesis parameters are updated every 5 ms and interpolated in between
+const FRAME_MS: f32 = 5.0;
+
+/// Silence before and after the word
+const PAUSE_MS: f32 = 100.0;
+
+/// Formant tracks are smoothed over this span, giving the transitions between segments
+const TRANSITION_MS: f32 = 30.0;
+
+/// Peak level of the rendered clip
+const PEAK: f32 = 0.5;
+
+/// Fixed fourth formant of an adult male vocal tract
+const F4: (f32, f32) = (3500.0, 250.0);
+
+/// Formant bandwidths for oral and nasal sounds, in Hz
+const ORAL_BANDWIDTHS: [f32; 3] = [60.0, 90.0, 150.0];
+const NASAL_BANDWIDTHS: [f32; 3] = [150.0, 250.0, 350.0];
+
+/// Level of noise sources relative to voicing, after the vocal tract filter
+const ASPIRATION_GAIN: f32 = 0.012;
+const FRICATION_GAIN: f32 = 0.12;
+
+/// Formant loci of consonants by place of articulation
+const LABIAL: [f32; 3] = [250.0, 900.0, 2200.0];
+const DENTAL: [f32; 3] = [300.0, 1500.0, 2600.0];
+const ALVEOLAR: [f32; 3] = [300.0, 1700.0, 2600.0];
+const POSTALVEOLAR: [f32; 3] = [300.0, 2000.0, 2600.0];
+const PALATAL: [f32; 3] = [280.0, 2200.0, 2900.0];
+const VELAR: [f32; 3] = [300.0, 1900.0, 2300.0];
+const UVULAR: [f32; 3] = [400.0, 1300.0, 2400.0];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Manner {
+  Vowel,
+  Approximant,
+  Nasal,
+  Tap,
+  Fricative,
+  Stop,
+  Affricate,
+  /// h and ɦ: noise shaped by the neighbouring vowels
+  Aspirate,
+  GlottalStop,
+}
+
+/// How a segment is produced
+#[derive(Debug, Clone, Copy)]
+struct Articulation {
+  manner: Manner,
+  voiced: bool,
+  /// F1-F3; `None` takes them from the neighbouring segments
+  formants: Option<[f32; 3]>,
+  /// F1-F3 a diphthong glides towards from `formants`
+  offglide: Option<[f32; 3]>,
+  /// Centre frequency and level of frication noise, and of stop bursts
+  noise: (f32, f32),
+}
+
+impl Articulation {
+  const fn new(manner: Manner, voiced: bool, formants: [f32; 3], noise: (f32, f32)) -> Self {
+    Self {
+      manner,
+      voiced,
+      formants: Some(formants),
+      offglide: None,
+      noise,
+    }
+  }
+
+  fn sonorant(&self) -> bool {
+    matches!(
+      self.manner,
+      Manner::Vowel | Manner::Approximant | Manner::Nasal | Manner::Tap
+    )
+  }
+}
+
+/// Articulation of a consonant, from its place, manner and voicing in the shared feature table
+fn consonant(phoneme: &str) -> Option<Articulation> {
+  use articulation::Manner::*;
+  let features = articulation::features(phoneme).filter(|f| f.is_consonant())?;
+  let (voiced, place, manner) = (features.voiced?, features.place?, features.manner?);
+  let silent = |manner| Articulation {
+    manner,
+    voiced,
+    formants: None,
+    offglide: None,
+    noise: (0.0, 0.0),
+  };
+  let a = |manner, formants, noise| Articulation::new(manner, voiced, formants, noise);
+  Some(match (place, manner) {
+    (Place::Glottal, Plosive) => silent(Manner::GlottalStop),
+    (Place::Glottal, _) => silent(Manner::Aspirate),
+    // ʍ is a voiceless w
+    (Place::LabialVelar, _) => a(Manner::Approximant, [300.0, 610.0, 2200.0], (0.0, 0.0)),
+    (_, Plosive) => {
+      let (centre, level) = burst(place);
+      a(
+        Manner::Stop,
+        locus(place),
+        (centre, level * voicing(voiced, 0.75)),
+      )
+    }
+    (_, Fricative | LateralFricative | Affricate) => {
+      let (centre, level) = frication(place, manner == LateralFricative);
+      let manner = match manner {
+        Affricate => Manner::Affricate,
+        _ => Manner::Fricative,
+      };
+      a(manner, locus(place), (centre, level * voicing(voiced, 0.6)))
+    }
+    (_, Nasal) => a(Manner::Nasal, nasal_formants(place), (0.0, 0.0)),
+    (_, Tap) => a(Manner::Tap, locus(place), (0.0, 0.0)),
+    (_, Trill | Approximant | LateralApproximant) => {
+      // Velarized laterals (ɫ, lˠ) are dark, with F2 low like ʟ
+      let dark = "ɫˠˤ".chars().any(|c| phoneme.contains(c));
+      let formants = match (place, manner) {
+        (Place::Velar, LateralApproximant) => [400.0, 900.0, 2500.0],
+        (_, LateralApproximant) if dark => [400.0, 900.0, 2500.0],
+        (_, LateralApproximant) => [360.0, 1300.0, 2700.0],
+        (Place::Bilabial | Place::Labiodental, _) => LABIAL,
+        (_, Trill) => [350.0, 1300.0, 1700.0],
+        (Place::Palatal, _) => [260.0, 2070.0, 3020.0],
+        (Place::Velar, _) => [300.0, 1300.0, 2400.0],
+        _ => [310.0, 1060.0, 1380.0],
+      };
+      a(Manner::Approximant, formants, (0.0, 0.0))
+    }
+  })
+}
+
+/// Formant locus a consonant's neighbours move towards
+fn locus(place: Place) -> [f32; 3] {
+  match place {
+    Place::Bilabial | Place::Labiodental | Place::LabialVelar => LABIAL,
+    Place::Dental => DENTAL,
+    Place::Alveolar => ALVEOLAR,
+    Place::Postalveolar | Place::Retroflex => POSTALVEOLAR,
+    Place::Palatal => PALATAL,
+    Place::Velar => VELAR,
+    Place::Uvular => UVULAR,
+    Place::Pharyngeal | Place::Glottal => [700.0, 1200.0, 2500.0],
+  }
+}
+
+fn nasal_formants(place: Place) -> [f32; 3] {
+  match place {
+    Place::Bilabial => [250.0, 1000.0, 2200.0],
+    Place::Labiodental => [250.0, 1100.0, 2200.0],
+    Place::Retroflex => [250.0, 1800.0, 2400.0],
+    Place::Palatal => [250.0, 2100.0, 2700.0],
+    Place::Velar => [250.0, 2000.0, 2400.0],
+    Place::Uvular => [250.0, 1500.0, 2400.0],
+    _ => [250.0, 1700.0, 2600.0],
+  }
+}
+
+/// Centre frequency and level of a voiceless stop's burst
+fn burst(place: Place) -> (f32, f32) {
+  match place {
+    Place::Bilabial | Place::Labiodental => (1000.0, 0.5),
+    Place::Dental | Place::Alveolar => (4000.0, 0.8),
+    Place::Velar => (2000.0, 0.8),
+    Place::Uvular | Place::Pharyngeal => (1500.0, 0.8),
+    _ => (3000.0, 0.8),
+  }
+}
+
+/// Centre frequency and level of a voiceless fricative's noise; sibilants are the loudest
+fn frication(place: Place, lateral: bool) -> (f32, f32) {
+  if lateral {
+    return (4500.0, 0.5);
+  }
+  match place {
+    Place::Bilabial => (1500.0, 0.2),
+    Place::Labiodental => (6000.0, 0.25),
+    Place::Dental => (6500.0, 0.2),
+    Place::Alveolar => (5500.0, 1.0),
+    Place::Postalveolar => (2800.0, 1.0),
+    Place::Retroflex => (3200.0, 1.0),
+    Place::Palatal => (3800.0, 0.7),
+    Place::Velar => (1800.0, 0.6),
+    Place::Uvular => (1400.0, 0.6),
+    _ => (1200.0, 0.4),
+  }
+}
+
+/// Voicing takes over part of the air that would otherwise make noise
+fn voicing(voiced: bool, scale: f32) -> f32 {
+  if voiced {
+    scale
+  } else {
+    1.0
+  }
+}
+
+/// One stretch of a segment with constant sources, e.g. the closure and release of a stop
+#[derive(Debug, Clone, Copy)]
+struct Phase {
+  ms: f32,
+  voicing: f32,
+  aspiration: f32,
+  frication: f32,
+}
+
+impl Phase {
+  fn new(ms: f32, voicing: f32, aspiration: f32, frication: f32) -> Self {
+    Self {
+      ms,
+      voicing,
+      aspiration,
+      frication,
+    }
+  }
+}
+
+/// Source parameters for one 5 ms frame
+#[derive(Debug, Clone, Copy, Default)]
+struct Frame {
+  f0: f32,
+  formants: [f32; 3],
+  bandwidths: [f32; 3],
+  voicing: f32,
+  aspiration: f32,
+  frication: f32,
+  noise_hz: f32,
+}
+
+/// The rendered word and where each phoneme falls in it
+#[derive(Debug, Clone)]
+pub struct Synthesis {
+  /// 16 kHz mono
+  pub audio: Vec<f32>,
+  /// Start and end of each phoneme of the transcription, in ms
+  pub spans: Vec<Range<u32>>,
+}
+
+/// Klatt-style cascade formant synthesizer that renders a transcription as a male voice.
+///
+/// Vowels take their formants from the dialect's measured vowel chart where there is one, so
+/// synthesized references sit in the same vowel space learners are compared against. Stress
+/// marks lengthen, raise in pitch and strengthen the stressed vowel.
+#[derive(Debug, Clone)]
+pub struct FormantSynthesizer {
+  /// Pitch at the start of the word, in Hz
+  f0: f32,
+  /// Measured vowel formants that override the built-in table
+  vowels: &'static [VowelTarget],
+}
+
+impl Default for FormantSynthesizer {
+  fn default() -> Self {
+    Self {
+      f0: 120.0,
+      vowels: &[],
+    }
+  }
+}
+
+impl FormantSynthesizer {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Use the dialect's vowel chart, when it has one
+  pub fn for_dialect(dialect: &Dialect) -> Self {
+    Self {
+      vowels: vowel_chart(dialect).map_or(&[], |chart| chart.male),
+      ..Self::default()
+    }
+  }
+
+  /// Speak at a different starting pitch
+  pub fn with_pitch(mut self, f0: f32) -> Self {
+    self.f0 = f0;
+    self
+  }
+
+  /// Render a transcription; fails on segments the synthesizer has no articulation for
+  pub fn synthesize(&self, target: &Transcription) -> Result<Synthesis> {
+    let (frames, spans) = self.plan(target)?;
+    Ok(Synthesis {
+      audio: render(&frames),
+      spans,
+    })
+  }
+
+  /// How long the word takes to say at the synthesizer's pace, without rendering it
+  pub fn duration_ms(&self, target: &Transcription) -> Result<u32> {
+    let spans = self.spans(target)?;
+    Ok(spans.last().map_or(0, |s| s.end) - spans.first().map_or(0, |s| s.start))
+  }
+
+  /// Where each phoneme would fall at the synthesizer's pace, without rendering the word
+  pub fn spans(&self, target: &Transcription) -> Result<Vec<Range<u32>>> {
+    Ok(self.plan(target)?.1)
+  }
+
+  /// Source and filter parameters for every frame, and each phoneme's span
+  fn plan(&self, target: &Transcription) -> Result<(Vec<Frame>, Vec<Range<u32>>)> {
+    ensure!(!target.phonemes.is_empty(), "Nothing to synthesize");
+    let articulations = target
+      .phonemes
+      .iter()
+      .map(|phoneme| self.articulation(phoneme))
+      .collect::<Result<Vec<_>>>()?;
+    let marked = target
+      .syllables
+      .iter()
+      .any(|s| s.stress != Stress::Unstressed);
+    let stress_of = |index: usize| {
+      target
+        .syllables
+        .iter()
+        .find(|s| s.nucleus.contains(&index))
+        .map(|s| s.stress)
+    };
+
+    let pause = (PAUSE_MS / FRAME_MS) as usize;
+    let mut frames = vec![Frame::default(); pause];
+    let mut formant_tracks: Vec<Option<[f32; 3]>> = vec![None; pause];
+    let mut spans = Vec::with_capacity(articulations.len());
+    // Where each stressed vowel sits, for the pitch accent
+    let mut accents: Vec<(f32, f32)> = Vec::new();
+
+    for (index, (phoneme, articulation)) in target.phonemes.iter().zip(&articulations).enumerate() {
+      let next = articulations.get(index + 1);
+      let aspirated = phoneme.contains('ʰ') || next.is_some_and(Articulation::sonorant);
+      let mut phases = phases(articulation, aspirated);
+
+      if articulation.manner == Manner::Vowel {
+        let stress = stress_of(index).unwrap_or(Stress::Unstressed);
+        let (length, level) = match (marked, stress) {
+          (false, _) => (1.0, 1.0),
+          (true, Stress::Primary) => (1.3, 1.0),
+          (true, Stress::Secondary) => (1.1, 0.85),
+          (true, Stress::Unstressed) => (0.75, 0.6),
+        };
+        // A diphthong needs time to move between its two qualities
+        let length = length
+          * if articulation.offglide.is_some() {
+            1.2
+          } else {
+            1.0
+          };
+        let long = if phoneme.contains('ː') { 1.6 } else { 1.0 };
+        for phase in &mut phases {
+          phase.ms *= length * long;
+          phase.voicing *= level;
+        }
+        let middle = (frames.len() as f32 + phases[0].ms / FRAME_MS / 2.0) * FRAME_MS;
+        match (marked, stress) {
+          (true, Stress::Primary) => accents.push((middle, 0.25)),
+          (true, Stress::Secondary) => accents.push((middle, 0.1)),
+          _ => {}
+        }
+      }
+
+      let start = frames.len();
+      let bandwidths = if articulation.manner == Manner::Nasal {
+        NASAL_BANDWIDTHS
+      } else {
+        ORAL_BANDWIDTHS
+      };
+      let phase_frames = |phase: &Phase| ((phase.ms / FRAME_MS).round() as usize).max(1);
+      let segment_frames: usize = phases.iter().map(phase_frames).sum();
+      for phase in &phases {
+        for _ in 0..phase_frames(phase) {
+          formant_tracks.push(glide(articulation, frames.len() - start, segment_frames));
+          frames.push(Frame {
+            bandwidths,
+            voicing: phase.voicing,
+            aspiration: phase.aspiration,
+            frication: phase.frication * articulation.noise.1,
+            noise_hz: articulation.noise.0,
+            ..Frame::default()
+          });
+        }
+      }
+      spans.push((start as f32 * FRAME_MS) as u32..(frames.len() as f32 * FRAME_MS) as u32);
+    }
+    frames.extend(vec![Frame::default(); pause]);
+    formant_tracks.extend(vec![None; pause]);
+
+    let formants = smooth(&fill_gaps(&formant_tracks));
+    let total_ms = frames.len() as f32 * FRAME_MS;
+    for (t, frame) in frames.iter_mut().enumerate() {
+      let ms = t as f32 * FRAME_MS;
+      // Pitch drifts down over the word, with a rise on each stressed vowel
+      let accent: f32 = accents
+        .iter()
+        .map(|(centre, height)| height * (-((ms - centre) / 60.0).powi(2)).exp())
+        .sum();
+      frame.f0 = self.f0 * (1.05 - 0.2 * ms / total_ms + accent);
+      frame.formants = formants[t];
+    }
+
+    Ok((frames, spans))
+  }
+
+  fn articulation(&self, phoneme: &str) -> Result<Articulation> {
+    if ipa::is_vowel(phoneme) {
+      let missing = || anyhow!("No formants for vowel '{}'", phoneme);
+      // A diphthong starts at its onset, or at the chart's measurement of the whole diphthong
+      let (onset, offglide) = match ipa::diphthong_halves(phoneme) {
+        Some((onset, offglide)) => (
+          self
+            .chart_formants(phoneme)
+            .or_else(|| self.vowel_formants(onset)),
+          Some(self.vowel_formants(offglide).ok_or_else(missing)?),
+        ),
+        None => (self.vowel_formants(phoneme), None),
+      };
+      return Ok(Articulation {
+        offglide,
+        ..Articulation::new(Manner::Vowel, true, onset.ok_or_else(missing)?, (0.0, 0.0))
+      });
+    }
+
+    consonant(phoneme).ok_or_else(|| anyhow!("Cannot synthesize '{}'", phoneme))
+  }
+
+  /// Formants of a single vowel, from the dialect's chart or else the IPA table
+  fn vowel_formants(&self, vowel: &str) -> Option<[f32; 3]> {
+    let mut formants = self
+      .chart_formants(vowel)
+      .or_else(|| ipa_vowel_formants(vowel))?;
+    if vowel.contains('˞') {
+      formants[2] = formants[2].min(1700.0);
+    }
+    Some(formants)
+  }
+
+  fn chart_formants(&self, vowel: &str) -> Option<[f32; 3]> {
+    let bare: String = vowel
+      .chars()
+      .filter(|&c| c != 'ː' && c != 'ˑ' && !ipa::is_combining(c))
+      .collect();
+    self
+      .vowels
+      .iter()
+      .find(|v| v.phoneme == bare)
+      .map(|v| [v.f1, v.f2, v.f3])
+  }
+}
+
+/// Formant target for frame `k` of `n` in a segment: diphthongs hold their onset for the first
+/// quarter, then move steadily to the offglide
+fn glide(articulation: &Articulation, k: usize, n: usize) -> Option<[f32; 3]> {
+  let onset = articulation.formants?;
+  let Some(offglide) = articulation.offglide else {
+    return Some(onset);
+  };
+  let progress = ((k as f32 / n.saturating_sub(1).max(1) as f32 - 0.25) / 0.75).clamp(0.0, 1.0);
+  let mut formants = onset;
+  for (f, target) in formants.iter_mut().zip(offglide) {
+    *f += (target - *f) * progress;
+  }
+  Some(formants)
+}
+
+/// Source timeline of a segment at its unstressed length
+fn phases(articulation: &Articulation, aspirated: bool) -> Vec<Phase> {
+  let voicing = if articulation.voiced { 1.0 } else { 0.0 };
+  match articulation.manner {
+    Manner::Vowel => vec![Phase::new(120.0, 1.0, 0.0, 0.0)],
+    Manner::Approximant => vec![Phase::new(70.0, 0.7 * voicing, 0.2 * (1.0 - voicing), 0.0)],
+    Manner::Nasal => vec![Phase::new(70.0, 0.5, 0.0, 0.0)],
+    Manner::Tap => vec![Phase::new(25.0, 0.3, 0.0, 0.0)],
+    Manner::Fricative => vec![Phase::new(
+      if articulation.voiced { 80.0 } else { 110.0 },
+      0.4 * voicing,
+      0.0,
+      1.0,
+    )],
+    Manner::Stop => {
+      let mut phases = vec![
+        Phase::new(
+          if articulation.voiced { 50.0 } else { 70.0 },
+          0.1 * voicing,
+          0.0,
+          0.0,
+        ),
+        Phase::new(10.0, 0.3 * voicing, 0.0, 1.0),
+      ];
+      if aspirated && !articulation.voiced {
+        phases.push(Phase::new(35.0, 0.0, 0.5, 0.0));
+      }
+      phases
+    }
+    Manner::Affricate => vec![
+      Phase::new(50.0, 0.1 * voicing, 0.0, 0.0),
+      Phase::new(90.0, 0.4 * voicing, 0.0, 1.0),
+    ],
+    Manner::Aspirate => vec![Phase::new(70.0, 0.3 * voicing, 0.6, 0.0)],
+    Manner::GlottalStop => vec![Phase::new(50.0, 0.0, 0.0, 0.0)],
+  }
+}
+
+/// Give frames without their own formants (pauses, h, ʔ) those of the nearest following
+/// segment, or failing that the nearest preceding one
+fn fill_gaps(tracks: &[Option<[f32; 3]>]) -> Vec<[f32; 3]> {
+  let schwa = [500.0, 1500.0, 2500.0];
+  let mut filled = vec![schwa; tracks.len()];
+  let mut next = tracks
+    .iter()
+    .rev()
+    .flatten()
+    .next()
+    .copied()
+    .unwrap_or(schwa);
+  for t in (0..tracks.len()).rev() {
+    if let Some(formants) = tracks[t] {
+      next = formants;
+    }
+    filled[t] = next;
+  }
+  let mut previous: Option<[f32; 3]> = None;
+  for t in 0..tracks.len() {
+    match tracks[t] {
+      Some(formants) => previous = Some(formants),
+      None if tracks[t..].iter().all(Option::is_none) => {
+        if let Some(formants) = previous {
+          filled[t] = formants;
+        }
+      }
+      None => {}
+    }
+  }
+  filled
+}
+
+/// Centred moving average of the formant tracks
+fn smooth(tracks: &[[f32; 3]]) -> Vec<[f32; 3]> {
+  let half = (TRANSITION_MS / FRAME_MS / 2.0) as usize;
+  (0..tracks.len())
+    .map(|t| {
+      let window = &tracks[t.saturating_sub(half)..(t + half + 1).min(tracks.len())];
+      let mut mean = [0.0; 3];
+      for formants in window {
+        for (m, f) in mean.iter_mut().zip(formants) {
+          *m += f / window.len() as f32;
+        }
+      }
+      mean
+    })
+    .collect()
+}
+
+/// Two-pole resonator, y[n] = a x[n] + b y[n-1] + c y[n-2]
+#[derive(Debug, Clone, Copy, Default)]
+struct Resonator {
+  y1: f32,
+  y2: f32,
+}
+
+impl Resonator {
+  /// Filter one sample; `unity_peak` scales the gain to 1 at the centre frequency rather than at
+  /// DC
+  fn process(&mut self, x: f32, freq: f32, bandwidth: f32, unity_peak: bool) -> f32 {
+    let period = 1.0 / SAMPLE_RATE as f32;
+    let c = -(-2.0 * PI * bandwidth * period).exp();
+    let b = 2.0 * (-PI * bandwidth * period).exp() * (2.0 * PI * freq * period).cos();
+    let a = if unity_peak {
+      // |1 - b e^-jw - c e^-2jw| at the centre frequency
+      let w = 2.0 * PI * freq * period;
+      let re = 1.0 - b * w.cos() - c * (2.0 * w).cos();
+      let im = b * w.sin() + c * (2.0 * w).sin();
+      (re * re + im * im).sqrt()
+    } else {
+      1.0 - b - c
+    };
+    let y = a * x + b * self.y1 + c * self.y2;
+    self.y2 = self.y1;
+    self.y1 = y;
+    y
+  }
+}
+
+/// Run the sources through the vocal tract, interpolating parameters between frames
+fn render(frames: &[Frame]) -> Vec<f32> {
+  let frame_len = (FRAME_MS * SAMPLE_RATE as f32 / 1000.0) as usize;
+  let mut audio = Vec::with_capacity(frames.len() * frame_len);
+  let mut tract = [Resonator::default(); 4];
+  let mut frication_filter = Resonator::default();
+  let (mut phase, mut previous_flow) = (0.0f32, 0.0f32);
+  // Deterministic noise, so the same transcription always renders the same clip
+  let mut state = 0x2545_f491u32;
+
+  for (t, frame) in frames.iter().enumerate() {
+    let next = frames.get(t + 1).unwrap_or(frame);
+    for n in 0..frame_len {
+      let w = n as f32 / frame_len as f32;
+      let lerp = |a: f32, b: f32| a + (b - a) * w;
+
+      // Rosenberg glottal pulse; its derivative includes the lip radiation
+      phase = (phase + lerp(frame.f0, next.f0) / SAMPLE_RATE as f32).fract();
+      let flow = if phase < 0.4 {
+        0.5 * (1.0 - (PI * phase / 0.4).cos())
+      } else if phase < 0.6 {
+        (PI * (phase - 0.4) / 0.4).cos()
+      } else {
+        0.0
+      };
+      let voice = (flow - previous_flow) * lerp(frame.voicing, next.voicing);
+      previous_flow = flow;
+
+      state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+      let noise = (state >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+
+      let mut sample = voice + ASPIRATION_GAIN * noise * lerp(frame.aspiration, next.aspiration);
+      for (k, resonator) in tract.iter_mut().enumerate() {
+        let (freq, bandwidth) = match k {
+          3 => F4,
+          _ => (
+            lerp(frame.formants[k], next.formants[k]),
+            lerp(frame.bandwidths[k], next.bandwidths[k]),
+          ),
+        };
+        sample = resonator.process(sample, freq, bandwidth, false);
+      }
+
+      let frication = lerp(frame.frication, next.frication);
+      let noise_hz = if frame.noise_hz > 0.0 {
+        frame.noise_hz
+      } else {
+        next.noise_hz
+      };
+      if noise_hz > 0.0 {
+        sample += FRICATION_GAIN
+          * frication
+          * frication_filter.process(noise, noise_hz, noise_hz * 0.3, true);
+      }
+      audio.push(sample);
+    }
+  }
+
+  let peak = audio.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+  if peak > f32::EPSILON {
+    for sample in &mut audio {
+      *sample *= PEAK / peak;
+    }
+  }
+  audio
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::{
+    FormantTracker, PhonemeScore, PronunciationScorer, StressAnalyzer,
+  };
+
+  fn rms(audio: &[f32]) -> f32 {
+    (audio.iter().map(|s| s * s).sum::<f32>() / audio.len().max(1) as f32).sqrt()
+  }
+
+  fn slice<'a>(audio: &'a [f32], span: &Range<u32>) -> &'a [f32] {
+    let per_ms = SAMPLE_RATE as usize / 1000;
+    &audio[span.start as usize * per_ms..span.end as usize * per_ms]
+  }
+
+  fn synthesize(ipa: &str) -> Synthesis {
+    FormantSynthesizer::new()
+      .synthesize(&Transcription::parse(ipa))
+      .unwrap()
+  }
+
+  #[test]
+  fn test_renders_every_phoneme_in_order() {
+    let word = synthesize("ˈsɑtʃəfhɑmi");
+    assert_eq!(word.spans.len(), 9);
+    assert_eq!(word.spans[0].start, PAUSE_MS as u32);
+    assert_eq!(
+      FormantSynthesizer::new()
+        .duration_ms(&Transcription::parse("ˈsɑtʃəfhɑmi"))
+        .unwrap(),
+      word.spans[8].end - word.spans[0].start
+    );
+    assert!(word.spans.windows(2).all(|w| w[0].end == w[1].start));
+    let peak = word.audio.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!((peak - PEAK).abs() < 1e-4);
+
+    // Deterministic, and the pauses are silent
+    assert_eq!(word.audio, synthesize("ˈsɑtʃəfhɑmi").audio);
+    assert!(rms(&word.audio[..800]) < 1e-3);
+
+    // The stressed vowel is louder than its consonants and the unstressed vowels
+    let level = |i: usize| rms(slice(&word.audio, &word.spans[i]));
+    assert!(level(1) > 2.0 * level(2), "{} {}", level(1), level(2));
+    assert!(level(1) > 1.5 * level(7));
+    assert!(level(0) > 3.0 * level(5), "s is stronger than f");
+  }
+
+  #[test]
+  fn test_vowel_formants_match_targets() {
+    let tracker = FormantTracker::default();
+    for (ipa, [f1, f2, _]) in [
+      ("ɑ", [770.0, 1300.0, 2550.0]),
+      ("i", [280.0, 2250.0, 2900.0]),
+      ("u", [320.0, 900.0, 2300.0]),
+    ] {
+      let word = synthesize(&format!("ˈbɑ{}ː", ipa));
+      let measured = tracker.measure(slice(&word.audio, &word.spans[2])).unwrap();
+      assert!(
+        (measured.f1 / f1 - 1.0).abs() < 0.15,
+        "{} {:?}",
+        ipa,
+        measured
+      );
+      assert!(
+        (measured.f2 / f2 - 1.0).abs() < 0.15,
+        "{} {:?}",
+        ipa,
+        measured
+      );
+    }
+
+    // Dialects with a measured chart use it
+    let ga = FormantSynthesizer::for_dialect(&Dialect::GA);
+    assert_eq!(
+      ga.articulation("ɪː").unwrap().formants,
+      Some([427.0, 2034.0, 2684.0])
+    );
+    assert_eq!(
+      FormantSynthesizer::new()
+        .articulation("ɪ")
+        .unwrap()
+        .formants,
+      Some([400.0, 2000.0, 2600.0])
+    );
+  }
+
+  #[test]
+  fn test_diphthong_glides_within_one_span() {
+    let tracker = FormantTracker::default();
+    let word = synthesize("ˈnaɪm");
+    assert_eq!(word.spans.len(), 3);
+    let span = &word.spans[1];
+    let third = (span.end - span.start) / 3;
+    let early = tracker
+      .measure(slice(&word.audio, &(span.start..span.start + third)))
+      .unwrap();
+    let late = tracker
+      .measure(slice(&word.audio, &(span.end - third..span.end)))
+      .unwrap();
+    assert!(early.f1 > late.f1 + 150.0, "{:?} {:?}", early, late);
+    assert!(late.f2 > early.f2 + 300.0, "{:?} {:?}", early, late);
+
+    // A diphthong is held longer than its onset alone
+    let monophthong = synthesize("ˈnam");
+    let length = |r: &Range<u32>| r.end - r.start;
+    assert!(length(span) > length(&monophthong.spans[1]));
+  }
+
+  #[test]
+  fn test_stress_is_audible() {
+    let analyzer = StressAnalyzer::new(SAMPLE_RATE);
+    for ipa in ["ˈbɑbɑ", "bɑˈbɑ", "ˈbɑnənə", "bəˈnɑnə"] {
+      let target = Transcription::parse(ipa);
+      let word = FormantSynthesizer::new().synthesize(&target).unwrap();
+      let per_phoneme: Vec<PhonemeScore> = target
+        .phonemes
+        .iter()
+        .zip(&word.spans)
+        .map(|(phoneme, span)| PhonemeScore {
+          phoneme: phoneme.clone(),
+          start_ms: span.start,
+          end_ms: span.end,
+          score: 100.0,
+          ..Default::default()
+        })
+        .collect();
+      let stress = analyzer
+        .analyze(&word.audio, &per_phoneme, &target)
+        .unwrap();
+      assert_eq!(
+        stress.detected_syllable, stress.expected_syllable,
+        "{}",
+        ipa
+      );
+    }
+  }
+
+  #[test]
+  fn test_usable_as_scoring_reference() {
+    let mut scorer = PronunciationScorer::new();
+    let reference = synthesize("ˈkæt").audio;
+    let learner = |ipa: &str| {
+      FormantSynthesizer::new()
+        .with_pitch(100.0)
+        .synthesize(&Transcription::parse(ipa))
+        .unwrap()
+        .audio
+    };
+
+    let target = Transcription::parse("ˈkæt");
+    let right = scorer
+      .score_pronunciation(&learner("ˈkæt"), &reference, &target)
+      .unwrap();
+    let wrong = scorer
+      .score_pronunciation(&learner("ˈkut"), &reference, &target)
+      .unwrap();
+    assert!(
+      right.overall_pct > wrong.overall_pct + 5.0,
+      "{} {}",
+      right.overall_pct,
+      wrong.overall_pct
+    );
+    assert!(right.per_phoneme[1].score > wrong.per_phoneme[1].score);
+  }
+
+  #[test]
+  fn test_unknown_segments_are_errors() {
+    let synth = FormantSynthesizer::new();
+    assert!(synth.synthesize(&Transcription::parse("ǃa")).is_err());
+    assert!(synth.synthesize(&Transcription::default()).is_err());
+    // Ties, length and secondary articulation are understood
+    assert!(synth.synthesize(&Transcription::parse("ˈt͡ʃɪnːʷ")).is_ok());
+    // Every consonant of the articulation table can be rendered
+    assert!(synth.synthesize(&Transcription::parse("ˈʟɬɴʙⱱa")).is_ok());
+  }
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+
+use super::SAMPLE_RATE;
+
+/// Voice activity detection parameters
+#[derive(Debug, Clone)]
+pub struct VadConfig {
+  pub sample_rate: u32,
+  pub frame_ms: f32,
+  pub hop_ms: f32,
+  /// Voiced frames must exceed the noise floor by this much
+  pub speech_margin_db: f32,
+  /// ...and be within this much of the loudest frame
+  pub dynamic_range_db: f32,
+  /// Weak frames next to speech (fricatives, releases) must exceed the noise floor by this much
+  pub weak_margin_db: f32,
+  /// Zero-crossing rate above which a frame is treated as frication rather than voicing
+  pub fricative_zcr: f32,
+  /// Voiced runs shorter than this are treated as clicks
+  pub min_speech_ms: f32,
+  /// How far speech may be extended into weak or fricative frames on each side
+  pub max_extension_ms: f32,
+  /// Margin kept around the detected speech
+  pub padding_ms: f32,
+}
+
+impl Default for VadConfig {
+  fn default() -> Self {
+    Self {
+      sample_rate: SAMPLE_RATE,
+      frame_ms: 20.0,
+      hop_ms: 10.0,
+      speech_margin_db: 12.0,
+      dynamic_range_db: 35.0,
+      weak_margin_db: 6.0,
+      fricative_zcr: 0.25,
+      min_speech_ms: 50.0,
+      max_extension_ms: 150.0,
+      padding_ms: 30.0,
+    }
+  }
+}
+
+/// Where speech was detected in a recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpeechSegment {
+  pub start_ms: u32,
+  pub end_ms: u32,
+  #[serde(skip)]
+  pub start_sample: usize,
+  #[serde(skip)]
+  pub end_sample: usize,
+}
+
+/// Per-frame measurements used by the detector
+struct FrameStats {
+  energy_db: f32,
+  zcr: f32,
+}
+
+/// Energy and zero-crossing voice activity detector
+pub struct VoiceActivityDetector {
+  config: VadConfig,
+}
+
+impl Default for VoiceActivityDetector {
+  fn default() -> Self {
+    Self::new(VadConfig::default())
+  }
+}
+
+impl VoiceActivityDetector {
+  pub fn new(config: VadConfig) -> Self {
+    Self { config }
+  }
+
+  /// Find the span from the first to the last speech frame, or `None` if nothing sounds like speech.
+  ///
+  /// Isolated breaths and clicks are ignored: weak or noisy frames only count when they are
+  /// contiguous with voiced speech.
+  pub fn detect(&self, audio: &[f32]) -> Option<SpeechSegment> {
+    let frame_len = self.samples(self.config.frame_ms).max(1);
+    let hop = self.samples(self.config.hop_ms).max(1);
+    let stats = self.frame_stats(audio, frame_len, hop);
+    if stats.is_empty() {
+      return None;
+    }
+
+    let mut sorted: Vec<f32> = stats.iter().map(|s| s.energy_db).collect();
+    sorted.sort_by(f32::total_cmp);
+    let noise_floor = sorted[sorted.len() / 10];
+    let loudest = sorted[sorted.len() - 1];
+
+    let voiced_threshold =
+      (noise_floor + self.config.speech_margin_db).max(loudest - self.config.dynamic_range_db);
+    let weak_threshold = noise_floor + self.config.weak_margin_db;
+
+    // Breaths and hiss are loud but aperiodic; only low zero-crossing frames anchor speech
+    let voiced: Vec<bool> = stats
+      .iter()
+      .map(|s| s.energy_db > voiced_threshold && s.zcr < self.config.fricative_zcr)
+      .collect();
+    let min_run = (self.config.min_speech_ms / self.config.hop_ms).ceil() as usize;
+    let runs = runs_of(&voiced, min_run.max(1));
+    let (first, last) = (runs.first()?.0, runs.last()?.1);
+
+    // Grow outwards over weak speech and frication, stopping at the first silent frame
+    let is_weak = |s: &FrameStats| {
+      s.energy_db > weak_threshold
+        || (s.zcr > self.config.fricative_zcr && s.energy_db > noise_floor + 3.0)
+    };
+    let max_extension = (self.config.max_extension_ms / self.config.hop_ms) as usize;
+    let mut start = first;
+    while start > 0 && first - start < max_extension && is_weak(&stats[start - 1]) {
+      start -= 1;
+    }
+    let mut end = last;
+    while end + 1 < stats.len() && end - last < max_extension && is_weak(&stats[end + 1]) {
+      end += 1;
+    }
+
+    let padding = self.samples(self.config.padding_ms);
+    let start_sample = (start * hop).saturating_sub(padding);
+    let end_sample = (end * hop + frame_len + padding).min(audio.len());
+
+    Some(SpeechSegment {
+      start_ms: self.millis(start_sample),
+      end_ms: self.millis(end_sample),
+      start_sample,
+      end_sample,
+    })
+  }
+
+  /// Trim leading and trailing silence; audio without detectable speech is returned unchanged
+  pub fn trim<'a>(&self, audio: &'a [f32]) -> (&'a [f32], Option<SpeechSegment>) {
+    match self.detect(audio) {
+      Some(segment) => (
+        &audio[segment.start_sample..segment.end_sample],
+        Some(segment),
+      ),
+      None => (audio, None),
+    }
+  }
+
+  fn frame_stats(&self, audio: &[f32], frame_len: usize, hop: usize) -> Vec<FrameStats> {
+    if audio.len() < frame_len {
+      return Vec::new();
+    }
+
+    (0..=audio.len() - frame_len)
+      .step_by(hop)
+      .map(|start| {
+        let frame = &audio[start..start + frame_len];
+        let power = frame.iter().map(|s| s * s).sum::<f32>() / frame_len as f32;
+        let crossings = frame
+          .windows(2)
+          .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+          .count();
+        FrameStats {
+          energy_db: 10.0 * power.max(1e-12).log10(),
+          zcr: crossings as f32 / frame_len as f32,
+        }
+      })
+      .collect()
+  }
+
+  fn samples(&self, ms: f32) -> usize {
+    (ms * self.config.sample_rate as f32 / 1000.0).round() as usize
+  }
+
+  fn millis(&self, samples: usize) -> u32 {
+    (samples as f64 * 1000.0 / self.config.sample_rate as f64).round() as u32
+  }
+}
+
+/// Inclusive index ranges of `true` runs at least `min_len` long
+fn runs_of(flags: &[bool], min_len: usize) -> Vec<(usize, usize)> {
+  let mut runs = Vec::new();
+  let mut start = None;
+
+  for (i, &flag) in flags.iter().chain(std::iter::once(&false)).enumerate() {
+    match (flag, start) {
+      (true, None) => start = Some(i),
+      (false, Some(s)) => {
+        if i - s >= min_len {
+          runs.push((s, i - 1));
+        }
+        start = None;
+      }
+      _ => {}
+    }
+  }
+
+  runs
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::scoring::signals::noise;
+
+  fn tone(len: usize, freq: f32, amplitude: f32) -> Vec<f32> {
+    (0..len)
+      .map(|n| amplitude * (2.0 * std::f32::consts::PI * freq * n as f32 / 16000.0).sin())
+      .collect()
+  }
+
+  fn mix(base: &mut [f32], offset: usize, signal: &[f32]) {
+    for (b, s) in base[offset..].iter_mut().zip(signal) {
+      *b += s;
+    }
+  }
+
+  #[test]
+  fn test_detects_speech_between_pauses() {
+    let mut audio = noise(24000, 0.001, 1);
+    mix(&mut audio, 8000, &tone(8000, 220.0, 0.4));
+
+    let vad = VoiceActivityDetector::default();
+    let segment = vad.detect(&audio).unwrap();
+    assert!((segment.start_ms as i32 - 500).abs() <= 40, "{:?}", segment);
+    assert!((segment.end_ms as i32 - 1000).abs() <= 40, "{:?}", segment);
+
+    let (trimmed, _) = vad.trim(&audio);
+    assert!(trimmed.len() < 10000);
+  }
+
+  #[test]
+  fn test_keeps_adjacent_fricative_but_drops_isolated_breath() {
+    let mut audio = noise(32000, 0.001, 2);
+    // Breath well before the word, separated by silence
+    mix(&mut audio, 1600, &noise(3200, 0.02, 3));
+    // /s/ running straight into a voiced vowel
+    mix(&mut audio, 12000, &noise(2400, 0.03, 4));
+    mix(&mut audio, 14400, &tone(6400, 180.0, 0.4));
+
+    let segment = VoiceActivityDetector::default().detect(&audio).unwrap();
+    assert!(
+      segment.start_ms >= 700 && segment.start_ms <= 760,
+      "{:?}",
+      segment
+    );
+    assert!((segment.end_ms as i32 - 1300).abs() <= 40, "{:?}", segment);
+  }
+
+  #[test]
+  fn test_silence_has_no_speech() {
+    let vad = VoiceActivityDetector::default();
+    assert!(vad.detect(&noise(16000, 0.001, 5)).is_none());
+    assert!(vad.detect(&[]).is_none());
+
+    let audio = noise(16000, 0.001, 6);
+    let (trimmed, segment) = vad.trim(&audio);
+    assert_eq!(trimmed.len(), audio.len());
+    assert!(segment.is_none());
+  }
+
+  #[test]
+  fn test_ignores_clicks() {
+    let mut audio = noise(16000, 0.001, 7);
+    mix(&mut audio, 4000, &tone(320, 1000.0, 0.5));
+    assert!(VoiceActivityDetector::default().detect(&audio).is_none());
+  }
+}
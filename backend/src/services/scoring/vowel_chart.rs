@@ -0,0 +1,145 @@
+use serde::Serialize;
+
+use super::formant::Formants;
+use crate::models::Dialect;
+use crate::services::ipa;
+
+/// Average formants of one vowel for a group of speakers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct VowelTarget {
+  pub phoneme: &'static str,
+  pub f1: f32,
+  pub f2: f32,
+  pub f3: f32,
+}
+
+/// Reference vowel positions for a dialect, for plotting learner measurements against
+#[derive(Debug, Clone, Serialize)]
+pub struct VowelChart {
+  pub dialect: Dialect,
+  pub source: &'static str,
+  /// Adult male speakers; lower formants overall
+  pub male: &'static [VowelTarget],
+  pub female: &'static [VowelTarget],
+}
+
+const fn vowel(phoneme: &'static str, f1: f32, f2: f32, f3: f32) -> VowelTarget {
+  VowelTarget {
+    phoneme,
+    f1,
+    f2,
+    f3,
+  }
+}
+
+const GA_SOURCE: &str = "Hillenbrand, Getty, Clark & Wheeler (1995), JASA 97(5)";
+
+const GA_MALE: &[VowelTarget] = &[
+  vowel("i", 342.0, 2322.0, 3000.0),
+  vowel("ɪ", 427.0, 2034.0, 2684.0),
+  vowel("eɪ", 476.0, 2089.0, 2691.0),
+  vowel("ɛ", 580.0, 1799.0, 2605.0),
+  vowel("æ", 588.0, 1952.0, 2601.0),
+  vowel("ɑ", 768.0, 1333.0, 2522.0),
+  vowel("ɔ", 652.0, 997.0, 2538.0),
+  vowel("oʊ", 497.0, 910.0, 2459.0),
+  vowel("ʊ", 469.0, 1122.0, 2434.0),
+  vowel("u", 378.0, 997.0, 2343.0),
+  vowel("ʌ", 623.0, 1200.0, 2550.0),
+  vowel("ɝ", 474.0, 1379.0, 1710.0),
+];
+
+const GA_FEMALE: &[VowelTarget] = &[
+  vowel("i", 437.0, 2761.0, 3372.0),
+  vowel("ɪ", 483.0, 2365.0, 3053.0),
+  vowel("eɪ", 536.0, 2530.0, 3047.0),
+  vowel("ɛ", 731.0, 2058.0, 2979.0),
+  vowel("æ", 669.0, 2349.0, 2972.0),
+  vowel("ɑ", 936.0, 1551.0, 2815.0),
+  vowel("ɔ", 781.0, 1136.0, 2824.0),
+  vowel("oʊ", 555.0, 1035.0, 2828.0),
+  vowel("ʊ", 519.0, 1225.0, 2827.0),
+  vowel("u", 459.0, 1105.0, 2735.0),
+  vowel("ʌ", 753.0, 1426.0, 2933.0),
+  vowel("ɝ", 523.0, 1588.0, 1929.0),
+];
+
+/// Average F1-F3 of adult male speakers for every IPA vowel, after Peterson & Barney (1952) and
+/// cardinal vowel measurements; used where the dialect has no measured vowel chart
+pub(crate) const IPA_VOWELS: &[(char, [f32; 3])] = &[
+  ('i', [280.0, 2250.0, 2900.0]),
+  ('y', [280.0, 1900.0, 2200.0]),
+  ('ɨ', [320.0, 1650.0, 2500.0]),
+  ('ʉ', [320.0, 1500.0, 2300.0]),
+  ('ɯ', [320.0, 1300.0, 2400.0]),
+  ('u', [320.0, 900.0, 2300.0]),
+  ('ɪ', [400.0, 2000.0, 2600.0]),
+  ('ʏ', [400.0, 1700.0, 2300.0]),
+  ('ʊ', [450.0, 1100.0, 2400.0]),
+  ('e', [400.0, 2100.0, 2700.0]),
+  ('ø', [400.0, 1600.0, 2300.0]),
+  ('ɘ', [450.0, 1600.0, 2500.0]),
+  ('ɵ', [450.0, 1300.0, 2400.0]),
+  ('ɤ', [450.0, 1200.0, 2500.0]),
+  ('o', [450.0, 850.0, 2500.0]),
+  ('ə', [500.0, 1500.0, 2500.0]),
+  ('ɛ', [580.0, 1800.0, 2600.0]),
+  ('œ', [580.0, 1500.0, 2400.0]),
+  ('ɜ', [560.0, 1450.0, 2500.0]),
+  ('ɞ', [560.0, 1250.0, 2400.0]),
+  ('ʌ', [620.0, 1200.0, 2550.0]),
+  ('ɔ', [650.0, 1000.0, 2550.0]),
+  ('æ', [660.0, 1800.0, 2600.0]),
+  ('ɐ', [680.0, 1350.0, 2500.0]),
+  ('a', [750.0, 1500.0, 2600.0]),
+  ('ɶ', [750.0, 1300.0, 2500.0]),
+  ('ɑ', [770.0, 1300.0, 2550.0]),
+  ('ɒ', [700.0, 1000.0, 2500.0]),
+  ('ɚ', [500.0, 1400.0, 1700.0]),
+  ('ɝ', [480.0, 1380.0, 1700.0]),
+];
+
+/// Published vowel space for a dialect; only dialects with a measured reference set are covered
+pub fn vowel_chart(dialect: &Dialect) -> Option<VowelChart> {
+  match dialect {
+    Dialect::GA => Some(VowelChart {
+      dialect: Dialect::GA,
+      source: GA_SOURCE,
+      male: GA_MALE,
+      female: GA_FEMALE,
+    }),
+    _ => None,
+  }
+}
+
+/// Table formants of a vowel, ignoring length and other marks. A diphthong gets the midpoint of
+/// its onset and offglide, which is what a measurement over the whole segment averages to.
+pub(crate) fn ipa_vowel_formants(phoneme: &str) -> Option<[f32; 3]> {
+  if let Some((onset, offglide)) = ipa::diphthong_halves(phoneme) {
+    let (onset, offglide) = (ipa_vowel_formants(onset)?, ipa_vowel_formants(offglide)?);
+    return Some([0, 1, 2].map(|k| (onset[k] + offglide[k]) / 2.0));
+  }
+  let first = phoneme.chars().next()?;
+  IPA_VOWELS
+    .iter()
+    .find(|(c, _)| *c == first)
+    .map(|(_, f)| *f)
+}
+
+/// Vowel in the IPA table closest to measured formants, on a log scale. F3 counts for half, as
+/// it mainly separates the rhotic vowels.
+pub fn nearest_vowel(formants: &Formants) -> char {
+  let measured = [formants.f1, formants.f2, formants.f3];
+  let distance = |table: &[f32; 3]| {
+    measured
+      .iter()
+      .zip(table)
+      .zip([1.0, 1.0, 0.5])
+      .map(|((m, t), weight)| weight * (m.max(1.0) / t).ln().powi(2))
+      .sum::<f32>()
+  };
+  IPA_VOWELS
+    .iter()
+    .min_by(|a, b| distance(&a.1).total_cmp(&distance(&b.1)))
+    .map_or('ə', |(c, _)| *c)
+}
@@ -1 +1,527 @@
 // Vocabulary service for managing words, phonemes, and search
+
+use anyhow::Result;
+use sqlx::postgres::{PgArguments, Postgres};
+use sqlx::query::Query;
+use sqlx::PgConnection;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::models::{
+  CreateDialectVariant, CreateReferenceRecording, CreateReferenceSpeaker, Dialect, DialectVariant,
+  Phoneme, ReferenceRecording, ReferenceSpeaker, SimilarWord, Word,
+};
+use crate::services::articulation::{self, ArticulatoryFeatures};
+use crate::services::ipa::Transcription;
+
+/// Words whose phoneme count differs from a word's by more than this are not compared when
+/// looking for similar words; each extra phoneme already costs a whole insertion
+pub const SIMILAR_LENGTH_DIFFERENCE: i32 = 2;
+
+/// Add a dialect variant to a word, storing its transcription's phonemes with it. Its audio, if
+/// any, becomes a reference recording by its speaker, so learners are scored against it.
+pub async fn create_variant(
+  conn: &mut PgConnection,
+  word_id: Uuid,
+  variant: &CreateDialectVariant,
+) -> Result<(DialectVariant, Vec<Phoneme>)> {
+  let created = sqlx::query_as::<_, DialectVariant>(
+    "INSERT INTO dialect_variants (word_id, dialect, ipa, audio_url, video_url)
+           VALUES ($1, $2, $3, $4, $5)
+           RETURNING *",
+  )
+  .bind(word_id)
+  .bind(variant.dialect)
+  .bind(&variant.ipa)
+  .bind(&variant.audio_url)
+  .bind(&variant.video_url)
+  .fetch_one(&mut *conn)
+  .await?;
+  let phonemes = store_phonemes(conn, &created).await?;
+
+  if let Some(audio_url) = &variant.audio_url {
+    let speaker_id = match variant.speaker_id {
+      Some(speaker_id) => speaker_id,
+      None => unattributed_speaker(conn).await?,
+    };
+    let recording = CreateReferenceRecording {
+      speaker_id,
+      audio_url: audio_url.clone(),
+      weight: None,
+    };
+    add_reference_recording(conn, created.id, &recording).await?;
+  }
+  Ok((created, phonemes))
+}
+
+/// Record a new reference speaker
+pub async fn create_speaker(
+  pool: &DbPool,
+  speaker: &CreateReferenceSpeaker,
+) -> Result<ReferenceSpeaker> {
+  let created = sqlx::query_as::<_, ReferenceSpeaker>(
+    "INSERT INTO reference_speakers (name, gender, birth_year, region, notes)
+           VALUES ($1, $2, $3, $4, $5)
+           RETURNING *",
+  )
+  .bind(&speaker.name)
+  .bind(&speaker.gender)
+  .bind(speaker.birth_year)
+  .bind(&speaker.region)
+  .bind(&speaker.notes)
+  .fetch_one(pool)
+  .await?;
+  Ok(created)
+}
+
+/// Add a speaker's recording of a dialect variant. Each speaker records a variant once.
+pub async fn add_reference_recording(
+  conn: &mut PgConnection,
+  variant_id: Uuid,
+  recording: &CreateReferenceRecording,
+) -> Result<ReferenceRecording> {
+  let created = sqlx::query_as::<_, ReferenceRecording>(
+    "INSERT INTO reference_recordings (variant_id, speaker_id, audio_url, weight)
+           VALUES ($1, $2, $3, COALESCE($4, 1))
+           RETURNING *",
+  )
+  .bind(variant_id)
+  .bind(recording.speaker_id)
+  .bind(&recording.audio_url)
+  .bind(recording.weight)
+  .fetch_one(conn)
+  .await?;
+  Ok(created)
+}
+
+/// The speaker that variant audio of unknown origin is attributed to, as the existing clips
+/// were when reference speakers were introduced
+async fn unattributed_speaker(conn: &mut PgConnection) -> Result<Uuid> {
+  let existing: Option<Uuid> = sqlx::query_scalar(
+    "SELECT id FROM reference_speakers WHERE name = 'Unattributed' ORDER BY created_at LIMIT 1",
+  )
+  .fetch_optional(&mut *conn)
+  .await?;
+  if let Some(id) = existing {
+    return Ok(id);
+  }
+  let id = sqlx::query_scalar(
+    "INSERT INTO reference_speakers (name, notes)
+           VALUES ('Unattributed', 'Original dialect variant audio')
+           RETURNING id",
+  )
+  .fetch_one(conn)
+  .await?;
+  Ok(id)
+}
+
+/// Change a variant's transcription and store the phonemes of the new one
+pub async fn update_variant_ipa(
+  pool: &DbPool,
+  variant_id: Uuid,
+  ipa: &str,
+) -> Result<(DialectVariant, Vec<Phoneme>)> {
+  let mut tx = pool.begin().await?;
+  let variant = sqlx::query_as::<_, DialectVariant>(
+    "UPDATE dialect_variants SET ipa = $2 WHERE id = $1 RETURNING *",
+  )
+  .bind(variant_id)
+  .bind(ipa)
+  .fetch_one(&mut *tx)
+  .await?;
+  let phonemes = store_phonemes(&mut tx, &variant).await?;
+  tx.commit().await?;
+  Ok((variant, phonemes))
+}
+
+/// Split a variant's transcription and store its phonemes in `word_phonemes` in order, replacing
+/// those of an earlier transcription. Symbols not yet in the inventory are added to it.
+pub async fn store_phonemes(
+  conn: &mut PgConnection,
+  variant: &DialectVariant,
+) -> Result<Vec<Phoneme>> {
+  let symbols = Transcription::parse(&variant.ipa).phonemes;
+  sqlx::query("DELETE FROM word_phonemes WHERE variant_id = $1")
+    .bind(variant.id)
+    .execute(&mut *conn)
+    .await?;
+
+  let mut phonemes = Vec::with_capacity(symbols.len());
+  for (order_index, symbol) in symbols.iter().enumerate() {
+    let features = articulation::features(symbol).unwrap_or_default();
+    bind_features(
+      sqlx::query(
+        "INSERT INTO phonemes (symbol, voiced, place, manner, height, backness, rounded, tense)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+               ON CONFLICT (symbol) DO NOTHING",
+      )
+      .bind(symbol),
+      &features,
+    )
+    .execute(&mut *conn)
+    .await?;
+    let phoneme = sqlx::query_as::<_, Phoneme>("SELECT * FROM phonemes WHERE symbol = $1")
+      .bind(symbol)
+      .fetch_one(&mut *conn)
+      .await?;
+    sqlx::query(
+      "INSERT INTO word_phonemes (word_id, variant_id, phoneme_id, order_index)
+             VALUES ($1, $2, $3, $4)",
+    )
+    .bind(variant.word_id)
+    .bind(variant.id)
+    .bind(phoneme.id)
+    .bind(order_index as i32)
+    .execute(&mut *conn)
+    .await?;
+    phonemes.push(phoneme);
+  }
+  Ok(phonemes)
+}
+
+/// Reference recordings to segment in the background: with `variant_id`, that variant's after its
+/// transcription changed; without, every recording not yet segmented. Recordings whose
+/// boundaries an editor set are left out, since alignment never replaces them.
+pub async fn references_to_segment(pool: &DbPool, variant_id: Option<Uuid>) -> Result<Vec<Uuid>> {
+  let ids = sqlx::query_scalar(
+    "SELECT id FROM reference_recordings
+           WHERE boundaries_source IS DISTINCT FROM 'editor'
+             AND CASE WHEN $1::uuid IS NULL THEN phoneme_boundaries IS NULL
+                      ELSE variant_id = $1 END
+           ORDER BY created_at",
+  )
+  .bind(variant_id)
+  .fetch_all(pool)
+  .await?;
+  Ok(ids)
+}
+
+/// Store phonemes for every variant without any, such as those created before they were stored
+/// or whose transcription was changed directly in the database
+pub async fn backfill_phonemes(pool: &DbPool) -> Result<usize> {
+  let variants = sqlx::query_as::<_, DialectVariant>(
+    "SELECT * FROM dialect_variants dv
+           WHERE NOT EXISTS (SELECT 1 FROM word_phonemes wp WHERE wp.variant_id = dv.id)",
+  )
+  .fetch_all(pool)
+  .await?;
+
+  let mut conn = pool.acquire().await?;
+  for variant in &variants {
+    store_phonemes(&mut conn, variant).await?;
+  }
+  Ok(variants.len())
+}
+
+/// Fill in the articulatory features of phonemes stored without any, such as those added before
+/// features were recorded. Symbols outside the IPA charts are left empty.
+pub async fn backfill_features(pool: &DbPool) -> Result<usize> {
+  let phonemes =
+    sqlx::query_as::<_, Phoneme>("SELECT * FROM phonemes WHERE manner IS NULL AND height IS NULL")
+      .fetch_all(pool)
+      .await?;
+
+  let mut filled = 0;
+  for phoneme in &phonemes {
+    let Some(features) = articulation::features(&phoneme.symbol) else {
+      continue;
+    };
+    bind_features(
+      sqlx::query(
+        "UPDATE phonemes
+               SET voiced = $2, place = $3, manner = $4, height = $5, backness = $6,
+                   rounded = $7, tense = $8
+               WHERE id = $1",
+      )
+      .bind(phoneme.id),
+      &features,
+    )
+    .execute(pool)
+    .await?;
+    filled += 1;
+  }
+  Ok(filled)
+}
+
+/// Words whose pronunciation in `dialect` is nearest to that of `word_id`, nearest first, by
+/// the feature-weighted edit distance between their stored phonemes. Only words within
+/// [`SIMILAR_LENGTH_DIFFERENCE`] phonemes of the word's length are compared.
+pub async fn similar_words(
+  pool: &DbPool,
+  word_id: Uuid,
+  dialect: Dialect,
+  limit: usize,
+) -> Result<Vec<SimilarWord>> {
+  let sequences = sqlx::query_as::<_, (Uuid, String, Vec<String>)>(
+    "WITH sequences AS (
+             SELECT dv.word_id, dv.ipa, array_agg(p.symbol ORDER BY wp.order_index) AS phonemes
+             FROM dialect_variants dv
+             JOIN word_phonemes wp ON wp.variant_id = dv.id
+             JOIN phonemes p ON p.id = wp.phoneme_id
+             WHERE dv.dialect = $2::dialect
+             GROUP BY dv.id
+           )
+           SELECT s.word_id, s.ipa, s.phonemes FROM sequences s
+           WHERE s.word_id = $1
+              OR EXISTS (SELECT 1 FROM sequences own
+                         WHERE own.word_id = $1
+                           AND abs(cardinality(own.phonemes) - cardinality(s.phonemes)) <= $3)",
+  )
+  .bind(word_id)
+  .bind(dialect)
+  .bind(SIMILAR_LENGTH_DIFFERENCE)
+  .fetch_all(pool)
+  .await?;
+  let (targets, others): (Vec<_>, Vec<_>) = sequences
+    .into_iter()
+    .partition(|(word, ..)| *word == word_id);
+  if targets.is_empty() {
+    return Ok(vec![]);
+  }
+
+  // Comparing against every candidate is CPU work; keep it off the async runtime
+  let nearest = tokio::task::spawn_blocking(move || {
+    // A word with several variants in the dialect is as close as its nearest one
+    let mut nearest: HashMap<Uuid, (String, f32)> = HashMap::new();
+    for (word, ipa, phonemes) in others {
+      let distance = targets
+        .iter()
+        .map(|(_, _, target)| articulation::sequence_distance(target, &phonemes))
+        .fold(f32::INFINITY, f32::min);
+      if nearest
+        .get(&word)
+        .is_none_or(|(_, closest)| distance < *closest)
+      {
+        nearest.insert(word, (ipa, distance));
+      }
+    }
+    let mut nearest: Vec<(Uuid, String, f32)> = nearest
+      .into_iter()
+      .map(|(word, (ipa, distance))| (word, ipa, distance))
+      .collect();
+    nearest.sort_by(|a, b| a.2.total_cmp(&b.2).then(a.0.cmp(&b.0)));
+    nearest.truncate(limit);
+    nearest
+  })
+  .await?;
+
+  let ids: Vec<Uuid> = nearest.iter().map(|(word, ..)| *word).collect();
+  let words = sqlx::query_as::<_, Word>("SELECT * FROM words WHERE id = ANY($1)")
+    .bind(&ids)
+    .fetch_all(pool)
+    .await?;
+  Ok(
+    nearest
+      .into_iter()
+      .filter_map(|(word_id, ipa, distance)| {
+        let word = words.iter().find(|w| w.id == word_id)?.clone();
+        Some(SimilarWord {
+          word,
+          ipa,
+          distance,
+        })
+      })
+      .collect(),
+  )
+}
+
+/// Find minimal pairs between words in the same dialect: transcriptions that differ in exactly
+/// one segment. With `word_id`, only that word's pairs are re-examined, as after it is added or
+/// its transcription changes; without, the whole vocabulary is. Discovered pairs that no longer
+/// hold are removed, those whose differing segment changed are corrected, and curated ones are
+/// left alone. Returns how many pairs were added or corrected.
+pub async fn discover_minimal_pairs(pool: &DbPool, word_id: Option<Uuid>) -> Result<usize> {
+  // Stored phoneme sequences, restricted for one word to the dialects and lengths it has
+  let sequences = sqlx::query_as::<_, (Uuid, Dialect, Vec<String>)>(
+    "SELECT dv.word_id, dv.dialect, array_agg(p.symbol ORDER BY wp.order_index)
+           FROM dialect_variants dv
+           JOIN word_phonemes wp ON wp.variant_id = dv.id
+           JOIN phonemes p ON p.id = wp.phoneme_id
+           WHERE $1::uuid IS NULL
+              OR dv.dialect IN (SELECT dialect FROM dialect_variants WHERE word_id = $1)
+           GROUP BY dv.id
+           HAVING $1::uuid IS NULL
+              OR count(*) IN (SELECT count(*) FROM word_phonemes WHERE word_id = $1
+                              GROUP BY variant_id)",
+  )
+  .bind(word_id)
+  .fetch_all(pool)
+  .await?;
+  let mut by_dialect: HashMap<Dialect, Vec<(Uuid, Vec<String>)>> = HashMap::new();
+  for (word, dialect, phonemes) in sequences {
+    by_dialect
+      .entry(dialect)
+      .or_default()
+      .push((word, phonemes));
+  }
+
+  // Word A is the lower id, so each pair has one spelling whichever word was found first
+  let mut pairs: Vec<(Uuid, Uuid, Dialect, String)> = Vec::new();
+  for (dialect, entries) in &by_dialect {
+    let found: Vec<(usize, usize, usize)> = match word_id {
+      // Only the word's own sequence needs comparing with the rest
+      Some(word_id) => entries
+        .iter()
+        .position(|(word, _)| *word == word_id)
+        .map(|own| {
+          (0..entries.len())
+            .filter_map(|other| {
+              let index = differing_segment(&entries[own].1, &entries[other].1)?;
+              Some((own, other, index))
+            })
+            .collect()
+        })
+        .unwrap_or_default(),
+      None => {
+        let sequences: Vec<&[String]> = entries.iter().map(|(_, p)| p.as_slice()).collect();
+        minimal_pairs(&sequences)
+      }
+    };
+    for (a, b, index) in found {
+      let (mut a, mut b) = (&entries[a], &entries[b]);
+      if a.0 == b.0 {
+        continue;
+      }
+      if b.0 < a.0 {
+        std::mem::swap(&mut a, &mut b);
+      }
+      let diff = format!("{}/{}", a.1[index], b.1[index]);
+      pairs.push((a.0, b.0, *dialect, diff));
+    }
+  }
+
+  let (a_ids, b_ids): (Vec<Uuid>, Vec<Uuid>) = pairs.iter().map(|(a, b, ..)| (*a, *b)).unzip();
+  let dialects: Vec<Dialect> = pairs.iter().map(|(_, _, dialect, _)| *dialect).collect();
+  let diffs: Vec<&str> = pairs.iter().map(|(.., diff)| diff.as_str()).collect();
+
+  // A pair an editor has curated, for any dialect, is never discovered alongside it
+  let mut tx = pool.begin().await?;
+  let changed = sqlx::query(
+    "INSERT INTO minimal_pairs (word_a_id, word_b_id, phoneme_diff, dialect, source)
+           SELECT a, b, diff, dialect, 'discovered'
+           FROM UNNEST($1::uuid[], $2::uuid[], $3::text[], $4::dialect[]) AS t(a, b, diff, dialect)
+           WHERE NOT EXISTS (
+             SELECT 1 FROM minimal_pairs curated
+             WHERE curated.source = 'editor'
+               AND LEAST(curated.word_a_id, curated.word_b_id) = t.a
+               AND GREATEST(curated.word_a_id, curated.word_b_id) = t.b
+           )
+           ON CONFLICT (LEAST(word_a_id, word_b_id), GREATEST(word_a_id, word_b_id), dialect)
+           DO UPDATE SET phoneme_diff = EXCLUDED.phoneme_diff
+           WHERE minimal_pairs.source = 'discovered'
+             AND minimal_pairs.phoneme_diff IS DISTINCT FROM EXCLUDED.phoneme_diff",
+  )
+  .bind(&a_ids)
+  .bind(&b_ids)
+  .bind(&diffs)
+  .bind(&dialects)
+  .execute(&mut *tx)
+  .await?
+  .rows_affected() as usize;
+
+  sqlx::query(
+    "DELETE FROM minimal_pairs discovered
+           WHERE source = 'discovered'
+             AND ($1::uuid IS NULL OR word_a_id = $1 OR word_b_id = $1)
+             AND ((LEAST(word_a_id, word_b_id), GREATEST(word_a_id, word_b_id), dialect)
+                    NOT IN (SELECT * FROM UNNEST($2::uuid[], $3::uuid[], $4::dialect[]))
+                  OR EXISTS (
+                    SELECT 1 FROM minimal_pairs curated
+                    WHERE curated.source = 'editor'
+                      AND LEAST(curated.word_a_id, curated.word_b_id)
+                          = LEAST(discovered.word_a_id, discovered.word_b_id)
+                      AND GREATEST(curated.word_a_id, curated.word_b_id)
+                          = GREATEST(discovered.word_a_id, discovered.word_b_id)
+                  ))",
+  )
+  .bind(word_id)
+  .bind(&a_ids)
+  .bind(&b_ids)
+  .bind(&dialects)
+  .execute(&mut *tx)
+  .await?;
+  tx.commit().await?;
+  Ok(changed)
+}
+
+/// Every pair of sequences of the same length that differ in exactly one segment, as the
+/// indices of both sequences and of the differing segment. Identical sequences are not pairs.
+pub fn minimal_pairs<S: AsRef<str>>(sequences: &[&[S]]) -> Vec<(usize, usize, usize)> {
+  // Sequences that match once the same position is blanked out differ at most there
+  let mut buckets: HashMap<(usize, Vec<&str>), Vec<usize>> = HashMap::new();
+  for (i, sequence) in sequences.iter().enumerate() {
+    for position in 0..sequence.len() {
+      let key = sequence
+        .iter()
+        .enumerate()
+        .map(|(p, s)| if p == position { "" } else { s.as_ref() })
+        .collect();
+      buckets.entry((position, key)).or_default().push(i);
+    }
+  }
+
+  let mut pairs = Vec::new();
+  for ((position, _), members) in &buckets {
+    for (n, &a) in members.iter().enumerate() {
+      for &b in &members[n + 1..] {
+        if sequences[a][*position].as_ref() != sequences[b][*position].as_ref() {
+          pairs.push((a, b, *position));
+        }
+      }
+    }
+  }
+  pairs.sort_unstable();
+  pairs
+}
+
+/// The only position at which two sequences of the same length differ, if there is exactly one
+fn differing_segment<S: AsRef<str>>(a: &[S], b: &[S]) -> Option<usize> {
+  if a.len() != b.len() {
+    return None;
+  }
+  let mut differing = (0..a.len()).filter(|&i| a[i].as_ref() != b[i].as_ref());
+  let index = differing.next()?;
+  differing.next().is_none().then_some(index)
+}
+
+/// Bind features, in column order, after a query's first parameter
+fn bind_features<'q>(
+  query: Query<'q, Postgres, PgArguments>,
+  features: &ArticulatoryFeatures,
+) -> Query<'q, Postgres, PgArguments> {
+  query
+    .bind(features.voiced)
+    .bind(features.place)
+    .bind(features.manner)
+    .bind(features.height)
+    .bind(features.backness)
+    .bind(features.rounded)
+    .bind(features.tense)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_minimal_pairs_differ_in_one_segment() {
+    let words = ["θɪŋk", "sɪŋk", "ʃɪp", "ʃiːp", "tʃɪp", "ʃɪps", "θɪŋk", "sɪŋ"];
+    let phonemes: Vec<Vec<String>> = words
+      .iter()
+      .map(|ipa| Transcription::parse(ipa).phonemes)
+      .collect();
+    let sequences: Vec<&[String]> = phonemes.iter().map(Vec::as_slice).collect();
+
+    assert_eq!(
+      minimal_pairs(&sequences),
+      vec![(0, 1, 0), (1, 6, 0), (2, 3, 1), (2, 4, 0)]
+    );
+
+    // Comparing one word against the rest finds the same pairs
+    let found: Vec<(usize, usize)> = (0..sequences.len())
+      .filter_map(|b| Some((b, differing_segment(sequences[2], sequences[b])?)))
+      .collect();
+    assert_eq!(found, vec![(3, 1), (4, 0)]);
+  }
+}
@@ -4,6 +4,7 @@ use sqlx::PgPool;
 
 use ipa_backend::config::Config;
 use ipa_backend::db::create_pool;
+use ipa_backend::models::{Dialect, Role, User};
 
 /// Create a test application
 pub async fn create_test_app() -> Result<Router> {
@@ -20,6 +21,10 @@ pub async fn create_test_app() -> Result<Router> {
     meilisearch_url: "http://localhost:7700".to_string(),
     meilisearch_key: "test_key".to_string(),
     allow_dev_google_sso: false,
+    scoring_engine: "mfcc-dtw".to_string(),
+    gop_model_dir: None,
+    scoring_workers: 1,
+    confidence_models: None,
   };
 
   // Create database pool
@@ -43,6 +48,21 @@ pub async fn create_test_app() -> Result<Router> {
   Ok(app)
 }
 
+/// A signed-in user to attach to requests, standing in for the auth middleware
+#[allow(dead_code)]
+pub fn test_user(role: Role) -> User {
+  User {
+    id: uuid::Uuid::new_v4(),
+    email: "learner@example.com".to_string(),
+    pass_hash: String::new(),
+    name: "Test Learner".to_string(),
+    avatar_url: None,
+    dialect: Dialect::GA,
+    role,
+    created_at: chrono::Utc::now(),
+  }
+}
+
 /// Create a test request
 #[allow(dead_code)]
 pub fn create_test_request(uri: &str, method: &str, body: Option<Body>) -> Request<Body> {
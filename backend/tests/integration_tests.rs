@@ -3,6 +3,8 @@ use axum::{
   body::Body,
   http::{Request, StatusCode},
 };
+use ipa_backend::models::{CreateDialectVariant, Dialect, Role};
+use ipa_backend::services::vocab;
 use serde_json::json;
 use tower::Service;
 
@@ -123,6 +125,39 @@ async fn test_pronunciation_scoring() -> Result<()> {
   Ok(())
 }
 
+#[tokio::test]
+async fn test_synthesis_refuses_long_transcriptions() -> Result<()> {
+  let mut app = create_test_app().await?;
+
+  let request = Request::builder()
+    .uri("/api/practice/synthesize?ipa=ˈbɑ&dialect=GA")
+    .method("GET")
+    .body(Body::empty())?;
+  let response = app.call(request).await?;
+  assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+  let request = Request::builder()
+    .uri("/api/practice/synthesize?ipa=ˈbɑ&dialect=GA")
+    .method("GET")
+    .extension(test_user(Role::Learner))
+    .body(Body::empty())?;
+  let response = app.call(request).await?;
+  assert_eq!(response.status(), StatusCode::OK);
+
+  let request = Request::builder()
+    .uri(format!(
+      "/api/practice/synthesize?ipa={}&dialect=GA",
+      "ba".repeat(40)
+    ))
+    .method("GET")
+    .extension(test_user(Role::Learner))
+    .body(Body::empty())?;
+  let response = app.call(request).await?;
+  assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+  Ok(())
+}
+
 #[tokio::test]
 async fn test_usage_tracking() -> Result<()> {
   let mut app = create_test_app().await?;
@@ -179,3 +214,87 @@ async fn test_media_upload() -> Result<()> {
 
   Ok(())
 }
+
+#[tokio::test]
+async fn test_discovery_leaves_curated_minimal_pairs_alone() -> Result<()> {
+  let pool = setup_test_db().await?;
+  let mut words = Vec::new();
+  for (text, ipa) in [("sip", "sɪp"), ("zip", "zɪp")] {
+    let word_id: uuid::Uuid = sqlx::query_scalar(
+      "INSERT INTO words (text, language, difficulty) VALUES ($1, 'en', 1) RETURNING id",
+    )
+    .bind(text)
+    .fetch_one(&pool)
+    .await?;
+    let variant = CreateDialectVariant {
+      dialect: Dialect::GA,
+      ipa: ipa.to_string(),
+      audio_url: None,
+      speaker_id: None,
+      video_url: None,
+    };
+    vocab::create_variant(&mut *pool.acquire().await?, word_id, &variant).await?;
+    words.push(word_id);
+  }
+
+  // An editor's pair applies to every dialect, so discovery must not add a GA copy of it
+  sqlx::query(
+    "INSERT INTO minimal_pairs (word_a_id, word_b_id, phoneme_diff) VALUES ($1, $2, 's/z')",
+  )
+  .bind(words[1])
+  .bind(words[0])
+  .execute(&pool)
+  .await?;
+  vocab::discover_minimal_pairs(&pool, Some(words[0])).await?;
+
+  let sources: Vec<String> = sqlx::query_scalar(
+    "SELECT source FROM minimal_pairs
+           WHERE LEAST(word_a_id, word_b_id) = LEAST($1, $2)
+             AND GREATEST(word_a_id, word_b_id) = GREATEST($1, $2)",
+  )
+  .bind(words[0])
+  .bind(words[1])
+  .fetch_all(&pool)
+  .await?;
+  assert_eq!(sources, vec!["editor"]);
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn test_similar_words_are_nearest_first_and_close_in_length() -> Result<()> {
+  let pool = setup_test_db().await?;
+  let mut words = Vec::new();
+  for (text, ipa) in [
+    ("mat", "mæt"),
+    ("bat", "bæt"),
+    ("mast", "mæst"),
+    ("catamaran", "ˈkætəməræn"),
+  ] {
+    let word_id: uuid::Uuid = sqlx::query_scalar(
+      "INSERT INTO words (text, language, difficulty) VALUES ($1, 'en', 1) RETURNING id",
+    )
+    .bind(text)
+    .fetch_one(&pool)
+    .await?;
+    let variant = CreateDialectVariant {
+      dialect: Dialect::GA,
+      ipa: ipa.to_string(),
+      audio_url: None,
+      speaker_id: None,
+      video_url: None,
+    };
+    vocab::create_variant(&mut *pool.acquire().await?, word_id, &variant).await?;
+    words.push(word_id);
+  }
+
+  let similar = vocab::similar_words(&pool, words[0], Dialect::GA, 10_000).await?;
+  let position = |word_id| similar.iter().position(|s| s.word.id == word_id);
+  assert!(position(words[0]).is_none());
+  assert!(position(words[1]).unwrap() < position(words[2]).unwrap());
+  // Six phonemes longer than "mat", so never compared
+  assert!(position(words[3]).is_none());
+  assert!(similar.windows(2).all(|w| w[0].distance <= w[1].distance));
+
+  Ok(())
+}
@@ -1,4 +1,5 @@
 import apiClient, { type ApiError } from './client';
+import type { Dialect, ScoreRequest, ScoreResponse, VowelChart } from '../types/types';
 
 export interface PracticeSession {
   id: string;
@@ -11,6 +12,11 @@ export interface PracticeSession {
   updated_at: string;
 }
 
+export interface UploadedRecording {
+  recording_id: string;
+  media_url: string;
+}
+
 export interface PracticeFeedback {
   overall_score: number;
   phoneme_scores: Record<string, number>;
@@ -163,4 +169,59 @@ export const practiceApi = {
       throw error as ApiError;
     }
   },
+
+  async uploadRecording(audioBlob: Blob): Promise<UploadedRecording> {
+    try {
+      const formData = new FormData();
+      formData.append('audio', audioBlob);
+
+      const response = await apiClient.post<UploadedRecording>('/media/recordings', formData, {
+        headers: {
+          'Content-Type': 'multipart/form-data',
+        },
+      });
+      return response.data;
+    } catch (error) {
+      throw error as ApiError;
+    }
+  },
+
+  async submitScore(request: ScoreRequest): Promise<ScoreResponse> {
+    try {
+      const response = await apiClient.post<ScoreResponse>('/practice/score', request);
+      return response.data;
+    } catch (error) {
+      throw error as ApiError;
+    }
+  },
+
+  async getScore(recordingId: string): Promise<ScoreResponse> {
+    try {
+      const response = await apiClient.get<ScoreResponse>(`/practice/score/${recordingId}`);
+      return response.data;
+    } catch (error) {
+      throw error as ApiError;
+    }
+  },
+
+  async synthesizeReference(ipa: string, dialect: Dialect): Promise<Blob> {
+    try {
+      const response = await apiClient.get<Blob>('/practice/synthesize', {
+        params: { ipa, dialect },
+        responseType: 'blob',
+      });
+      return response.data;
+    } catch (error) {
+      throw error as ApiError;
+    }
+  },
+
+  async getVowelChart(dialect: Dialect): Promise<VowelChart | null> {
+    try {
+      const response = await apiClient.get<VowelChart | null>(`/practice/vowel-chart/${dialect}`);
+      return response.data;
+    } catch (error) {
+      throw error as ApiError;
+    }
+  },
 };
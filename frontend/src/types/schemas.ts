@@ -21,6 +21,7 @@ export const UserSchema = z.object({
   name: z.string().min(1),
   avatar_url: z.string().url().optional(),
   dialect: DialectSchema,
+  role: z.enum(['learner', 'tutor', 'editor', 'admin']),
   created_at: z.string().datetime(),
 });
 
@@ -43,10 +44,95 @@ export const DialectVariantSchema = z.object({
   created_at: z.string().datetime(),
 });
 
+export const ReferenceSpeakerSchema = z.object({
+  id: z.string().uuid(),
+  name: z.string().min(1),
+  gender: z.enum(['female', 'male', 'other']).optional(),
+  birth_year: z.number().int().min(1901).optional(),
+  region: z.string().optional(),
+  notes: z.string().optional(),
+  created_at: z.string().datetime(),
+});
+
+export const PhonemeBoundarySchema = z.object({
+  phoneme: z.string().min(1),
+  start_ms: z.number().int().min(0),
+  end_ms: z.number().int().min(0),
+});
+
+export const BoundarySourceSchema = z.enum(['aligned', 'editor']);
+
+export const ReferenceRecordingSchema = z.object({
+  id: z.string().uuid(),
+  variant_id: z.string().uuid(),
+  speaker_id: z.string().uuid(),
+  audio_url: z.string().url(),
+  weight: z.number().min(0),
+  phoneme_boundaries: z.array(PhonemeBoundarySchema).optional(),
+  boundaries_source: BoundarySourceSchema.optional(),
+  created_at: z.string().datetime(),
+});
+
+export const ReferenceBoundariesSchema = z.object({
+  reference_recording_id: z.string().uuid(),
+  source: BoundarySourceSchema.optional(),
+  phonemes: z.array(PhonemeBoundarySchema),
+});
+
+export const UpdateReferenceBoundariesSchema = z.object({
+  phonemes: z.array(PhonemeBoundarySchema).min(1),
+});
+
+export const PlaceSchema = z.enum([
+  'bilabial',
+  'labiodental',
+  'dental',
+  'alveolar',
+  'postalveolar',
+  'retroflex',
+  'palatal',
+  'velar',
+  'uvular',
+  'pharyngeal',
+  'glottal',
+  'labial_velar',
+]);
+
+export const MannerSchema = z.enum([
+  'plosive',
+  'nasal',
+  'trill',
+  'tap',
+  'fricative',
+  'lateral_fricative',
+  'affricate',
+  'approximant',
+  'lateral_approximant',
+]);
+
+export const VowelHeightSchema = z.enum([
+  'close',
+  'near_close',
+  'close_mid',
+  'mid',
+  'open_mid',
+  'near_open',
+  'open',
+]);
+
+export const VowelBacknessSchema = z.enum(['front', 'central', 'back']);
+
 export const PhonemeSchema = z.object({
   id: z.string().uuid(),
   symbol: z.string().min(1).max(10),
   description: z.string().optional(),
+  voiced: z.boolean().optional(),
+  place: PlaceSchema.optional(),
+  manner: MannerSchema.optional(),
+  height: VowelHeightSchema.optional(),
+  backness: VowelBacknessSchema.optional(),
+  rounded: z.boolean().optional(),
+  tense: z.boolean().optional(),
 });
 
 export const MinimalPairSchema = z.object({
@@ -54,6 +140,8 @@ export const MinimalPairSchema = z.object({
   word_a_id: z.string().uuid(),
   word_b_id: z.string().uuid(),
   phoneme_diff: z.string().min(1),
+  dialect: DialectSchema.optional(),
+  source: z.enum(['editor', 'discovered']),
   created_at: z.string().datetime(),
 });
 
@@ -71,6 +159,30 @@ export const PracticeSessionSchema = z.object({
   total_ms: z.number().int().min(0),
 });
 
+export const QualityIssueSchema = z.object({
+  reason: z.enum(['no_speech', 'clipping', 'too_quiet', 'noisy', 'dc_offset', 'too_short', 'too_long']),
+  fraction: z.number().min(0).max(1).optional(),
+  level_db: z.number().optional(),
+  snr_db: z.number().optional(),
+  offset: z.number().optional(),
+  speech_ms: z.number().int().min(0).optional(),
+  expected_ms: z.number().int().min(0).optional(),
+});
+
+export const RetryReasonSchema = QualityIssueSchema.extend({
+  message: z.string(),
+});
+
+export const QualityReportSchema = z.object({
+  dc_offset: z.number(),
+  clipped_fraction: z.number().min(0).max(1),
+  level_db: z.number().optional(),
+  snr_db: z.number(),
+  speech_ms: z.number().int().min(0).optional(),
+  expected_ms: z.number().int().min(0).optional(),
+  issues: z.array(QualityIssueSchema),
+});
+
 export const RecordingSchema = z.object({
   id: z.string().uuid(),
   user_id: z.string().uuid(),
@@ -79,15 +191,122 @@ export const RecordingSchema = z.object({
   dialect: DialectSchema,
   media_url: z.string().url(),
   duration_ms: z.number().int().min(0),
+  quality: QualityReportSchema.optional(),
   created_at: z.string().datetime(),
 });
 
+export const DiagnosisSchema = z.discriminatedUnion('kind', [
+  z.object({ kind: z.literal('correct') }),
+  z.object({ kind: z.literal('substituted'), produced: z.string().min(1).optional() }),
+  z.object({ kind: z.literal('deleted') }),
+]);
+
+export const InsertionSchema = z.object({
+  sound: z.string().min(1).optional(),
+  start_ms: z.number().int().min(0),
+  end_ms: z.number().int().min(0),
+});
+
+export const PhonemeScoreSchema = z.object({
+  phoneme: z.string().min(1),
+  start_ms: z.number().int().min(0),
+  end_ms: z.number().int().min(0),
+  score: z.number().min(0).max(100),
+  diagnosis: DiagnosisSchema.optional(),
+  inserted_before: z.array(InsertionSchema).optional(),
+  inserted_after: z.array(InsertionSchema).optional(),
+});
+
+export const SyllableProsodySchema = z.object({
+  start_ms: z.number().int().min(0),
+  end_ms: z.number().int().min(0),
+  energy_db: z.number(),
+  f0_hz: z.number().positive().optional(),
+  prominence: z.number(),
+  expected: z.enum(['primary', 'secondary', 'unstressed']),
+});
+
+export const StressScoreSchema = z.object({
+  expected_syllable: z.number().int().min(0),
+  detected_syllable: z.number().int().min(0),
+  score: z.number().min(0).max(100),
+  syllables: z.array(SyllableProsodySchema),
+});
+
+export const PhonemeDurationSchema = z.object({
+  phoneme: z.string(),
+  learner_ms: z.number().int().min(0),
+  reference_ms: z.number().int().min(0),
+  relative: z.number().min(0),
+  issue: z.enum(['lengthened', 'clipped']).optional(),
+});
+
+export const SyllableDurationSchema = z.object({
+  start_ms: z.number().int().min(0),
+  end_ms: z.number().int().min(0),
+  reference_ms: z.number().int().min(0),
+  relative: z.number().min(0),
+});
+
+export const LengthContrastSchema = z.object({
+  phoneme_index: z.number().int().min(0),
+  expected: z.string(),
+  heard_as: z.string(),
+});
+
+export const SpeakingRateSchema = z.object({
+  learner_syllables_per_sec: z.number().min(0),
+  reference_syllables_per_sec: z.number().min(0),
+  learner_phones_per_sec: z.number().min(0),
+  reference_phones_per_sec: z.number().min(0),
+  tempo: z.number().positive(),
+});
+
+export const DurationScoreSchema = z.object({
+  rate: SpeakingRateSchema,
+  phonemes: z.array(PhonemeDurationSchema),
+  syllables: z.array(SyllableDurationSchema),
+  missing_contrasts: z.array(LengthContrastSchema),
+});
+
+export const FormantsSchema = z.object({
+  f1: z.number().positive(),
+  f2: z.number().positive(),
+  f3: z.number().positive(),
+});
+
+export const VowelFormantsSchema = z.object({
+  phoneme: z.string(),
+  start_ms: z.number().int().min(0),
+  end_ms: z.number().int().min(0),
+  learner: FormantsSchema.optional(),
+  reference: FormantsSchema.optional(),
+});
+
+export const ConfidenceFeaturesSchema = z.object({
+  snr_db: z.number().min(0),
+  clipped_fraction: z.number().min(0).max(1),
+  path_stability: z.number().min(0).max(1),
+  competitor_margin: z.number().min(-1).max(1).optional(),
+  speech_ms: z.number().int().min(0),
+});
+
 export const ScoreSchema = z.object({
   id: z.string().uuid(),
   recording_id: z.string().uuid(),
   overall_pct: z.number().min(0).max(100),
-  per_phoneme: z.record(z.string(), z.number().min(0).max(100)),
+  per_phoneme: z.array(PhonemeScoreSchema),
   latency_ms: z.number().int().min(0),
+  speech_start_ms: z.number().int().min(0).optional(),
+  speech_end_ms: z.number().int().min(0).optional(),
+  stress: StressScoreSchema.optional(),
+  duration: DurationScoreSchema.optional(),
+  vowels: z.array(VowelFormantsSchema),
+  confidence: z.number().min(0).max(1).optional(),
+  confidence_features: ConfidenceFeaturesSchema.optional(),
+  reference_recording_id: z.string().uuid().optional(),
+  engine: z.string().min(1),
+  engine_version: z.string().optional(),
   created_at: z.string().datetime(),
 });
 
@@ -149,6 +368,7 @@ export const CreateDialectVariantSchema = z.object({
   dialect: DialectSchema,
   ipa: z.string().min(1),
   audio_url: z.string().url().optional(),
+  speaker_id: z.string().uuid().optional(),
   video_url: z.string().url().optional(),
 });
 
@@ -164,12 +384,22 @@ export const ScoreRequestSchema = z.object({
   recording_id: z.string().uuid(),
   word_id: z.string().uuid(),
   dialect: DialectSchema,
+  noise_reduction: z.enum(['off', 'spectral_subtraction', 'wiener']).optional(),
 });
 
 export const ScoreResponseSchema = z.object({
-  status: z.enum(['pending', 'processing', 'completed', 'failed']),
+  status: z.enum(['pending', 'processing', 'completed', 'retry', 'failed']),
   overall_pct: z.number().min(0).max(100).optional(),
-  per_phoneme: z.record(z.string(), z.number().min(0).max(100)).optional(),
+  per_phoneme: z.array(PhonemeScoreSchema).optional(),
+  speech_start_ms: z.number().int().min(0).optional(),
+  speech_end_ms: z.number().int().min(0).optional(),
+  stress: StressScoreSchema.optional(),
+  duration: DurationScoreSchema.optional(),
+  vowels: z.array(VowelFormantsSchema).optional(),
+  confidence: z.number().min(0).max(1).optional(),
+  engine: z.string().optional(),
+  engine_version: z.string().optional(),
+  retry_reasons: z.array(RetryReasonSchema).optional(),
   job_id: z.string().optional(),
 });
 
@@ -203,7 +433,7 @@ export const WebSocketMessageSchema = z.object({
 export const ScoreCompletedEventSchema = z.object({
   recording_id: z.string().uuid(),
   overall_pct: z.number().min(0).max(100),
-  per_phoneme: z.record(z.string(), z.number().min(0).max(100)),
+  per_phoneme: z.array(PhonemeScoreSchema),
 });
 
 export const RoomUserJoinedEventSchema = z.object({
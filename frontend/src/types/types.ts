@@ -18,9 +18,13 @@ export interface User {
   name: string;
   avatar_url?: string;
   dialect: Dialect;
+  role: Role;
   created_at: string;
 }
 
+// Tutors review learners' recordings; editors curate words and reference audio
+export type Role = 'learner' | 'tutor' | 'editor' | 'admin';
+
 export interface Word {
   id: string;
   text: string;
@@ -40,10 +44,104 @@ export interface DialectVariant {
   created_at: string;
 }
 
+export interface ReferenceSpeaker {
+  id: string;
+  name: string;
+  gender?: 'female' | 'male' | 'other';
+  birth_year?: number;
+  region?: string;
+  notes?: string;
+  created_at: string;
+}
+
+export interface ReferenceRecording {
+  id: string;
+  variant_id: string;
+  speaker_id: string;
+  audio_url: string;
+  weight: number;
+  phoneme_boundaries?: PhonemeBoundary[];
+  boundaries_source?: BoundarySource;
+  created_at: string;
+}
+
+export type BoundarySource = 'aligned' | 'editor';
+
+export interface PhonemeBoundary {
+  phoneme: string;
+  start_ms: number;
+  end_ms: number;
+}
+
+export interface ReferenceBoundaries {
+  reference_recording_id: string;
+  source?: BoundarySource;
+  phonemes: PhonemeBoundary[];
+}
+
+export interface UpdateReferenceBoundaries {
+  phonemes: PhonemeBoundary[];
+}
+
+export type Place =
+  | 'bilabial'
+  | 'labiodental'
+  | 'dental'
+  | 'alveolar'
+  | 'postalveolar'
+  | 'retroflex'
+  | 'palatal'
+  | 'velar'
+  | 'uvular'
+  | 'pharyngeal'
+  | 'glottal'
+  | 'labial_velar';
+
+export type Manner =
+  | 'plosive'
+  | 'nasal'
+  | 'trill'
+  | 'tap'
+  | 'fricative'
+  | 'lateral_fricative'
+  | 'affricate'
+  | 'approximant'
+  | 'lateral_approximant';
+
+export type VowelHeight =
+  | 'close'
+  | 'near_close'
+  | 'close_mid'
+  | 'mid'
+  | 'open_mid'
+  | 'near_open'
+  | 'open';
+
+export type VowelBackness = 'front' | 'central' | 'back';
+
+// Consonants have voiced, place and manner; vowels have height, backness, rounded and tense
 export interface Phoneme {
   id: string;
   symbol: string;
   description?: string;
+  voiced?: boolean;
+  place?: Place;
+  manner?: Manner;
+  height?: VowelHeight;
+  backness?: VowelBackness;
+  rounded?: boolean;
+  tense?: boolean;
+}
+
+export interface SimilarWord {
+  id: string;
+  text: string;
+  language: string;
+  pos?: PartOfSpeech;
+  difficulty: number;
+  created_at: string;
+  ipa: string;
+  distance: number;
 }
 
 export interface WordWithVariants {
@@ -62,7 +160,11 @@ export interface MinimalPair {
   id: string;
   word_a_id: string;
   word_b_id: string;
+  // Contrasting segments, word A's first, e.g. "θ/s"
   phoneme_diff: string;
+  // Absent for curated pairs, which hold in every dialect
+  dialect?: Dialect;
+  source: 'editor' | 'discovered';
   created_at: string;
 }
 
@@ -82,15 +184,169 @@ export interface Recording {
   dialect: Dialect;
   media_url: string;
   duration_ms: number;
+  quality?: QualityReport;
   created_at: string;
 }
 
+export type QualityIssueReason =
+  | 'no_speech'
+  | 'clipping'
+  | 'too_quiet'
+  | 'noisy'
+  | 'dc_offset'
+  | 'too_short'
+  | 'too_long';
+
+export interface QualityIssue {
+  reason: QualityIssueReason;
+  fraction?: number;
+  level_db?: number;
+  snr_db?: number;
+  offset?: number;
+  speech_ms?: number;
+  expected_ms?: number;
+}
+
+export interface RetryReason extends QualityIssue {
+  message: string;
+}
+
+export interface QualityReport {
+  dc_offset: number;
+  clipped_fraction: number;
+  level_db?: number;
+  snr_db: number;
+  speech_ms?: number;
+  expected_ms?: number;
+  issues: QualityIssue[];
+}
+
+export type Diagnosis =
+  | { kind: 'correct' }
+  | { kind: 'substituted'; produced?: string }
+  | { kind: 'deleted' };
+
+export interface Insertion {
+  sound?: string;
+  start_ms: number;
+  end_ms: number;
+}
+
+export interface PhonemeScore {
+  phoneme: string;
+  start_ms: number;
+  end_ms: number;
+  score: number;
+  diagnosis?: Diagnosis;
+  inserted_before?: Insertion[];
+  inserted_after?: Insertion[];
+}
+
+export type Stress = 'primary' | 'secondary' | 'unstressed';
+
+export interface SyllableProsody {
+  start_ms: number;
+  end_ms: number;
+  energy_db: number;
+  f0_hz?: number;
+  prominence: number;
+  expected: Stress;
+}
+
+export interface StressScore {
+  expected_syllable: number;
+  detected_syllable: number;
+  score: number;
+  syllables: SyllableProsody[];
+}
+
+export type LengthIssue = 'lengthened' | 'clipped';
+
+export interface PhonemeDuration {
+  phoneme: string;
+  learner_ms: number;
+  reference_ms: number;
+  relative: number;
+  issue?: LengthIssue;
+}
+
+export interface SyllableDuration {
+  start_ms: number;
+  end_ms: number;
+  reference_ms: number;
+  relative: number;
+}
+
+export interface LengthContrast {
+  phoneme_index: number;
+  expected: string;
+  heard_as: string;
+}
+
+export interface SpeakingRate {
+  learner_syllables_per_sec: number;
+  reference_syllables_per_sec: number;
+  learner_phones_per_sec: number;
+  reference_phones_per_sec: number;
+  tempo: number;
+}
+
+export interface DurationScore {
+  rate: SpeakingRate;
+  phonemes: PhonemeDuration[];
+  syllables: SyllableDuration[];
+  missing_contrasts: LengthContrast[];
+}
+
+export interface Formants {
+  f1: number;
+  f2: number;
+  f3: number;
+}
+
+export interface VowelFormants {
+  phoneme: string;
+  start_ms: number;
+  end_ms: number;
+  learner?: Formants;
+  reference?: Formants;
+}
+
+export interface VowelTarget extends Formants {
+  phoneme: string;
+}
+
+export interface VowelChart {
+  dialect: Dialect;
+  source: string;
+  male: VowelTarget[];
+  female: VowelTarget[];
+}
+
+export interface ConfidenceFeatures {
+  snr_db: number;
+  clipped_fraction: number;
+  path_stability: number;
+  competitor_margin?: number;
+  speech_ms: number;
+}
+
 export interface Score {
   id: string;
   recording_id: string;
   overall_pct: number;
-  per_phoneme: Record<string, number>;
+  per_phoneme: PhonemeScore[];
   latency_ms: number;
+  speech_start_ms?: number;
+  speech_end_ms?: number;
+  stress?: StressScore;
+  duration?: DurationScore;
+  vowels: VowelFormants[];
+  confidence?: number;
+  confidence_features?: ConfidenceFeatures;
+  reference_recording_id?: string;
+  engine: string;
+  engine_version?: string;
   created_at: string;
 }
 
@@ -160,6 +416,8 @@ export interface CreateDialectVariant {
   dialect: Dialect;
   ipa: string;
   audio_url?: string;
+  // Speaker of audio_url; unattributed when absent
+  speaker_id?: string;
   video_url?: string;
 }
 
@@ -167,12 +425,24 @@ export interface ScoreRequest {
   recording_id: string;
   word_id: string;
   dialect: Dialect;
+  noise_reduction?: NoiseReduction;
 }
 
+export type NoiseReduction = 'off' | 'spectral_subtraction' | 'wiener';
+
 export interface ScoreResponse {
-  status: 'pending' | 'processing' | 'completed' | 'failed';
+  status: 'pending' | 'processing' | 'completed' | 'retry' | 'failed';
   overall_pct?: number;
-  per_phoneme?: Record<string, number>;
+  per_phoneme?: PhonemeScore[];
+  speech_start_ms?: number;
+  speech_end_ms?: number;
+  stress?: StressScore;
+  duration?: DurationScore;
+  vowels?: VowelFormants[];
+  confidence?: number;
+  engine?: string;
+  engine_version?: string;
+  retry_reasons?: RetryReason[];
   job_id?: string;
 }
 
@@ -206,7 +476,7 @@ export interface WebSocketMessage {
 export interface ScoreCompletedEvent {
   recording_id: string;
   overall_pct: number;
-  per_phoneme: Record<string, number>;
+  per_phoneme: PhonemeScore[];
 }
 
 export interface RoomUserJoinedEvent {